@@ -0,0 +1,71 @@
+//! Benchmarks for rail-fence-cipher
+//!
+//! Run with `cargo bench`.  The ASCII and non-ASCII inputs hold the same
+//! number of characters, so the difference between them is the cost of
+//! working with `char`s instead of bytes.  The output buffers are sized up
+//! front, and `tests/allocations.rs` checks that they are never grown.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rail_fence_cipher::RailFence;
+
+/// The number of characters in each benchmark message
+const LENGTH: usize = 64 * 1024;
+
+/// Messages of LENGTH characters, named by their contents
+fn inputs() -> Vec<(&'static str, String)> {
+    vec![
+        ("ascii", "WEAREDISCOVEREDFLEEATONCE".chars().cycle().take(LENGTH).collect()),
+        ("non-ascii", "古池蛙飛び込む水の音".chars().cycle().take(LENGTH).collect()),
+    ]
+}
+
+fn encode(c: &mut Criterion) {
+    let rail_fence = RailFence::new(5);
+    let mut group = c.benchmark_group("encode");
+    group.throughput(Throughput::Elements(LENGTH as u64));
+    for (name, text) in inputs() {
+        group.bench_with_input(BenchmarkId::from_parameter(name), &text, |b, text| {
+            b.iter(|| rail_fence.encode(text))
+        });
+    }
+    group.finish();
+}
+
+fn decode(c: &mut Criterion) {
+    let rail_fence = RailFence::new(5);
+    let mut group = c.benchmark_group("decode");
+    group.throughput(Throughput::Elements(LENGTH as u64));
+    for (name, text) in inputs() {
+        let cipher = rail_fence.encode(&text);
+        group.bench_with_input(BenchmarkId::from_parameter(name), &cipher, |b, cipher| {
+            b.iter(|| rail_fence.decode(cipher))
+        });
+    }
+    group.finish();
+}
+
+fn encode_rails(c: &mut Criterion) {
+    let rail_fence = RailFence::new(5);
+    let mut group = c.benchmark_group("encode_rails");
+    group.throughput(Throughput::Elements(LENGTH as u64));
+    for (name, text) in inputs() {
+        group.bench_with_input(BenchmarkId::from_parameter(name), &text, |b, text| {
+            b.iter(|| rail_fence.encode_rails(text))
+        });
+    }
+    group.finish();
+}
+
+fn encode_parallel(c: &mut Criterion) {
+    let rail_fence = RailFence::new(5);
+    // Blocks are tens of thousands of characters, so use a longer message
+    let text: String = "WEAREDISCOVEREDFLEEATONCE".chars().cycle().take(64 * LENGTH).collect();
+    let mut group = c.benchmark_group("encode_parallel");
+    group.throughput(Throughput::Elements(text.len() as u64));
+    group.bench_function("serial", |b| b.iter(|| rail_fence.encode(&text)));
+    group.bench_function("parallel", |b| b.iter(|| rail_fence.encode_parallel(&text)));
+    group.finish();
+}
+
+criterion_group!(benches, encode, decode, encode_rails, encode_parallel);
+criterion_main!(benches);
@@ -0,0 +1,65 @@
+#ifndef RAIL_FENCE_CIPHER_H
+#define RAIL_FENCE_CIPHER_H
+
+/* Generated with cbindgen from src/ffi.rs; do not edit by hand. */
+
+#include <stdint.h>
+
+typedef struct RailFence RailFence;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Create a fence with the given number of rails
+ *
+ * Returns null if `rails` is 0.
+ */
+RailFence *rail_fence_new(uint32_t rails);
+
+/**
+ * Release a fence from `rail_fence_new`
+ *
+ * # Safety
+ * `fence` must be null or a pointer returned by `rail_fence_new` that has
+ * not already been freed.
+ */
+void rail_fence_free(RailFence *fence);
+
+/**
+ * Encode a NUL-terminated UTF-8 message
+ *
+ * Returns null if either pointer is null or `text` is not UTF-8.
+ *
+ * # Safety
+ * `fence` must be null or a live pointer from `rail_fence_new`, and `text`
+ * must be null or point to a NUL-terminated string.
+ */
+char *rail_fence_encode(const RailFence *fence, const char *text);
+
+/**
+ * Decode a NUL-terminated UTF-8 cipher-text
+ *
+ * Returns null if either pointer is null or `cipher` is not UTF-8.
+ *
+ * # Safety
+ * `fence` must be null or a live pointer from `rail_fence_new`, and
+ * `cipher` must be null or point to a NUL-terminated string.
+ */
+char *rail_fence_decode(const RailFence *fence, const char *cipher);
+
+/**
+ * Release a string returned by `rail_fence_encode` or `rail_fence_decode`
+ *
+ * # Safety
+ * `s` must be null or a string returned by this library that has not
+ * already been freed.
+ */
+void rail_fence_string_free(char *s);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RAIL_FENCE_CIPHER_H */
@@ -0,0 +1,8 @@
+//! The shared library maturin packages as the `rail_fence_cipher` Python
+//! module
+//!
+//! The module itself is defined by the `python` feature of the main crate.
+//! Linking it into this `cdylib` exports its entry point, which the main
+//! crate cannot build as itself without losing `no_std` support.
+
+extern crate rail_fence_cipher;
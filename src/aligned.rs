@@ -0,0 +1,70 @@
+//! Rectangular cipher-text with every rail the same length
+//!
+//! The zig-zag puts a different number of characters on each rail, and the
+//! end rails only get one character per period.  Aligned mode writes out
+//! the whole grid instead, with a fill character in every cell that the
+//! zig-zag skips, so each rail is as long as the message and the output is
+//! `rails` times longer.  Decoding reads only the cells on the zig-zag, so
+//! the fill character may also appear in the message.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{RailFence, RailFenceError};
+
+impl RailFence {
+    /// Encode the message with the gaps in the fence filled in
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    /// * `fill`    The character written in the cells off the zig-zag
+    ///
+    /// # Returns
+    /// The rails of the filled grid, one after another
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3);
+    /// assert_eq!("E...C..X.R.I..E...", cipher.encode_aligned("EXERCI", '.'))
+    /// ```
+    pub fn encode_aligned(&self, text: &str, fill: char) -> String {
+        self.grid(text)
+            .into_iter()
+            .flatten()
+            .map(|cell| cell.unwrap_or(fill))
+            .collect()
+    }
+
+    /// Decode a message produced by `encode_aligned`
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  The cipher-text of the filled grid
+    ///
+    /// # Returns
+    /// The clear-text message, or `RailFenceError::UnalignedLength` if the
+    /// cipher-text cannot be split into equal rails
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3);
+    /// assert_eq!(Ok(String::from("EXERCI")), cipher.decode_aligned("E...C..X.R.I..E..."))
+    /// ```
+    pub fn decode_aligned(&self, cipher: &str) -> Result<String, RailFenceError> {
+        let cells: Vec<char> = cipher.chars().collect();
+        let rails = self.rails as usize;
+        if rails == 0 || !cells.len().is_multiple_of(rails) {
+            return Err(RailFenceError::UnalignedLength {
+                length: cells.len(),
+                rails,
+            });
+        }
+        let width = cells.len() / rails;
+        Ok((0..width)
+            .map(|column| cells[self.rail_at(column) * width + column])
+            .collect())
+    }
+}
@@ -0,0 +1,291 @@
+//! Streaming bytes through the fence with tokio
+//!
+//! These are the asynchronous forms of `RailFenceWriter` and
+//! `RailFenceReader`, and they cut a stream into the same blocks, so
+//! cipher-text written by one kind of writer is read back by either kind of
+//! reader with the same fence and block size.  The inner writer or reader
+//! must be `Unpin`; pin it in a `Box` if it is not.
+//!
+//! An asynchronous writer cannot write from `Drop`, so the last partial
+//! block is only written by `shutdown`.  A writer dropped without being
+//! shut down loses it.
+
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::io;
+use std::vec;
+use std::vec::Vec;
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::wipe::wipe;
+use crate::RailFence;
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Encodes everything written to it, one block at a time, without blocking
+///
+/// A block is only encoded once it is full, or when the writer is shut
+/// down, so `flush` passes on the blocks written so far but keeps a partly
+/// filled block back.
+#[derive(Debug)]
+pub struct AsyncRailFenceWriter<W: AsyncWrite + Unpin> {
+    fence: RailFence,
+    inner: Option<W>,
+    block_len: usize,
+    block: Vec<u8>,
+    /// The encoded block being written out
+    encoded: Vec<u8>,
+    /// How much of `encoded` the inner writer has taken
+    written: usize,
+}
+
+/// Decodes a stream written by an `AsyncRailFenceWriter` or a
+/// `RailFenceWriter`, one block at a time, without blocking
+#[derive(Debug)]
+pub struct AsyncRailFenceReader<R: AsyncRead + Unpin> {
+    fence: RailFence,
+    inner: Option<R>,
+    block_len: usize,
+    /// The cipher-text of the block being read in
+    cipher: Vec<u8>,
+    /// How much of `cipher` has been read
+    filled: usize,
+    block: Vec<u8>,
+    position: usize,
+}
+
+impl RailFence {
+    /// Wrap an asynchronous writer so that everything written to it is
+    /// encoded
+    ///
+    /// # Arguments
+    ///
+    /// * `inner`           Where the cipher-text is written
+    /// * `block_periods`   The number of periods in each block
+    ///
+    /// # Returns
+    /// A writer that encodes blocks of `block_periods * period()` bytes,
+    /// exactly as `writer` does
+    ///
+    /// # Panics
+    /// If `block_periods` is 0, or the fence has a custom schedule and so
+    /// no period
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// let cipher = RailFence::new(3);
+    /// let mut writer = cipher.async_writer(Vec::new(), 2);
+    /// writer.write_all(b"WEAREDISCOVERED").await.unwrap();
+    /// writer.shutdown().await.unwrap();
+    /// let encoded = writer.into_inner();
+    /// assert_eq!(cipher.encode("WEAREDIS") + &cipher.encode("COVERED"), String::from_utf8(encoded.clone()).unwrap());
+    ///
+    /// let mut decoded = Vec::new();
+    /// cipher.async_reader(&encoded[..], 2).read_to_end(&mut decoded).await.unwrap();
+    /// assert_eq!(b"WEAREDISCOVERED".to_vec(), decoded);
+    /// # });
+    /// ```
+    pub fn async_writer<W: AsyncWrite + Unpin>(&self, inner: W, block_periods: usize) -> AsyncRailFenceWriter<W> {
+        let block_len = self.stream_block_len(block_periods);
+        AsyncRailFenceWriter {
+            fence: self.clone(),
+            inner: Some(inner),
+            block_len,
+            block: Vec::with_capacity(block_len),
+            encoded: Vec::new(),
+            written: 0,
+        }
+    }
+
+    /// Wrap an asynchronous reader of cipher-text from `async_writer` or
+    /// `writer` so that reading from it gives the clear-text
+    ///
+    /// # Arguments
+    ///
+    /// * `inner`           Where the cipher-text is read from
+    /// * `block_periods`   The number of periods in each block, as given
+    ///   to the writer
+    ///
+    /// # Panics
+    /// If `block_periods` is 0, or the fence has a custom schedule and so
+    /// no period
+    pub fn async_reader<R: AsyncRead + Unpin>(&self, inner: R, block_periods: usize) -> AsyncRailFenceReader<R> {
+        let block_len = self.stream_block_len(block_periods);
+        AsyncRailFenceReader {
+            fence: self.clone(),
+            inner: Some(inner),
+            block_len,
+            cipher: vec![0; block_len],
+            filled: 0,
+            block: Vec::with_capacity(block_len),
+            position: 0,
+        }
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncRailFenceWriter<W> {
+    /// The number of bytes in each block
+    pub fn block_len(&self) -> usize {
+        self.block_len
+    }
+
+    /// The writer the cipher-text goes to
+    pub fn get_ref(&self) -> &W {
+        self.inner.as_ref().expect("the writer is only taken by into_inner")
+    }
+
+    /// Give back the inner writer, dropping anything not yet written to it
+    ///
+    /// Shut the writer down first to write the last partial block.
+    pub fn into_inner(mut self) -> W {
+        self.inner.take().expect("the writer is only taken by into_inner")
+    }
+
+    /// Encode the buffered block, if there is one, and write all of it to
+    /// the inner writer
+    fn poll_write_block(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if self.encoded.is_empty() && !self.block.is_empty() {
+            self.encoded = self.fence.encode_bytes(&self.block);
+            self.written = 0;
+            wipe(&mut self.block);
+            self.block.clear();
+        }
+        let inner = self.inner.as_mut().expect("the writer is only taken by into_inner");
+        while self.written < self.encoded.len() {
+            match Pin::new(&mut *inner).poll_write(cx, &self.encoded[self.written..]) {
+                Poll::Ready(Ok(0)) => return Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+                Poll::Ready(Ok(written)) => self.written += written,
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        wipe(&mut self.encoded);
+        self.encoded.clear();
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for AsyncRailFenceWriter<W> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, data: &[u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.block.len() == this.block_len || !this.encoded.is_empty() {
+            match this.poll_write_block(cx) {
+                Poll::Ready(Ok(())) => {}
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        let taken = data.len().min(this.block_len - this.block.len());
+        this.block.extend_from_slice(&data[..taken]);
+        Poll::Ready(Ok(taken))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if this.block.len() == this.block_len || !this.encoded.is_empty() {
+            match this.poll_write_block(cx) {
+                Poll::Ready(Ok(())) => {}
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        Pin::new(this.inner.as_mut().expect("the writer is only taken by into_inner")).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        match this.poll_write_block(cx) {
+            Poll::Ready(Ok(())) => {}
+            other => return other,
+        }
+        Pin::new(this.inner.as_mut().expect("the writer is only taken by into_inner")).poll_shutdown(cx)
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRailFenceReader<R> {
+    /// The number of bytes in each block
+    pub fn block_len(&self) -> usize {
+        self.block_len
+    }
+
+    /// Give back the inner reader, dropping any decoded bytes not yet read
+    pub fn into_inner(mut self) -> R {
+        self.inner.take().expect("the reader is only taken by into_inner")
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for AsyncRailFenceReader<R> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, out: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if this.position == this.block.len() && out.remaining() > 0 {
+            let inner = this.inner.as_mut().expect("the reader is only taken by into_inner");
+            // Fill the block, keeping what has arrived if the inner reader
+            // is not ready
+            while this.filled < this.block_len {
+                let mut space = ReadBuf::new(&mut this.cipher[this.filled..]);
+                match Pin::new(&mut *inner).poll_read(cx, &mut space) {
+                    Poll::Ready(Ok(())) if space.filled().is_empty() => break,
+                    Poll::Ready(Ok(())) => this.filled += space.filled().len(),
+                    Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+            wipe(&mut this.block);
+            this.block = this.fence.decode_bytes(&this.cipher[..this.filled]);
+            wipe(&mut this.cipher[..this.filled]);
+            this.filled = 0;
+            this.position = 0;
+        }
+        let read = out.remaining().min(this.block.len() - this.position);
+        out.put_slice(&this.block[this.position..this.position + read]);
+        this.position += read;
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<W: AsyncWrite + Unpin> Zeroize for AsyncRailFenceWriter<W> {
+    /// Wipe the partly filled block and any encoded block not yet written,
+    /// neither of which is then written
+    fn zeroize(&mut self) {
+        self.block.zeroize();
+        self.encoded.zeroize();
+        self.written = 0;
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<W: AsyncWrite + Unpin> Drop for AsyncRailFenceWriter<W> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<W: AsyncWrite + Unpin> ZeroizeOnDrop for AsyncRailFenceWriter<W> {}
+
+#[cfg(feature = "zeroize")]
+impl<R: AsyncRead + Unpin> Zeroize for AsyncRailFenceReader<R> {
+    /// Wipe the decoded bytes not yet read and the cipher-text of the
+    /// block being read in, both of which are then skipped
+    fn zeroize(&mut self) {
+        self.block.zeroize();
+        self.position = 0;
+        self.cipher.as_mut_slice().zeroize();
+        self.filled = 0;
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<R: AsyncRead + Unpin> Drop for AsyncRailFenceReader<R> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<R: AsyncRead + Unpin> ZeroizeOnDrop for AsyncRailFenceReader<R> {}
@@ -0,0 +1,206 @@
+//! Encoding many independent messages at once
+//!
+//! With the `parallel` feature the messages are spread across a thread pool,
+//! otherwise they are encoded one after another.  Either way the output is
+//! the same as calling `encode` on each message in turn.
+//!
+//! Records are different: they are transposed together as one message, so
+//! characters move between records.  Multiplexed messages are transposed
+//! together too, with their characters interleaved.
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::wipe::wipe;
+use crate::RailFence;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+impl RailFence {
+    /// Encode each message in a batch
+    ///
+    /// # Arguments
+    ///
+    /// * `texts`   The clear-text messages to encode
+    ///
+    /// # Returns
+    /// The cipher-text of each message, in the same order
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// let batch = cipher.encode_batch(&[String::from("RUSTISGREAT"), String::from("EXERCISES")]);
+    /// assert_eq!(vec!["RGUSRSIETTA", "ESXIEECSR"], batch)
+    /// ```
+    pub fn encode_batch(&self, texts: &[String]) -> Vec<String> {
+        #[cfg(feature = "parallel")]
+        let encoded = texts.par_iter().map(|text| self.encode(text)).collect();
+        #[cfg(not(feature = "parallel"))]
+        let encoded = texts.iter().map(|text| self.encode(text)).collect();
+        encoded
+    }
+
+    /// Encode a list of records as one message
+    ///
+    /// The records are normalized, joined and transposed as a whole, and
+    /// the cipher-text is cut back into pieces with the same character
+    /// counts as the normalized records.  The shape of the list therefore
+    /// records where each record ends, with no separator that could clash
+    /// with the data.  Period padding and grouping would not fit that
+    /// shape, so they are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `records` The clear-text records to encode
+    ///
+    /// # Returns
+    /// One piece of cipher-text per record, each as long as its normalized
+    /// record
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// let records = cipher.encode_records(&[String::from("RUST"), String::from("ISGREAT")]);
+    /// assert_eq!(vec!["RGUS", "RSIETTA"], records);
+    /// ```
+    pub fn encode_records(&self, records: &[String]) -> Vec<String> {
+        self.assert_rails();
+        let mut normalized: Vec<String> = records.iter().map(|record| self.normalize(record).into_owned()).collect();
+        let mut joined = normalized.concat();
+        let mut encoded = self.encode_normalized(&joined);
+        let pieces = split_like(&encoded, &normalized);
+        wipe(&mut encoded);
+        wipe(&mut joined);
+        normalized.iter_mut().for_each(wipe);
+        pieces
+    }
+
+    /// Decode records encoded with `encode_records`
+    ///
+    /// # Arguments
+    ///
+    /// * `records` The cipher-text pieces, in order
+    ///
+    /// # Returns
+    /// The clear-text records
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// let records = cipher.decode_records(&[String::from("RGUS"), String::from("RSIETTA")]);
+    /// assert_eq!(vec!["RUST", "ISGREAT"], records);
+    /// ```
+    pub fn decode_records(&self, records: &[String]) -> Vec<String> {
+        self.assert_rails();
+        let mut decoded = self.decode_normalized(&records.concat());
+        let pieces = split_like(&decoded, records);
+        wipe(&mut decoded);
+        pieces
+    }
+
+    /// Interleave several messages and encode them as one
+    ///
+    /// The first character of each message is taken in turn, then the
+    /// second of each, and so on, and the result is encoded.  Nothing marks
+    /// where one message's characters are, so decoding deals them back out
+    /// in turn.  That only works when the messages are all the same length,
+    /// except that some at the end may be one character shorter, so pad
+    /// them first if they are not.
+    ///
+    /// # Arguments
+    ///
+    /// * `messages`    The clear-text messages to encode
+    ///
+    /// # Returns
+    /// The cipher-text of all the messages together
+    ///
+    /// # Panics
+    /// If the message lengths cannot be dealt back out
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3);
+    /// let multiplexed = cipher.encode_multiplex(&["RUS", "TIS", "GR"]);
+    /// assert_eq!(vec!["RUS", "TIS", "GR"], cipher.decode_multiplex(&multiplexed, 3));
+    /// ```
+    pub fn encode_multiplex(&self, messages: &[&str]) -> String {
+        let lengths: Vec<usize> = messages.iter().map(|message| message.chars().count()).collect();
+        let longest = lengths.first().copied().unwrap_or(0);
+        assert!(
+            lengths.windows(2).all(|pair| pair[0] >= pair[1]) && lengths.iter().all(|&len| len + 1 >= longest),
+            "multiplexed messages {:?} cannot be dealt back out in turn",
+            lengths
+        );
+        let mut chars: Vec<_> = messages.iter().map(|message| message.chars()).collect();
+        let mut interleaved = String::new();
+        for _ in 0..longest {
+            interleaved.extend(chars.iter_mut().filter_map(|message| message.next()));
+        }
+        self.encode(interleaved)
+    }
+
+    /// Decode messages multiplexed by `encode_multiplex`
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  The cipher-text of the multiplexed messages
+    /// * `n`       How many messages there are
+    ///
+    /// # Returns
+    /// The clear-text messages, in order.  When the characters do not deal
+    /// out evenly the last messages are one character shorter.
+    ///
+    /// # Panics
+    /// If `n` is 0
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// assert_eq!(vec!["RSIGET", "UTSRA"], cipher.decode_multiplex("RGUSRSIETTA", 2));
+    /// ```
+    pub fn decode_multiplex(&self, cipher: &str, n: usize) -> Vec<String> {
+        assert!(n > 0, "there must be at least one multiplexed message");
+        let mut messages = vec![String::new(); n];
+        for (i, c) in self.decode(cipher).chars().enumerate() {
+            messages[i % n].push(c);
+        }
+        messages
+    }
+
+    /// Pair sample messages with their cipher-text under this fence
+    ///
+    /// This is meant for generating test vectors to publish or commit, so
+    /// other implementations can check that they agree with this one.
+    ///
+    /// # Arguments
+    ///
+    /// * `samples` The clear-text messages
+    ///
+    /// # Returns
+    /// A `(clear-text, cipher-text)` pair for each sample, in order
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let vectors = RailFence::new(4).test_vectors(&["RUSTISGREAT"]);
+    /// assert_eq!(vec![(String::from("RUSTISGREAT"), String::from("RGUSRSIETTA"))], vectors);
+    /// ```
+    pub fn test_vectors(&self, samples: &[&str]) -> Vec<(String, String)> {
+        samples.iter().map(|sample| (sample.to_string(), self.encode(sample))).collect()
+    }
+}
+
+/// Cut text into pieces with the character counts of the given records
+fn split_like(text: &str, records: &[String]) -> Vec<String> {
+    let mut chars = text.chars();
+    records
+        .iter()
+        .map(|record| chars.by_ref().take(record.chars().count()).collect())
+        .collect()
+}
@@ -0,0 +1,181 @@
+//! Command line interface for the rail fence cipher
+//!
+//! Built with the `cli` feature:
+//!
+//! ```shell
+//! $ cargo run --features cli -- show --rails 4 "RUSTISGREAT"
+//! $ echo RUSTISGREAT | cargo run --features cli -- encode --rails 4
+//! $ cargo run --features cli -- crack --in secret.txt --out guesses.txt
+//! ```
+//!
+//! `encode`, `decode` and `crack` read standard input unless given `--in`
+//! and write standard output unless given `--out`.  One trailing newline is
+//! dropped from the input and one is added to the output, so they work on
+//! lines of text.
+
+use clap::{Args, Parser, Subcommand};
+use rail_fence_cipher::RailFence;
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::PathBuf;
+use std::process;
+
+/// ANSI foreground colours given to the rails in turn
+const RAIL_COLORS: [&str; 6] = ["31", "32", "33", "34", "35", "36"];
+
+#[derive(Parser)]
+#[command(name = "rail-fence", version, about = "Rail fence cipher tools")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Draw a message on the fence
+    Show {
+        /// The number of rails
+        #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+        rails: u32,
+        /// Colour each rail differently when writing to a terminal
+        #[arg(long)]
+        color: bool,
+        /// The clear-text to draw
+        text: String,
+    },
+    /// Encode a message
+    Encode {
+        #[command(flatten)]
+        key: Key,
+        #[command(flatten)]
+        files: Files,
+    },
+    /// Decode a message
+    Decode {
+        #[command(flatten)]
+        key: Key,
+        #[command(flatten)]
+        files: Files,
+    },
+    /// Guess the rail count of an English message and decode it
+    Crack {
+        /// The largest rail count to try
+        #[arg(long, default_value_t = 20, value_parser = clap::value_parser!(u32).range(1..))]
+        max_rails: u32,
+        /// How many of the best guesses to show
+        #[arg(long, default_value_t = 3)]
+        top: usize,
+        #[command(flatten)]
+        files: Files,
+    },
+}
+
+/// The settings a message is encoded and decoded with
+#[derive(Args)]
+struct Key {
+    /// The number of rails
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    rails: u32,
+    /// The number of zig-zag steps to skip before the first character
+    #[arg(long, default_value_t = 0)]
+    offset: usize,
+}
+
+impl Key {
+    fn fence(&self) -> RailFence {
+        RailFence::new(self.rails).with_offset(self.offset)
+    }
+}
+
+/// Where a command reads its input and writes its output
+#[derive(Args)]
+struct Files {
+    /// Read the input from this file instead of standard input
+    #[arg(long = "in")]
+    input: Option<PathBuf>,
+    /// Write the output to this file instead of standard output
+    #[arg(long = "out")]
+    output: Option<PathBuf>,
+}
+
+impl Files {
+    /// Read the whole input, without its trailing newline
+    fn read(&self) -> io::Result<String> {
+        let mut text = match &self.input {
+            Some(path) => fs::read_to_string(path)?,
+            None => {
+                let mut text = String::new();
+                io::stdin().read_to_string(&mut text)?;
+                text
+            }
+        };
+        if text.ends_with('\n') {
+            text.pop();
+            if text.ends_with('\r') {
+                text.pop();
+            }
+        }
+        Ok(text)
+    }
+
+    /// Write the output, followed by a newline
+    fn write(&self, text: &str) -> io::Result<()> {
+        match &self.output {
+            Some(path) => fs::write(path, format!("{}\n", text)),
+            None => writeln!(io::stdout().lock(), "{}", text),
+        }
+    }
+}
+
+fn main() {
+    if let Err(error) = run(Cli::parse().command) {
+        eprintln!("rail-fence: {}", error);
+        process::exit(1);
+    }
+}
+
+fn run(command: Command) -> io::Result<()> {
+    match command {
+        Command::Show { rails, color, text } => {
+            let fence = RailFence::new(rails);
+            if color && std::io::stdout().is_terminal() {
+                println!("{}", colored_diagram(&fence, &text));
+            } else {
+                println!("{}", fence.diagram(&text));
+            }
+            Ok(())
+        }
+        Command::Encode { key, files } => files.write(&key.fence().try_encode(&files.read()?)?),
+        Command::Decode { key, files } => files.write(&key.fence().try_decode(&files.read()?)?),
+        Command::Crack { max_rails, top, files } => {
+            let guesses: Vec<String> = RailFence::crack(&files.read()?, max_rails)
+                .into_iter()
+                .take(top)
+                .map(|candidate| format!("{}\t{}", candidate.rails, candidate.clear_text))
+                .collect();
+            files.write(&guesses.join("\n"))
+        }
+    }
+}
+
+/// Draw the fence like `RailFence::diagram`, with the letters of each rail
+/// in their own colour
+fn colored_diagram(fence: &RailFence, text: &str) -> String {
+    let lines: Vec<String> = fence
+        .grid(text)
+        .iter()
+        .enumerate()
+        .map(|(rail, cells)| {
+            let color = RAIL_COLORS[rail % RAIL_COLORS.len()];
+            let cells: Vec<String> = cells
+                .iter()
+                .map(|cell| match cell {
+                    Some(c) => format!("\x1b[{}m{}\x1b[0m", color, c),
+                    None => String::from("-"),
+                })
+                .collect();
+            cells.join(" ")
+        })
+        .collect();
+    lines.join("\n")
+}
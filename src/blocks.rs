@@ -0,0 +1,147 @@
+//! Splitting long messages into blocks that can be encoded separately
+//!
+//! A block that starts on a period boundary starts at the top of the
+//! zig-zag, just like a whole message does.  So each block's rails are
+//! exactly the matching stretch of the whole message's rails, and they can
+//! be computed independently, e.g. in parallel, with no state carried over
+//! from the block before.  The cipher-text is then put together rail by
+//! rail: all of the blocks' top rails, then all of their second rails, and
+//! so on.  Concatenating each block's complete cipher-text instead does
+//! not give the cipher-text of the whole message.
+//!
+//! Blocks that do not start on a period boundary start part way down the
+//! zig-zag, and encoding them separately places their characters on the
+//! wrong rails.
+//!
+//! `encode_parallel` does this for a single long message, using the
+//! `rayon` thread pool when the `parallel` feature is enabled.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::wipe::{wipe_cow, working, Wipe};
+use crate::{ascii_string, sized_string, CharFilter, RailFence, ReadOrder};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// The number of characters `encode_parallel` aims to put in each block
+const PARALLEL_BLOCK: usize = 1 << 16;
+
+impl RailFence {
+    /// Choose block boundaries for encoding a long message in pieces
+    ///
+    /// # Arguments
+    ///
+    /// * `len`             The number of characters in the message
+    /// * `target_block`    The preferred number of characters per block
+    ///
+    /// # Returns
+    /// Increasing character indices starting at 0 and ending at `len`, so
+    /// consecutive pairs give the blocks.  Every block except the last is
+    /// the whole number of periods nearest to `target_block`, and at least
+    /// one period.  A custom schedule has no period, so the whole message is
+    /// a single block.
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3);
+    /// assert_eq!(vec![0, 8, 16, 24, 25], cipher.block_boundaries(25, 9));
+    ///
+    /// // Each block's rails continue the rails of the block before it
+    /// let text = "WEAREDISCOVEREDFLEEATONCE";
+    /// let mut rails = vec![String::new(); 3];
+    /// for block in cipher.block_boundaries(text.len(), 9).windows(2) {
+    ///     for (rail, part) in rails.iter_mut().zip(cipher.encode_rails(&text[block[0]..block[1]])) {
+    ///         rail.push_str(&part);
+    ///     }
+    /// }
+    /// assert_eq!(cipher.encode(text), rails.concat());
+    /// ```
+    pub fn block_boundaries(&self, len: usize, target_block: usize) -> Vec<usize> {
+        let period = self.period();
+        if len == 0 {
+            return vec![0];
+        }
+        if period == 0 {
+            return vec![0, len];
+        }
+        let periods = ((target_block + period / 2) / period).max(1);
+        let mut boundaries: Vec<usize> = (0..len).step_by(periods * period).collect();
+        boundaries.push(len);
+        boundaries
+    }
+
+    /// Encode a long message in blocks, in parallel with the `parallel`
+    /// feature
+    ///
+    /// The message is cut into blocks of whole periods, each block's rails
+    /// are filled independently, and the rails are then joined block by
+    /// block.  Fences that read their periods separately, keep letters in
+    /// place, or follow a custom schedule are encoded with `encode` instead.
+    /// Splitting only pays off for messages of many thousands of
+    /// characters.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    ///
+    /// # Returns
+    /// The same cipher-text as `encode`
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// let text = "RUSTISGREAT".repeat(10_000);
+    /// assert_eq!(cipher.encode(&text), cipher.encode_parallel(&text));
+    /// ```
+    pub fn encode_parallel(&self, text: &str) -> String {
+        let normalized = self.normalize(text);
+        let text = self.pad_to_period(&normalized);
+        let encoded = if self.schedule.is_some()
+            || self.read_order != ReadOrder::RailMajor
+            || self.period_alternating
+            || self.rounds != 1
+            || self.char_filter == CharFilter::PreservePositions
+        {
+            self.encode_normalized(&text)
+        } else if text.is_ascii() {
+            ascii_string(self.encode_blocks(text.as_bytes()))
+        } else {
+            let chars = working(text.chars().collect::<Vec<char>>());
+            sized_string(self.encode_blocks(&chars[..]), text.len())
+        };
+        wipe_cow(text);
+        wipe_cow(normalized);
+        self.group(encoded)
+    }
+
+    /// Fill the rails of each block separately and join them rail by rail
+    fn encode_blocks<T: Clone + Send + Sync + Wipe>(&self, items: &[T]) -> Vec<T> {
+        let boundaries = self.block_boundaries(items.len(), PARALLEL_BLOCK);
+        let fill = |block: &[usize]| {
+            // Every block starts a period, so it splits like a whole message
+            let mut rails: Vec<Vec<T>> =
+                self.rail_lengths(block[1] - block[0]).into_iter().map(Vec::with_capacity).collect();
+            for position in block[0]..block[1] {
+                rails[self.rail_at(position)].push(items[position].clone());
+            }
+            rails
+        };
+        #[cfg(feature = "parallel")]
+        let blocks: Vec<Vec<Vec<T>>> = boundaries.par_windows(2).map(fill).collect();
+        #[cfg(not(feature = "parallel"))]
+        let blocks: Vec<Vec<Vec<T>>> = boundaries.windows(2).map(fill).collect();
+        let blocks = working(blocks);
+        let mut encoded = Vec::with_capacity(items.len());
+        let top_down: Vec<usize> = (0..self.rails as usize).collect();
+        for &rail in self.rail_order.as_ref().unwrap_or(&top_down) {
+            for block in blocks.iter() {
+                encoded.extend_from_slice(&block[rail]);
+            }
+        }
+        encoded
+    }
+}
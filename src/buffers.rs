@@ -0,0 +1,343 @@
+//! Encoding into caller-provided buffers
+
+use alloc::string::String;
+use core::fmt;
+use core::ops::Range;
+
+use crate::wipe::wipe;
+use crate::{RailFence, RailFenceError, ReadOrder};
+
+impl RailFence {
+    /// Encode bytes into a caller-provided buffer without allocating
+    ///
+    /// The rails are read off by scanning the input once per rail, which
+    /// takes `rails` times longer than `encode` on large inputs but needs no
+    /// memory beyond the output buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `input`   The clear-text bytes to encode
+    /// * `out`     The buffer to write the cipher-text bytes to
+    ///
+    /// # Returns
+    /// The number of bytes written, which is the length of `input`, or
+    /// `RailFenceError::BufferTooSmall` if `out` is shorter than `input`, in
+    /// which case nothing is written
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// let mut out = [0u8; 16];
+    /// let written = cipher.encode_into_slice(b"RUSTISGREAT", &mut out).unwrap();
+    /// assert_eq!(b"RGUSRSIETTA", &out[..written]);
+    /// ```
+    pub fn encode_into_slice(&self, input: &[u8], out: &mut [u8]) -> Result<usize, RailFenceError> {
+        if out.len() < input.len() {
+            return Err(RailFenceError::BufferTooSmall {
+                needed: input.len(),
+                available: out.len(),
+            });
+        }
+        if self.read_order == ReadOrder::ColumnMajor {
+            out[..input.len()].copy_from_slice(input);
+            return Ok(input.len());
+        }
+        if self.rounds != 1 {
+            // Reading off rail by rail only gives the first round
+            for (byte, from) in out.iter_mut().zip(self.inverse_permutation(input.len())) {
+                *byte = input[from];
+            }
+            return Ok(input.len());
+        }
+        let period = self.period();
+        if self.period_alternating && period > 0 {
+            // Read each period on its own, flipping the rail order on odd ones
+            let bottom = self.rails as usize - 1;
+            let mut written = 0;
+            for (block, chunk) in input.chunks(period).enumerate() {
+                for index in 0..self.rails as usize {
+                    let index = if block % 2 == 1 { bottom - index } else { index };
+                    let rail = self.rail_order.as_ref().map_or(index, |order| order[index]);
+                    for (offset, &byte) in chunk.iter().enumerate() {
+                        if self.rail_at(block * period + offset) == rail {
+                            out[written] = byte;
+                            written += 1;
+                        }
+                    }
+                }
+            }
+            return Ok(written);
+        }
+        let mut written = 0;
+        for index in 0..self.rails as usize {
+            let rail = self.rail_order.as_ref().map_or(index, |order| order[index]);
+            for (i, &byte) in input.iter().enumerate() {
+                if self.rail_at(i) == rail {
+                    out[written] = byte;
+                    written += 1;
+                }
+            }
+        }
+        Ok(written)
+    }
+
+    /// Encode the message into a string allocated with a given capacity
+    ///
+    /// Callers that know the largest message they will encode can keep one
+    /// string big enough for all of them, clearing it between messages.
+    /// The cipher-text is worked out by `encode`, with every option of the
+    /// fence, and moved into a string of the requested capacity if it does
+    /// not already have room.  A capacity below what the cipher-text needs
+    /// is not an error: the string is just as big as the cipher-text.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    /// * `cap`     The capacity, in bytes, to allocate for the result
+    ///
+    /// # Returns
+    /// The same cipher-text as `encode`
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// let cipher_text = cipher.encode_with_capacity("RUSTISGREAT", 64);
+    /// assert_eq!("RGUSRSIETTA", cipher_text);
+    /// assert!(cipher_text.capacity() >= 64);
+    /// ```
+    pub fn encode_with_capacity(&self, text: &str, cap: usize) -> String {
+        let encoded = self.encode(text);
+        if encoded.capacity() >= cap {
+            return encoded;
+        }
+        let mut sized = String::with_capacity(cap);
+        sized.push_str(&encoded);
+        sized
+    }
+
+    /// Encode the message onto the end of an existing string
+    ///
+    /// Clearing and reusing one string for many messages saves allocating
+    /// a new one for each cipher-text once it has grown big enough.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    /// * `out`     The string to append the cipher-text to
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// let mut out = String::from("cipher: ");
+    /// cipher.encode_into("RUSTISGREAT", &mut out);
+    /// assert_eq!("cipher: RGUSRSIETTA", out);
+    /// ```
+    pub fn encode_into(&self, text: &str, out: &mut String) {
+        out.extend(self.encode_chars(text));
+    }
+
+    /// Decode the message onto the end of an existing string
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  The cipher-text string to decode
+    /// * `out`     The string to append the clear-text to
+    pub fn decode_into(&self, cipher: &str, out: &mut String) {
+        out.extend(self.decode_iter(cipher));
+    }
+
+    /// Encode the message straight into a formatter or other text sink
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    /// * `w`       Where to write the cipher-text
+    ///
+    /// # Returns
+    /// The first error from `w`, if any
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// use std::fmt;
+    /// struct Sealed<'a>(&'a str);
+    /// impl fmt::Display for Sealed<'_> {
+    ///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    ///         RailFence::new(4).encode_to(self.0, f)
+    ///     }
+    /// }
+    /// assert_eq!("[RGUSRSIETTA]", format!("[{}]", Sealed("RUSTISGREAT")));
+    /// ```
+    pub fn encode_to<W: fmt::Write>(&self, text: &str, w: &mut W) -> fmt::Result {
+        self.encode_chars(text).try_for_each(|c| w.write_char(c))
+    }
+
+    /// Decode the message straight into a formatter or other text sink
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  The cipher-text string to decode
+    /// * `w`       Where to write the clear-text
+    ///
+    /// # Returns
+    /// The first error from `w`, if any
+    pub fn decode_to<W: fmt::Write>(&self, cipher: &str, w: &mut W) -> fmt::Result {
+        self.decode_iter(cipher).try_for_each(|c| w.write_char(c))
+    }
+
+    /// Encode the message in its own buffer, without allocating
+    ///
+    /// The characters are rotated into place one at a time, so only a few
+    /// counters are needed on top of the buffer.  In exchange this takes
+    /// time quadratic in the length of the message in the worst case, and
+    /// is meant for small buffers where memory is tight rather than speed.
+    ///
+    /// Only the transposition itself is done in place.  The fence's
+    /// normalizing, character filter, period padding and grouping are not
+    /// applied, so the result is the same as `encode` only on a fence
+    /// without them.  A fence with more than one round has its rounds
+    /// combined into one permutation first, which takes memory in
+    /// proportion to the message.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf`     The clear-text, replaced by the cipher-text
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// let mut buf = String::from("RUSTISGREAT");
+    /// cipher.encode_in_place(&mut buf);
+    /// assert_eq!("RGUSRSIETTA", buf);
+    /// ```
+    pub fn encode_in_place(&self, buf: &mut String) {
+        match self.rounds {
+            0 => {}
+            1 => self.encode_round_in_place(buf),
+            _ => {
+                let mut clear_text = core::mem::replace(buf, self.transpose(buf));
+                wipe(&mut clear_text);
+            }
+        }
+    }
+
+    /// Encode the message in its own buffer for a single round
+    fn encode_round_in_place(&self, buf: &mut String) {
+        if self.read_order == ReadOrder::ColumnMajor {
+            return;
+        }
+        let len = buf.chars().count();
+        let mut bytes = core::mem::take(buf).into_bytes();
+        let mut write = 0;
+        for segment in self.read_segments(len) {
+            // Each pass moves the characters of one rail, in order, to the
+            // end of the cipher-text written so far
+            for rank in 0..self.rails as usize {
+                let mut read = write;
+                for position in segment.clone() {
+                    let current = self.read_rank(position);
+                    if current < rank {
+                        continue;
+                    }
+                    let width = utf8_width(bytes[read]);
+                    if current == rank {
+                        bytes[write..read + width].rotate_right(width);
+                        write += width;
+                    }
+                    read += width;
+                }
+            }
+        }
+        *buf = String::from_utf8(bytes).expect("whole characters were moved");
+    }
+
+    /// Decode the message in its own buffer, without allocating
+    ///
+    /// This reverses `encode_in_place`, with the same quadratic worst case
+    /// and the same limits: only the transposition is undone, and several
+    /// rounds are combined into one permutation first.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf`     The cipher-text, replaced by the clear-text
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// let mut buf = String::from("RGUSRSIETTA");
+    /// cipher.decode_in_place(&mut buf);
+    /// assert_eq!("RUSTISGREAT", buf);
+    /// ```
+    pub fn decode_in_place(&self, buf: &mut String) {
+        match self.rounds {
+            0 => {}
+            1 => self.decode_round_in_place(buf),
+            _ => {
+                let mut cipher = core::mem::replace(buf, self.untranspose(buf));
+                wipe(&mut cipher);
+            }
+        }
+    }
+
+    /// Decode the message in its own buffer for a single round
+    fn decode_round_in_place(&self, buf: &mut String) {
+        if self.read_order == ReadOrder::ColumnMajor {
+            return;
+        }
+        let len = buf.chars().count();
+        let mut bytes = core::mem::take(buf).into_bytes();
+        let mut write = 0;
+        for segment in self.read_segments(len) {
+            for position in segment.clone() {
+                // The unplaced characters are still in cipher-text order, so
+                // this one comes after every later one read off before it
+                let rank = self.read_rank(position);
+                let skip = (position + 1..segment.end).filter(|&later| self.read_rank(later) < rank).count();
+                let mut read = write;
+                for _ in 0..skip {
+                    read += utf8_width(bytes[read]);
+                }
+                let width = utf8_width(bytes[read]);
+                bytes[write..read + width].rotate_right(width);
+                write += width;
+            }
+        }
+        *buf = String::from_utf8(bytes).expect("whole characters were moved");
+    }
+
+    /// The runs of positions that are read off the fence independently
+    fn read_segments(&self, len: usize) -> impl Iterator<Item = Range<usize>> {
+        let period = self.period();
+        let step = if self.period_alternating && period > 0 { period } else { len.max(1) };
+        (0..len).step_by(step).map(move |start| start..(start + step).min(len))
+    }
+
+    /// How early the rail of a position is read within its segment
+    fn read_rank(&self, position: usize) -> usize {
+        let rail = self.rail_at(position);
+        let rank = match &self.rail_order {
+            Some(order) => order.iter().position(|&read| read == rail).unwrap_or(rail),
+            None => rail,
+        };
+        let period = self.period();
+        if self.period_alternating && period > 0 && (position / period) % 2 == 1 {
+            return self.rails as usize - 1 - rank;
+        }
+        rank
+    }
+}
+
+/// The number of bytes in the UTF-8 character that starts with this byte
+fn utf8_width(first: u8) -> usize {
+    match first {
+        0x00..=0x7f => 1,
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        _ => 4,
+    }
+}
@@ -0,0 +1,334 @@
+//! Building a fence from options collected one at a time
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::{Case, CharFilter, RailFence, RailFenceError, ReadOrder, Schedule, TurnPolicy, Wave};
+
+/// Collects the options for a fence and checks them all together
+///
+/// Unlike the `with_*` methods on `RailFence`, which apply each option
+/// straight away, the builder holds on to every option until `build`, so
+/// `validate` can report every problem with the configuration at once.
+#[derive(Debug, Clone)]
+pub struct RailFenceBuilder {
+    rails: u32,
+    schedule: Option<Schedule>,
+    turn: TurnPolicy,
+    wave: Wave,
+    read_order: ReadOrder,
+    period_alternating: bool,
+    offset: usize,
+    upward: bool,
+    rail_order: Option<Vec<u32>>,
+    alphabet: Option<String>,
+    case: Case,
+    strip_whitespace: bool,
+    char_filter: CharFilter,
+    period_fill: Option<char>,
+    grouping: Option<usize>,
+    ignore_whitespace: bool,
+    rounds: u32,
+}
+
+impl RailFenceBuilder {
+    /// Start building a fence with the given number of rails
+    ///
+    /// # Arguments
+    /// * `rails`   The number of rails for this cipher
+    ///
+    /// # Returns
+    /// A builder with every other option at its default
+    pub fn new(rails: u32) -> RailFenceBuilder {
+        RailFenceBuilder {
+            rails,
+            schedule: None,
+            turn: TurnPolicy::Bounce,
+            wave: Wave::Triangle,
+            read_order: ReadOrder::RailMajor,
+            period_alternating: false,
+            offset: 0,
+            upward: false,
+            rail_order: None,
+            alphabet: None,
+            case: Case::Preserve,
+            strip_whitespace: false,
+            char_filter: CharFilter::Passthrough,
+            period_fill: None,
+            grouping: None,
+            ignore_whitespace: false,
+            rounds: 1,
+        }
+    }
+
+    /// Change the number of rails
+    pub fn rails(mut self, rails: u32) -> RailFenceBuilder {
+        self.rails = rails;
+        self
+    }
+
+    /// Place characters by a custom schedule instead of the zig-zag
+    pub fn schedule(mut self, schedule: impl Fn(usize) -> u32 + Send + Sync + 'static) -> RailFenceBuilder {
+        self.schedule = Some(Schedule(Arc::new(schedule)));
+        self
+    }
+
+    /// Use a different turn-around policy at the top and bottom rails
+    pub fn turn_policy(mut self, turn: TurnPolicy) -> RailFenceBuilder {
+        self.turn = turn;
+        self
+    }
+
+    /// Change the shape of the path across the rails
+    pub fn wave(mut self, wave: Wave) -> RailFenceBuilder {
+        self.wave = wave;
+        self
+    }
+
+    /// Read the cipher-text off the fence in a different order
+    pub fn read_order(mut self, order: ReadOrder) -> RailFenceBuilder {
+        self.read_order = order;
+        self
+    }
+
+    /// Read the cipher-text period by period with alternating rail order
+    pub fn period_alternating(mut self, alternating: bool) -> RailFenceBuilder {
+        self.period_alternating = alternating;
+        self
+    }
+
+    /// Start the zig-zag part way through its period
+    pub fn offset(mut self, offset: usize) -> RailFenceBuilder {
+        self.offset = offset;
+        self
+    }
+
+    /// Start the zig-zag heading up from the bottom rail instead of down
+    ///
+    /// A start offset counts steps along the upward zig-zag.
+    pub fn upward(mut self, upward: bool) -> RailFenceBuilder {
+        self.upward = upward;
+        self
+    }
+
+    /// Read the rails in a keyed order instead of from the top down
+    ///
+    /// The order lists the rails, counting the top rail as 0, in the order
+    /// they are read, and must have one entry per rail.
+    pub fn rail_order(mut self, order: Option<Vec<u32>>) -> RailFenceBuilder {
+        self.rail_order = order;
+        self
+    }
+
+    /// Only let `try_encode` encode characters from an alphabet
+    pub fn alphabet(mut self, alphabet: Option<&str>) -> RailFenceBuilder {
+        self.alphabet = alphabet.map(String::from);
+        self
+    }
+
+    /// Change the case of the letters before encoding
+    pub fn case(mut self, case: Case) -> RailFenceBuilder {
+        self.case = case;
+        self
+    }
+
+    /// Remove whitespace from the clear-text before encoding
+    pub fn strip_whitespace(mut self, strip: bool) -> RailFenceBuilder {
+        self.strip_whitespace = strip;
+        self
+    }
+
+    /// Choose what happens to characters that are not letters
+    pub fn char_filter(mut self, filter: CharFilter) -> RailFenceBuilder {
+        self.char_filter = filter;
+        self
+    }
+
+    /// Pad every message with filler to a whole number of periods
+    pub fn period_padding(mut self, fill: Option<char>) -> RailFenceBuilder {
+        self.period_fill = fill;
+        self
+    }
+
+    /// Write the cipher-text in groups of the given length
+    pub fn grouping(mut self, group_len: Option<usize>) -> RailFenceBuilder {
+        self.grouping = group_len;
+        self
+    }
+
+    /// Remove whitespace from the cipher-text before decoding
+    pub fn ignore_whitespace(mut self, ignore: bool) -> RailFenceBuilder {
+        self.ignore_whitespace = ignore;
+        self
+    }
+
+    /// Apply the transposition several times over
+    pub fn rounds(mut self, rounds: u32) -> RailFenceBuilder {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Check the options without building the fence
+    ///
+    /// Every problem is reported, not just the first, so a form can flag
+    /// all of its bad fields together.  Options that would be silently
+    /// ignored by the fence, such as a turn policy alongside a custom
+    /// schedule, count as problems.
+    ///
+    /// # Returns
+    /// `Ok` if the options make a working fence, otherwise every problem
+    /// found, in the order the options are listed on the builder
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let builder = RailFenceBuilder::new(0)
+    ///     .read_order(ReadOrder::ColumnMajor)
+    ///     .period_alternating(true);
+    /// let problems = builder.validate().unwrap_err();
+    /// assert_eq!(2, problems.len());
+    /// assert_eq!(RailFenceError::ZeroRails, problems[0]);
+    /// ```
+    pub fn validate(&self) -> Result<(), Vec<RailFenceError>> {
+        let mut problems = Vec::new();
+        if self.rails == 0 {
+            problems.push(RailFenceError::ZeroRails);
+        }
+        if self.schedule.is_some() {
+            if self.turn != TurnPolicy::Bounce {
+                problems.push(RailFenceError::ConflictingOptions {
+                    first: "a custom schedule",
+                    second: "a turn policy",
+                });
+            }
+            if self.wave != Wave::Triangle {
+                problems.push(RailFenceError::ConflictingOptions {
+                    first: "a custom schedule",
+                    second: "a sawtooth wave",
+                });
+            }
+            if self.period_alternating {
+                problems.push(RailFenceError::ConflictingOptions {
+                    first: "a custom schedule",
+                    second: "alternating periods",
+                });
+            }
+            if self.offset != 0 {
+                problems.push(RailFenceError::ConflictingOptions {
+                    first: "a custom schedule",
+                    second: "a start offset",
+                });
+            }
+            if self.upward {
+                problems.push(RailFenceError::ConflictingOptions {
+                    first: "a custom schedule",
+                    second: "an upward start",
+                });
+            }
+            if self.period_fill.is_some() {
+                problems.push(RailFenceError::ConflictingOptions {
+                    first: "a custom schedule",
+                    second: "period padding",
+                });
+            }
+        } else {
+            if self.wave == Wave::Sawtooth && self.turn != TurnPolicy::Bounce {
+                problems.push(RailFenceError::ConflictingOptions {
+                    first: "a sawtooth wave",
+                    second: "a turn policy",
+                });
+            }
+            // Larger offsets would wrap, which is more likely a slip than
+            // intended
+            let period = RailFence::new(self.rails).with_turn_policy(self.turn).with_wave(self.wave).period();
+            if self.offset >= period {
+                problems.push(RailFenceError::OffsetOutOfRange { offset: self.offset, period });
+            }
+        }
+        if self.read_order == ReadOrder::ColumnMajor && self.period_alternating {
+            problems.push(RailFenceError::ConflictingOptions {
+                first: "column-major reading",
+                second: "alternating periods",
+            });
+        }
+        if let Some(order) = &self.rail_order {
+            let mut sorted = order.clone();
+            sorted.sort_unstable();
+            if order.is_empty() || !sorted.iter().copied().eq(0..order.len() as u32) {
+                problems.push(RailFenceError::InvalidRailOrder);
+            } else if order.len() != self.rails as usize {
+                problems.push(RailFenceError::ConflictingOptions {
+                    first: "a rail order",
+                    second: "a different number of rails",
+                });
+            }
+            if self.read_order == ReadOrder::ColumnMajor {
+                problems.push(RailFenceError::ConflictingOptions {
+                    first: "column-major reading",
+                    second: "a rail order",
+                });
+            }
+        }
+        if let Some(alphabet) = &self.alphabet {
+            if let Some((_, ch)) = alphabet.char_indices().find(|&(i, ch)| alphabet[..i].contains(ch)) {
+                problems.push(RailFenceError::RepeatedInAlphabet { ch });
+            }
+        }
+        if self.char_filter == CharFilter::PreservePositions {
+            // Removing whitespace on decode would move the characters kept
+            // in place
+            if self.grouping.is_some_and(|group_len| group_len > 0) {
+                problems.push(RailFenceError::ConflictingOptions {
+                    first: "preserved positions",
+                    second: "grouping",
+                });
+            }
+            if self.ignore_whitespace {
+                problems.push(RailFenceError::ConflictingOptions {
+                    first: "preserved positions",
+                    second: "ignoring whitespace",
+                });
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Build the fence, if the options are valid
+    ///
+    /// # Returns
+    /// The configured fence, or every problem `validate` finds
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFenceBuilder::new(4).turn_policy(TurnPolicy::Dwell).build().unwrap();
+    /// assert_eq!(cipher.encode("RUSTISGREAT"), RailFence::new(4).with_turn_policy(TurnPolicy::Dwell).encode("RUSTISGREAT"));
+    /// ```
+    pub fn build(self) -> Result<RailFence, Vec<RailFenceError>> {
+        self.validate()?;
+        Ok(RailFence {
+            rails: self.rails,
+            schedule: self.schedule,
+            turn: self.turn,
+            wave: self.wave,
+            read_order: self.read_order,
+            period_alternating: self.period_alternating,
+            offset: self.offset,
+            upward: self.upward,
+            rail_order: self.rail_order.map(|order| order.into_iter().map(|rail| rail as usize).collect()),
+            alphabet: self.alphabet,
+            case: self.case,
+            strip_whitespace: self.strip_whitespace,
+            char_filter: self.char_filter,
+            period_fill: self.period_fill,
+            grouping: self.grouping,
+            ignore_whitespace: self.ignore_whitespace,
+            rounds: self.rounds,
+        })
+    }
+}
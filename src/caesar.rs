@@ -0,0 +1,81 @@
+//! The rail fence combined with a Caesar shift
+//!
+//! Encoding shifts each ASCII letter forward through the alphabet, keeping
+//! its case, and then transposes the result.  Decoding undoes the
+//! transposition and then shifts the letters back.  Everything other than
+//! ASCII letters is transposed but not shifted.
+//!
+//! Any period padding is added before the shift and stripped after the
+//! shift back, so a message letter that shifts onto the fill character is
+//! never mistaken for padding.
+
+use alloc::string::String;
+
+use crate::wipe::{wipe, wipe_cow};
+use crate::RailFence;
+
+impl RailFence {
+    /// Caesar shift the message, then encode it
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    /// * `shift`   How many places to move each letter forward, modulo 26
+    ///
+    /// # Returns
+    /// The cipher-text message
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// assert_eq!("UJXVUVLHWWD", cipher.encode_caesar("RUSTISGREAT", 3))
+    /// ```
+    pub fn encode_caesar(&self, text: &str, shift: u8) -> String {
+        self.assert_rails();
+        let normalized = self.normalize(text);
+        let padded = self.pad_to_period(&normalized);
+        let mut shifted: String = padded.chars().map(|c| caesar_shift(c, shift % 26)).collect();
+        wipe_cow(padded);
+        wipe_cow(normalized);
+        let encoded = self.group(self.encode_normalized(&shifted));
+        wipe(&mut shifted);
+        encoded
+    }
+
+    /// Decode a message produced by `encode_caesar`
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  The cipher-text string to decode
+    /// * `shift`   The shift that was used to encode it
+    ///
+    /// # Returns
+    /// The clear-text message
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// assert_eq!("RUSTISGREAT", cipher.decode_caesar("UJXVUVLHWWD", 3))
+    /// ```
+    pub fn decode_caesar(&self, cipher: &str, shift: u8) -> String {
+        let mut padded = match self.try_decode_padded(cipher) {
+            Ok(padded) => padded,
+            Err(error) => panic!("{}", error),
+        };
+        let clear_text = padded.chars().map(|c| caesar_shift(c, 26 - shift % 26)).collect();
+        wipe(&mut padded);
+        self.strip_period_padding(clear_text)
+    }
+}
+
+/// Move an ASCII letter forward through the alphabet, wrapping from Z to A
+fn caesar_shift(c: char, shift: u8) -> char {
+    let base = match c {
+        'a'..='z' => b'a',
+        'A'..='Z' => b'A',
+        _ => return c,
+    };
+    (base + (c as u8 - base + shift) % 26) as char
+}
@@ -0,0 +1,83 @@
+//! A columnar transposition followed by the rail fence
+//!
+//! The clear-text is first written into rows of a fixed width and read
+//! back out column by column, and the result is then put on the fence.
+//! The last row may be short: its missing cells are skipped when reading
+//! the columns rather than padded, so the transposition adds nothing to
+//! the normalized message. Any period padding or grouping the fence asks
+//! for is applied to the transposed text, and undone before the columns
+//! are read back.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::wipe::{wipe, wipe_cow, working};
+use crate::RailFence;
+
+impl RailFence {
+    /// Transpose the message by columns, then encode it on the fence
+    ///
+    /// The message is normalized first, so the columns are those of the
+    /// text the fence places, and then padded and grouped as `encode` does.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    /// * `cols`    The width of the rows the message is written in, where 0
+    ///   is treated as 1
+    ///
+    /// # Returns
+    /// The cipher-text
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(2);
+    /// // Rows "RUS", "TIS", "GRE", "AT" give columns "RTGA", "UIRT", "SSE"
+    /// assert_eq!("RGURSETAITS", cipher.encode_2d("RUSTISGREAT", 3));
+    /// ```
+    pub fn encode_2d(&self, text: &str, cols: usize) -> String {
+        self.assert_rails();
+        let normalized = self.normalize(text);
+        let chars = working(normalized.chars().collect::<Vec<char>>());
+        wipe_cow(normalized);
+        let mut columns: String = column_order(chars.len(), cols).map(|from| chars[from]).collect();
+        let padded = self.pad_to_period(&columns);
+        let encoded = self.group(self.encode_normalized(&padded));
+        wipe_cow(padded);
+        wipe(&mut columns);
+        encoded
+    }
+
+    /// Decode a message encoded with `encode_2d`
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  The cipher-text string to decode
+    /// * `cols`    The row width used to encode it
+    ///
+    /// # Returns
+    /// The clear-text message
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(2);
+    /// assert_eq!("RUSTISGREAT", cipher.decode_2d("RGURSETAITS", 3));
+    /// ```
+    pub fn decode_2d(&self, cipher: &str, cols: usize) -> String {
+        let columns: Vec<char> = self.decode(cipher).chars().collect();
+        let mut clear = vec!['\0'; columns.len()];
+        for (c, to) in columns.into_iter().zip(column_order(clear.len(), cols)) {
+            clear[to] = c;
+        }
+        clear.into_iter().collect()
+    }
+}
+
+/// The clear-text positions in the order the columns read them
+pub(crate) fn column_order(len: usize, cols: usize) -> impl Iterator<Item = usize> {
+    let cols = cols.max(1);
+    (0..cols).flat_map(move |col| (col..len).step_by(cols))
+}
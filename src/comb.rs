@@ -0,0 +1,66 @@
+//! Reading the even rails before the odd ones
+//!
+//! The comb, or teeth, variant lays the message out on the usual zig-zag
+//! but reads off every other rail first: rails 0, 2, 4 and so on, then
+//! rails 1, 3, 5.  Neighbouring rails end up far apart in the cipher-text.
+//!
+//! The comb is a rail order of its own, so it takes the place of any rail
+//! order the fence already has.  With alternating periods the comb order
+//! is read forwards and backwards in turn, like any other rail order.
+
+use alloc::string::String;
+
+use crate::RailFence;
+
+impl RailFence {
+    /// Encode the message, reading the even rails and then the odd rails
+    ///
+    /// Any rail order the fence has, as from `with_rail_order`, is replaced
+    /// by the comb order.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    ///
+    /// # Returns
+    /// The cipher-text with the rails in comb order
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// // Rails "RG", "USR", "SIET" and "TA" are read 0, 2, 1, 3
+    /// assert_eq!("RGSIETUSRTA", cipher.encode_comb("RUSTISGREAT"));
+    /// ```
+    pub fn encode_comb(&self, text: &str) -> String {
+        self.comb().encode(text)
+    }
+
+    /// Decode a message encoded with `encode_comb`
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  The cipher-text string to decode
+    ///
+    /// # Returns
+    /// The clear-text message
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// assert_eq!("RUSTISGREAT", cipher.decode_comb("RGSIETUSRTA"));
+    /// ```
+    pub fn decode_comb(&self, cipher: &str) -> String {
+        self.comb().decode(cipher)
+    }
+
+    /// The same fence, reading its rails in comb order instead of any rail
+    /// order it has
+    fn comb(&self) -> RailFence {
+        let rails = self.rails as usize;
+        let mut comb = self.clone();
+        comb.rail_order = Some((0..rails).step_by(2).chain((1..rails).step_by(2)).collect());
+        comb
+    }
+}
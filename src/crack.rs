@@ -0,0 +1,226 @@
+//! Trying every rail count against an unknown cipher-text
+
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::RangeInclusive;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use crate::{RailFence, RailFenceError};
+
+/// One possible decoding of an unknown cipher-text
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    /// The rail count tried
+    pub rails: u32,
+    /// The cipher-text decoded with that rail count
+    pub clear_text: String,
+    /// How good the clear-text looks, higher is better
+    pub score: f64,
+}
+
+impl RailFence {
+    /// Decode the cipher-text with every rail count up to a maximum
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`      The cipher-text string to decode
+    /// * `max_rails`   The largest rail count to try
+    ///
+    /// # Returns
+    /// The rail count and clear-text for each of `1..=max_rails`
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let candidates = RailFence::decode_all("RGUSRSIETTA", 4);
+    /// assert_eq!((4, String::from("RUSTISGREAT")), candidates[3])
+    /// ```
+    pub fn decode_all(cipher: &str, max_rails: u32) -> Vec<(u32, String)> {
+        (1..=max_rails)
+            .map(|rails| (rails, RailFence::new(rails).decode(cipher)))
+            .collect()
+    }
+
+    /// Encode the message with every rail count in a range
+    ///
+    /// This is the encoding counterpart of `decode_all`, for showing the
+    /// cipher-texts of several rail counts side by side.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    /// * `rails`   The rail counts to use
+    ///
+    /// # Returns
+    /// The rail count and cipher-text for each count in `rails`, in order,
+    /// or `RailFenceError::ZeroRails` if the range includes 0
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let outputs = RailFence::encode_range_of_rails("RUSTISGREAT", 3..=4).unwrap();
+    /// assert_eq!(vec![(3, String::from("RIEUTSRASGT")), (4, String::from("RGUSRSIETTA"))], outputs);
+    /// assert_eq!(Err(RailFenceError::ZeroRails), RailFence::encode_range_of_rails("RUSTISGREAT", 0..=4));
+    /// ```
+    pub fn encode_range_of_rails(text: &str, rails: RangeInclusive<u32>) -> Result<Vec<(u32, String)>, RailFenceError> {
+        if rails.contains(&0) {
+            return Err(RailFenceError::ZeroRails);
+        }
+        Ok(rails.map(|rails| (rails, RailFence::new(rails).encode(text))).collect())
+    }
+
+    /// Find the rail count whose decoding scores highest
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`      The cipher-text string to decode
+    /// * `max_rails`   The largest rail count to try
+    /// * `score`       Rates a candidate clear-text, higher is better
+    ///
+    /// # Returns
+    /// The best rail count and its clear-text.  Ties go to the smaller rail
+    /// count, and a `max_rails` of 0 is treated as 1.
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let score = |text: &str| text.matches("DISCOVER").count() as f64;
+    /// let (rails, clear_text) = RailFence::best_rails("WECRLTEERDSOEEFEAOCAIVDEN", 8, score);
+    /// assert_eq!(3, rails);
+    /// assert_eq!("WEAREDISCOVEREDFLEEATONCE", clear_text)
+    /// ```
+    pub fn best_rails(cipher: &str, max_rails: u32, score: impl Fn(&str) -> f64) -> (u32, String) {
+        let mut best = (1, cipher.to_string());
+        let mut best_score = score(cipher);
+        for (rails, candidate) in RailFence::decode_all(cipher, max_rails).into_iter().skip(1) {
+            let candidate_score = score(&candidate);
+            if candidate_score > best_score {
+                best_score = candidate_score;
+                best = (rails, candidate);
+            }
+        }
+        best
+    }
+
+    /// Decode with every rail count and rank the results
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`      The cipher-text string to decode
+    /// * `max_rails`   The largest rail count to try
+    /// * `score`       Rates a candidate clear-text, higher is better
+    ///
+    /// # Returns
+    /// A candidate for each of `1..=max_rails`, best first.  Ties keep the
+    /// smaller rail count first, as `best_rails` does.
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let score = |text: &str| text.matches("DISCOVER").count() as f64;
+    /// let candidates = RailFence::rank_rails("WECRLTEERDSOEEFEAOCAIVDEN", 8, score);
+    /// assert_eq!(8, candidates.len());
+    /// assert_eq!(3, candidates[0].rails);
+    /// assert_eq!("WEAREDISCOVEREDFLEEATONCE", candidates[0].clear_text);
+    /// ```
+    pub fn rank_rails(cipher: &str, max_rails: u32, score: impl Fn(&str) -> f64) -> Vec<Candidate> {
+        let mut candidates: Vec<Candidate> = RailFence::decode_all(cipher, max_rails)
+            .into_iter()
+            .map(|(rails, clear_text)| Candidate { rails, score: score(&clear_text), clear_text })
+            .collect();
+        candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+        candidates
+    }
+
+    /// Rank the rail counts for an unknown cipher-text by how English the
+    /// decodings look
+    ///
+    /// This is `rank_rails` with `english::english_score`, which needs a
+    /// few dozen letters to tell the candidates apart reliably.
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`      The cipher-text string to decode
+    /// * `max_rails`   The largest rail count to try
+    ///
+    /// # Returns
+    /// A candidate for each of `1..=max_rails`, most English-like first
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(5).encode("THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG AND THEN RUNS INTO THE FOREST");
+    /// let candidates = RailFence::crack(&cipher, 10);
+    /// assert_eq!(5, candidates[0].rails);
+    /// ```
+    #[cfg(feature = "english")]
+    pub fn crack(cipher: &str, max_rails: u32) -> Vec<Candidate> {
+        RailFence::rank_rails(cipher, max_rails, crate::english::english_score)
+    }
+
+    /// Count the different cipher-texts a message has over a range of rails
+    ///
+    /// Short messages collapse quickly: every rail count from the message
+    /// length upwards leaves the message unchanged, just as one rail does,
+    /// so the number of keys worth trying is far smaller than it looks.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`        The clear-text string to encode
+    /// * `max_rails`   The largest rail count to try
+    ///
+    /// # Returns
+    /// The number of distinct results of encoding `text` with each of
+    /// `1..=max_rails` rails
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// // Rail counts from 11 upwards leave the message as it is, like one rail
+    /// assert_eq!(10, RailFence::distinct_ciphertexts("RUSTISGREAT", 20));
+    /// ```
+    pub fn distinct_ciphertexts(text: &str, max_rails: u32) -> usize {
+        (1..=max_rails)
+            .map(|rails| RailFence::new(rails).encode(text))
+            .collect::<BTreeSet<_>>()
+            .len()
+    }
+
+    /// Count how often each character occurs on each rail
+    ///
+    /// Each rail holds a regular subsequence of the clear-text, so for the
+    /// right rail count the rails of a long message look like samples of
+    /// the same language, while for a wrong one they do not.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to lay out on the fence
+    ///
+    /// # Returns
+    /// One map per rail, from the top rail down, from each character on
+    /// that rail to its number of occurrences there
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let frequencies = RailFence::new(4).rail_frequencies("RUSTISGREAT");
+    /// // The third rail holds "SIET"
+    /// assert_eq!(Some(&1), frequencies[2].get(&'S'));
+    /// assert_eq!(4, frequencies[2].values().sum::<usize>());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn rail_frequencies(&self, text: &str) -> Vec<HashMap<char, usize>> {
+        self.encode_rails(text)
+            .iter()
+            .map(|rail| {
+                let mut counts = HashMap::new();
+                for c in rail.chars() {
+                    *counts.entry(c).or_insert(0) += 1;
+                }
+                counts
+            })
+            .collect()
+    }
+}
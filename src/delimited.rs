@@ -0,0 +1,131 @@
+//! Cipher-text with visible rail boundaries
+//!
+//! This is a teaching and debugging aid: the rails are written out with a
+//! separator between them, so `RUSTISGREAT` on four rails becomes
+//! `RG|USR|SIET|TA`.
+//!
+//! A separator that occurs in the message itself is escaped with a
+//! backslash, and so is a backslash, so `A|B` on one rail is written as
+//! `A\|B`.  When the separator is a backslash, the escape character is `/`
+//! instead.  Messages without either character are written unchanged.
+//!
+//! The framed format does not need escaping: its header fields are plain
+//! numbers, and everything after the header is taken as the cipher-text.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{RailFence, RailFenceError};
+
+impl RailFence {
+    /// Encode the message with a separator between each rail
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    /// * `sep`     The character written between rails
+    ///
+    /// # Returns
+    /// The cipher-text with the rails separated by `sep`
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// assert_eq!("RG|USR|SIET|TA", cipher.encode_delimited("RUSTISGREAT", '|'))
+    /// ```
+    pub fn encode_delimited(&self, text: &str, sep: char) -> String {
+        let escape = escape_for(sep);
+        let mut delimited = String::with_capacity(text.len() + self.rails as usize);
+        for (i, rail) in self.encode_rails(text).iter().enumerate() {
+            if i > 0 {
+                delimited.push(sep);
+            }
+            for c in rail.chars() {
+                if c == sep || c == escape {
+                    delimited.push(escape);
+                }
+                delimited.push(c);
+            }
+        }
+        delimited
+    }
+
+    /// Decode a message produced by `encode_delimited`
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  The cipher-text with rails separated by `sep`
+    /// * `sep`     The character written between rails
+    ///
+    /// # Returns
+    /// The clear-text message, or an error:
+    /// * `RailFenceError::RailCountMismatch` if splitting on unescaped
+    ///   `sep` does not give one part per rail
+    /// * `RailFenceError::InvalidEscape` if the cipher-text ends with an
+    ///   escape character
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// assert_eq!(Ok(String::from("RUSTISGREAT")), cipher.decode_delimited("RG|USR|SIET|TA", '|'))
+    /// ```
+    pub fn decode_delimited(&self, cipher: &str, sep: char) -> Result<String, RailFenceError> {
+        let escape = escape_for(sep);
+        let mut rails = vec![String::new()];
+        let mut chars = cipher.chars();
+        while let Some(c) = chars.next() {
+            if c == escape {
+                let escaped = chars.next().ok_or(RailFenceError::InvalidEscape)?;
+                rails.last_mut().unwrap().push(escaped);
+            } else if c == sep {
+                rails.push(String::new());
+            } else {
+                rails.last_mut().unwrap().push(c);
+            }
+        }
+        self.decode_from_rails(&rails)
+    }
+
+    /// Decode cipher-text written with one line per rail
+    ///
+    /// Lines may end in `\n` or `\r\n`.  Empty lines are empty rails, and
+    /// a single newline at the very end of the text is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The rails of the cipher-text, one per line
+    ///
+    /// # Returns
+    /// The clear-text message, or an error as for `decode_from_rails` if
+    /// the line count does not match the number of rails or the lines could
+    /// not have come from `encode_rails`
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// assert_eq!(Ok(String::from("RUSTISGREAT")), cipher.decode_multiline("RG\nUSR\nSIET\nTA\n"))
+    /// ```
+    pub fn decode_multiline(&self, text: &str) -> Result<String, RailFenceError> {
+        let mut lines: Vec<&str> = text
+            .split('\n')
+            .map(|line| line.strip_suffix('\r').unwrap_or(line))
+            .collect();
+        if lines.len() == self.rails as usize + 1 && text.ends_with('\n') {
+            lines.pop();
+        }
+        self.decode_from_rails(&lines)
+    }
+}
+
+/// The character that escapes a separator inside a rail
+fn escape_for(sep: char) -> char {
+    if sep == '\\' {
+        '/'
+    } else {
+        '\\'
+    }
+}
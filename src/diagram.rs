@@ -0,0 +1,146 @@
+//! Drawing the fence
+//!
+//! The grid has one row per rail and one column per character, with each
+//! character in the column of its position and the row of its rail.  The
+//! text diagram draws empty cells as `-`, like the example in the crate
+//! documentation.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::wipe::wipe_cow;
+use crate::RailFence;
+
+/// The character drawn for a cell that no letter was placed in
+const EMPTY_CELL: char = '-';
+
+impl RailFence {
+    /// Lay the message out on the fence
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to place
+    ///
+    /// # Returns
+    /// One row per rail, each with one cell per character of the message
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let grid = RailFence::new(2).grid("RUST");
+    /// assert_eq!(vec![Some('R'), None, Some('S'), None], grid[0]);
+    /// assert_eq!(vec![None, Some('U'), None, Some('T')], grid[1]);
+    /// ```
+    pub fn grid(&self, text: &str) -> Vec<Vec<Option<char>>> {
+        let mut grid = self.empty_grid(text.chars().count());
+        for (i, c) in text.chars().enumerate() {
+            grid[self.rail_at(i)][i] = Some(c);
+        }
+        grid
+    }
+
+    /// Encode the message and lay it out on the fence together
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    ///
+    /// # Returns
+    /// The cipher-text, exactly as `encode` gives it, and the grid that
+    /// `grid` gives for the message once it is normalized and padded, which
+    /// is the text `encode` lays out
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let (cipher_text, grid) = RailFence::new(2).encode_with_grid("RUST");
+    /// assert_eq!("RSUT", cipher_text);
+    /// assert_eq!(vec![Some('R'), None, Some('S'), None], grid[0]);
+    /// ```
+    pub fn encode_with_grid(&self, text: &str) -> (String, Vec<Vec<Option<char>>>) {
+        self.assert_rails();
+        let normalized = self.normalize(text);
+        let padded = self.pad_to_period(&normalized);
+        let grid = self.grid(&padded);
+        let encoded = self.group(self.encode_normalized(&padded));
+        wipe_cow(padded);
+        wipe_cow(normalized);
+        (encoded, grid)
+    }
+
+    /// Draw the message on the fence as text
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to draw
+    ///
+    /// # Returns
+    /// One line per rail, with cells separated by spaces
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let expected = "\
+    /// R - - - - - G - - - -
+    /// - U - - - S - R - - -
+    /// - - S - I - - - E - T
+    /// - - - T - - - - - A -";
+    /// assert_eq!(expected, RailFence::new(4).diagram("RUSTISGREAT"));
+    /// ```
+    pub fn diagram(&self, text: &str) -> String {
+        render(&self.grid(text))
+    }
+
+    /// Draw the fence after each character is placed
+    ///
+    /// This is meant for animating how the fence fills up, and renders the
+    /// whole grid once per character, so it is much heavier than `diagram`.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to draw
+    ///
+    /// # Returns
+    /// One diagram per character, the i-th showing the first i + 1
+    /// characters in place.  The last one is the same as `diagram`.
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let steps = RailFence::new(2).trace("HI!");
+    /// assert_eq!(vec!["H - -\n- - -", "H - -\n- I -", "H - !\n- I -"], steps);
+    /// ```
+    pub fn trace(&self, text: &str) -> Vec<String> {
+        let mut grid = self.empty_grid(text.chars().count());
+        let mut steps = Vec::with_capacity(grid.first().map_or(0, Vec::len));
+        for (i, c) in text.chars().enumerate() {
+            grid[self.rail_at(i)][i] = Some(c);
+            steps.push(render(&grid));
+        }
+        steps
+    }
+
+    /// A grid of the right size for a message, with nothing placed yet
+    fn empty_grid(&self, len: usize) -> Vec<Vec<Option<char>>> {
+        vec![vec![None; len]; self.rails as usize]
+    }
+}
+
+/// Draw a grid with one line per rail
+fn render(grid: &[Vec<Option<char>>]) -> String {
+    let width = grid.first().map_or(0, Vec::len);
+    let mut diagram = String::with_capacity(grid.len() * width * 2);
+    for (row, rail) in grid.iter().enumerate() {
+        if row > 0 {
+            diagram.push('\n');
+        }
+        for (column, cell) in rail.iter().enumerate() {
+            if column > 0 {
+                diagram.push(' ');
+            }
+            diagram.push(cell.unwrap_or(EMPTY_CELL));
+        }
+    }
+    diagram
+}
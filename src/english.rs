@@ -0,0 +1,75 @@
+//! Scoring of decoded text against English bigram statistics
+//!
+//! A transposition keeps the letters of a message and only changes their
+//! order, so single letter frequencies are the same for every candidate
+//! decoding.  The order shows up in which letters are next to each other,
+//! so the score is built from the frequencies of adjacent letter pairs.
+
+/// Approximate frequencies (in percent) of the most common English bigrams
+const BIGRAMS: [(&str, f64); 42] = [
+    ("TH", 3.56), ("HE", 3.07), ("IN", 2.43), ("ER", 2.05), ("AN", 1.99),
+    ("RE", 1.85), ("ON", 1.76), ("AT", 1.49), ("EN", 1.45), ("ND", 1.35),
+    ("TI", 1.34), ("ES", 1.34), ("OR", 1.28), ("TE", 1.20), ("OF", 1.17),
+    ("ED", 1.17), ("IS", 1.13), ("IT", 1.12), ("AL", 1.09), ("AR", 1.07),
+    ("ST", 1.05), ("TO", 1.04), ("NT", 1.04), ("NG", 0.95), ("SE", 0.93),
+    ("HA", 0.93), ("AS", 0.87), ("OU", 0.87), ("IO", 0.83), ("LE", 0.83),
+    ("VE", 0.83), ("CO", 0.79), ("ME", 0.79), ("DE", 0.76), ("HI", 0.76),
+    ("RI", 0.73), ("RO", 0.73), ("IC", 0.70), ("NE", 0.69), ("EA", 0.69),
+    ("RA", 0.69), ("CE", 0.65),
+];
+
+/// The frequency (in percent) assumed for any bigram not in the table
+const RARE_BIGRAM: f64 = 0.01;
+
+/// Score how much a text looks like English
+///
+/// Letters are compared case-insensitively, and anything other than an
+/// ASCII letter breaks the chain of adjacent pairs.
+///
+/// # Arguments
+///
+/// * `text`    The candidate clear-text to score
+///
+/// # Returns
+/// The average log-frequency of the letter pairs in the text.  Larger
+/// (closer to zero) is more English-like, and a text without any letter
+/// pairs scores negative infinity.
+///
+/// # Example
+/// ```
+/// use rail_fence_cipher::english::english_score;
+/// assert!(english_score("THEDEVILISINTHEDETAILS") > english_score("TEITELHDVLSNHDTISEIIEA"));
+/// ```
+pub fn english_score(text: &str) -> f64 {
+    let mut total = 0.0;
+    let mut pairs = 0;
+    let mut previous: Option<char> = None;
+    for c in text.chars() {
+        if !c.is_ascii_alphabetic() {
+            previous = None;
+            continue;
+        }
+        let c = c.to_ascii_uppercase();
+        if let Some(p) = previous {
+            total += bigram_frequency(p, c).ln();
+            pairs += 1;
+        }
+        previous = Some(c);
+    }
+    if pairs == 0 {
+        f64::NEG_INFINITY
+    } else {
+        total / pairs as f64
+    }
+}
+
+/// Look up the frequency of an uppercase bigram
+fn bigram_frequency(first: char, second: char) -> f64 {
+    BIGRAMS
+        .iter()
+        .find(|(pair, _)| {
+            let mut letters = pair.chars();
+            letters.next() == Some(first) && letters.next() == Some(second)
+        })
+        .map_or(RARE_BIGRAM, |(_, frequency)| *frequency)
+}
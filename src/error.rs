@@ -0,0 +1,256 @@
+//! Errors reported by the rail fence cipher
+
+use core::fmt;
+
+/// Errors that can occur while configuring the cipher or decoding a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RailFenceError {
+    /// A fence needs at least one rail
+    ZeroRails,
+    /// The length header of a padded message is missing or does not match
+    /// the text that follows it
+    InvalidPadding,
+    /// The cipher-text was split into a different number of rails than the
+    /// fence has
+    RailCountMismatch {
+        /// The number of rails on the fence
+        expected: usize,
+        /// The number of rails supplied
+        found: usize,
+    },
+    /// A rail holds a different number of characters than a message of the
+    /// combined length would put on it
+    RailLengthMismatch {
+        /// The index of the first inconsistent rail, counting from the top
+        rail: usize,
+        /// The number of characters that belong on the rail
+        expected: usize,
+        /// The number of characters supplied for the rail
+        found: usize,
+    },
+    /// An aligned cipher-text does not divide into rails of equal length
+    UnalignedLength {
+        /// The number of characters in the cipher-text
+        length: usize,
+        /// The number of rails on the fence
+        rails: usize,
+    },
+    /// A delimited cipher-text ends part way through an escape sequence
+    InvalidEscape,
+    /// Decoded digits are not a number that fits in the target type
+    InvalidNumber,
+    /// A frame header is truncated or holds something other than numbers
+    BadHeader,
+    /// A text is not as long as a header, mask or schedule says it should be
+    LengthMismatch {
+        /// The number of characters expected
+        expected: usize,
+        /// The number of characters actually present
+        found: usize,
+    },
+    /// An output buffer cannot hold the result
+    BufferTooSmall {
+        /// The number of bytes the result needs
+        needed: usize,
+        /// The number of bytes in the buffer
+        available: usize,
+    },
+    /// A custom schedule placed a character on a rail the fence does not have
+    ScheduleOutOfRange {
+        /// The position of the character
+        position: usize,
+        /// The rail the schedule chose
+        rail: u32,
+        /// The number of rails on the fence
+        rails: u32,
+    },
+    /// Stored schedule bytes do not hold a permutation of the given length
+    InvalidSchedule,
+    /// A start offset is not smaller than the period of the fence
+    OffsetOutOfRange {
+        /// The requested offset
+        offset: usize,
+        /// The period of the fence
+        period: usize,
+    },
+    /// A message holds a character outside the fence's alphabet
+    InvalidCharacter {
+        /// The character
+        ch: char,
+        /// Its position in the message, counting characters
+        position: usize,
+    },
+    /// A key written as text is malformed, names a setting that does not
+    /// exist, or gives a rail order that does not fit the rails
+    InvalidKey,
+    /// A traversal pattern has no rails, or skips a rail below the lowest
+    /// one it uses
+    InvalidPattern,
+    /// Two options were chosen that cannot work together
+    ConflictingOptions {
+        /// The option that takes precedence
+        first: &'static str,
+        /// The option that would be ignored
+        second: &'static str,
+    },
+    /// A rail order is empty or does not list each of its rails exactly
+    /// once
+    InvalidRailOrder,
+    /// An alphabet lists the same character more than once
+    RepeatedInAlphabet {
+        /// The first character listed again
+        ch: char,
+    },
+}
+
+impl fmt::Display for RailFenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RailFenceError::ZeroRails => write!(f, "a rail fence needs at least one rail"),
+            RailFenceError::InvalidPadding => {
+                write!(f, "the padded message has a missing or invalid length header")
+            }
+            RailFenceError::RailCountMismatch { expected, found } => {
+                write!(f, "expected {} rails but found {}", expected, found)
+            }
+            RailFenceError::RailLengthMismatch { rail, expected, found } => write!(
+                f,
+                "rail {} should hold {} characters but holds {}",
+                rail, expected, found
+            ),
+            RailFenceError::UnalignedLength { length, rails } => write!(
+                f,
+                "{} characters cannot be split into {} rails of equal length",
+                length, rails
+            ),
+            RailFenceError::InvalidEscape => {
+                write!(f, "the delimited cipher-text ends with an escape character")
+            }
+            RailFenceError::InvalidNumber => {
+                write!(f, "the decoded text is not a decimal number in range")
+            }
+            RailFenceError::BadHeader => write!(f, "the frame header is missing or malformed"),
+            RailFenceError::LengthMismatch { expected, found } => write!(
+                f,
+                "expected a text of {} characters but found {}",
+                expected, found
+            ),
+            RailFenceError::BufferTooSmall { needed, available } => write!(
+                f,
+                "the output needs {} bytes but the buffer holds {}",
+                needed, available
+            ),
+            RailFenceError::ScheduleOutOfRange { position, rail, rails } => write!(
+                f,
+                "the schedule placed position {} on rail {} of a {}-rail fence",
+                position, rail, rails
+            ),
+            RailFenceError::InvalidSchedule => {
+                write!(f, "the stored schedule is not a permutation of the given length")
+            }
+            RailFenceError::OffsetOutOfRange { offset, period } => write!(
+                f,
+                "a start offset of {} is not within the period of {}",
+                offset, period
+            ),
+            RailFenceError::InvalidCharacter { ch, position } => {
+                write!(f, "the character {:?} at position {} is not in the alphabet", ch, position)
+            }
+            RailFenceError::InvalidKey => {
+                write!(f, "the key is not a list of rails=, offset= and order= settings for a working fence")
+            }
+            RailFenceError::InvalidPattern => {
+                write!(f, "the traversal pattern is empty or leaves out one of its rails")
+            }
+            RailFenceError::ConflictingOptions { first, second } => {
+                write!(f, "{} cannot be combined with {}", first, second)
+            }
+            RailFenceError::InvalidRailOrder => {
+                write!(f, "the rail order does not list each of its rails exactly once")
+            }
+            RailFenceError::RepeatedInAlphabet { ch } => write!(f, "the alphabet lists {:?} more than once", ch),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RailFenceError {}
+
+/// What an error is about
+///
+/// This is the kind of problem, not the call that reported it: a fence
+/// that cannot work is a construction problem even when `try_encode` or
+/// `try_decode` is the first to notice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorContext {
+    /// The fence and its options, including a custom schedule
+    Construction,
+    /// The message being encoded, or where its cipher-text is to go
+    Encode,
+    /// The cipher-text being decoded
+    Decode,
+    /// The header or layout of a stored or transmitted message
+    Framing,
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ErrorContext::Construction => "construction",
+            ErrorContext::Encode => "encoding",
+            ErrorContext::Decode => "decoding",
+            ErrorContext::Framing => "framing",
+        })
+    }
+}
+
+impl RailFenceError {
+    /// What this kind of error is about
+    ///
+    /// Each kind of error has one context, whichever call reports it.  A
+    /// schedule that places characters on rails the fence does not have is
+    /// a problem with the fence, so it counts as construction, and a length
+    /// that does not match is a problem with the cipher-text, so it counts
+    /// as decoding.
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// assert_eq!(ErrorContext::Construction, RailFenceError::ZeroRails.context());
+    /// assert_eq!(ErrorContext::Framing, RailFenceError::BadHeader.context());
+    /// ```
+    pub fn context(&self) -> ErrorContext {
+        match self {
+            RailFenceError::ZeroRails
+            | RailFenceError::OffsetOutOfRange { .. }
+            | RailFenceError::InvalidKey
+            | RailFenceError::InvalidPattern
+            | RailFenceError::ConflictingOptions { .. }
+            | RailFenceError::InvalidRailOrder
+            | RailFenceError::RepeatedInAlphabet { .. }
+            | RailFenceError::ScheduleOutOfRange { .. } => ErrorContext::Construction,
+            RailFenceError::BufferTooSmall { .. } | RailFenceError::InvalidCharacter { .. } => ErrorContext::Encode,
+            RailFenceError::RailCountMismatch { .. }
+            | RailFenceError::RailLengthMismatch { .. }
+            | RailFenceError::UnalignedLength { .. }
+            | RailFenceError::LengthMismatch { .. }
+            | RailFenceError::InvalidNumber => ErrorContext::Decode,
+            RailFenceError::InvalidPadding
+            | RailFenceError::InvalidEscape
+            | RailFenceError::BadHeader
+            | RailFenceError::InvalidSchedule => ErrorContext::Framing,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<RailFenceError> for std::io::Error {
+    /// Report a cipher error through I/O, as invalid input or data
+    fn from(error: RailFenceError) -> std::io::Error {
+        let kind = match error.context() {
+            ErrorContext::Construction => std::io::ErrorKind::InvalidInput,
+            _ => std::io::ErrorKind::InvalidData,
+        };
+        std::io::Error::new(kind, error)
+    }
+}
@@ -0,0 +1,99 @@
+//! C bindings
+//!
+//! A fence is handed to C as an opaque pointer from `rail_fence_new` and
+//! released with `rail_fence_free`.  Strings go in and out as
+//! NUL-terminated UTF-8; every string returned is newly allocated by Rust
+//! and must be released with `rail_fence_string_free`, not `free`.
+//! Failures, such as a null argument or text that is not UTF-8, return a
+//! null pointer rather than unwinding into C.
+//!
+//! `include/rail_fence_cipher.h` declares these functions, and can be
+//! regenerated with `cbindgen --config cbindgen.toml --output
+//! include/rail_fence_cipher.h`.
+
+use core::ptr;
+use std::boxed::Box;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::string::String;
+
+use crate::RailFence;
+
+/// Create a fence with the given number of rails
+///
+/// Returns null if `rails` is 0.
+#[no_mangle]
+pub extern "C" fn rail_fence_new(rails: u32) -> *mut RailFence {
+    match RailFence::try_new(rails) {
+        Ok(fence) => Box::into_raw(Box::new(fence)),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Release a fence from `rail_fence_new`
+///
+/// # Safety
+/// `fence` must be null or a pointer returned by `rail_fence_new` that has
+/// not already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn rail_fence_free(fence: *mut RailFence) {
+    if !fence.is_null() {
+        drop(Box::from_raw(fence));
+    }
+}
+
+/// Encode a NUL-terminated UTF-8 message
+///
+/// Returns null if either pointer is null or `text` is not UTF-8.
+///
+/// # Safety
+/// `fence` must be null or a live pointer from `rail_fence_new`, and `text`
+/// must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rail_fence_encode(fence: *const RailFence, text: *const c_char) -> *mut c_char {
+    transform(fence, text, RailFence::try_encode)
+}
+
+/// Decode a NUL-terminated UTF-8 cipher-text
+///
+/// Returns null if either pointer is null or `cipher` is not UTF-8.
+///
+/// # Safety
+/// `fence` must be null or a live pointer from `rail_fence_new`, and
+/// `cipher` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rail_fence_decode(fence: *const RailFence, cipher: *const c_char) -> *mut c_char {
+    transform(fence, cipher, RailFence::try_decode)
+}
+
+/// Release a string returned by `rail_fence_encode` or `rail_fence_decode`
+///
+/// # Safety
+/// `s` must be null or a string returned by this library that has not
+/// already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn rail_fence_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Run a fallible string method across the C boundary
+unsafe fn transform<E>(
+    fence: *const RailFence,
+    text: *const c_char,
+    method: impl Fn(&RailFence, &str) -> Result<String, E>,
+) -> *mut c_char {
+    if fence.is_null() || text.is_null() {
+        return ptr::null_mut();
+    }
+    let text = match CStr::from_ptr(text).to_str() {
+        Ok(text) => text,
+        Err(_) => return ptr::null_mut(),
+    };
+    // The result has the same characters as the text, so no NUL either
+    match method(&*fence, text).map(CString::new) {
+        Ok(Ok(result)) => result.into_raw(),
+        _ => ptr::null_mut(),
+    }
+}
@@ -0,0 +1,149 @@
+//! Encoding and decoding whole files
+//!
+//! The input is read through a buffered reader and the output written
+//! through a buffered writer.  How the line breaks of the input are treated
+//! is up to a `NewlinePolicy`: by default each line is its own message, so
+//! a file of any size is worked through one line at a time and its line
+//! breaks stay where they were.
+//!
+//! The output file is created before the input is read to the end, so the
+//! two paths must not name the same file.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::string::String;
+
+use crate::lines::split_line_ending;
+use crate::{RailFence, RailFenceError};
+
+/// How `encode_file_with` and `decode_file_with` treat line breaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum NewlinePolicy {
+    /// Encode or decode each line on its own and keep its line break, `\n`
+    /// or `\r\n`, as it is
+    #[default]
+    PerLine,
+    /// Treat the whole file as one message, apart from a single line break
+    /// at the very end, which is kept as it is
+    WholeTrimmed,
+    /// Treat the whole file, line breaks and all, as one message
+    Whole,
+}
+
+impl RailFence {
+    /// Encode a file line by line into another file
+    ///
+    /// # Arguments
+    ///
+    /// * `input`   The clear-text file to read
+    /// * `output`  The file to write the cipher-text to, which is created
+    ///   or truncated
+    ///
+    /// # Returns
+    /// An error if either file cannot be used, the input is not UTF-8, or a
+    /// line cannot be encoded, as for `try_encode`
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let dir = std::env::temp_dir();
+    /// let (clear, secret) = (dir.join("encode_file_clear.txt"), dir.join("encode_file_secret.txt"));
+    /// std::fs::write(&clear, "RUSTISGREAT\nEXERCISES\n").unwrap();
+    /// RailFence::new(4).encode_file(&clear, &secret).unwrap();
+    /// assert_eq!("RGUSRSIETTA\nESXIEECSR\n", std::fs::read_to_string(&secret).unwrap());
+    /// ```
+    pub fn encode_file(&self, input: &Path, output: &Path) -> io::Result<()> {
+        self.encode_file_with(input, output, NewlinePolicy::PerLine)
+    }
+
+    /// Decode a file written by `encode_file` into another file
+    ///
+    /// # Arguments
+    ///
+    /// * `input`   The cipher-text file to read
+    /// * `output`  The file to write the clear-text to, which is created or
+    ///   truncated
+    ///
+    /// # Returns
+    /// An error as for `encode_file`, or if a line cannot be decoded, as
+    /// for `try_decode`
+    pub fn decode_file(&self, input: &Path, output: &Path) -> io::Result<()> {
+        self.decode_file_with(input, output, NewlinePolicy::PerLine)
+    }
+
+    /// Encode a file into another file, treating line breaks as asked
+    ///
+    /// # Arguments
+    ///
+    /// * `input`       The clear-text file to read
+    /// * `output`      The file to write the cipher-text to
+    /// * `newlines`    How the line breaks of the input are treated
+    ///
+    /// # Returns
+    /// An error as for `encode_file`
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let dir = std::env::temp_dir();
+    /// let (clear, secret) = (dir.join("encode_file_with_clear.txt"), dir.join("encode_file_with_secret.txt"));
+    /// std::fs::write(&clear, "RUSTIS\nGREAT\n").unwrap();
+    /// let cipher = RailFence::new(4);
+    /// cipher.encode_file_with(&clear, &secret, NewlinePolicy::WholeTrimmed).unwrap();
+    /// assert_eq!(cipher.encode("RUSTIS\nGREAT") + "\n", std::fs::read_to_string(&secret).unwrap());
+    /// ```
+    pub fn encode_file_with(&self, input: &Path, output: &Path, newlines: NewlinePolicy) -> io::Result<()> {
+        transform_file(input, output, newlines, |text| self.try_encode(text))
+    }
+
+    /// Decode a file written by `encode_file_with` into another file
+    ///
+    /// # Arguments
+    ///
+    /// * `input`       The cipher-text file to read
+    /// * `output`      The file to write the clear-text to
+    /// * `newlines`    The policy the file was encoded with
+    ///
+    /// # Returns
+    /// An error as for `decode_file`
+    pub fn decode_file_with(&self, input: &Path, output: &Path, newlines: NewlinePolicy) -> io::Result<()> {
+        transform_file(input, output, newlines, |text| self.try_decode(text))
+    }
+}
+
+/// Read one file, transform it as the policy asks and write the result to
+/// another
+fn transform_file(
+    input: &Path,
+    output: &Path,
+    newlines: NewlinePolicy,
+    transform: impl Fn(&str) -> Result<String, RailFenceError>,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(File::open(input)?);
+    let mut writer = BufWriter::new(File::create(output)?);
+    match newlines {
+        NewlinePolicy::PerLine => {
+            let mut line = String::new();
+            while reader.read_line(&mut line)? > 0 {
+                let (text, ending) = split_line_ending(&line);
+                writer.write_all(transform(text)?.as_bytes())?;
+                writer.write_all(ending.as_bytes())?;
+                line.clear();
+            }
+        }
+        NewlinePolicy::WholeTrimmed | NewlinePolicy::Whole => {
+            let mut text = String::new();
+            reader.read_to_string(&mut text)?;
+            let (text, ending) = match newlines {
+                NewlinePolicy::WholeTrimmed => split_line_ending(&text),
+                _ => (&text[..], ""),
+            };
+            writer.write_all(transform(text)?.as_bytes())?;
+            writer.write_all(ending.as_bytes())?;
+        }
+    }
+    writer.flush()
+}
+
@@ -0,0 +1,142 @@
+//! A fence with the number of rails fixed at compile time
+//!
+//! `ConstRailFence` keeps its per-rail bookkeeping in arrays sized by the
+//! rail count, and reads and writes only caller-provided buffers, so it
+//! never touches the heap.  That suits microcontrollers, where allocator
+//! churn costs more than the transposition itself.  It only encodes the
+//! plain zig-zag that `RailFence::new` starts with, and works on bytes, as
+//! `encode_bytes` does.
+
+use crate::{RailFence, RailFenceError};
+
+/// A plain rail fence with `RAILS` rails that never allocates
+///
+/// # Example
+/// ```
+/// use rail_fence_cipher::*;
+/// let cipher = ConstRailFence::<4>::new();
+/// let mut out = [0u8; 11];
+/// cipher.encode_into_slice(b"RUSTISGREAT", &mut out).unwrap();
+/// assert_eq!(b"RGUSRSIETTA", &out);
+/// let mut back = [0u8; 11];
+/// cipher.decode_into_slice(&out, &mut back).unwrap();
+/// assert_eq!(b"RUSTISGREAT", &back);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstRailFence<const RAILS: usize>;
+
+impl<const RAILS: usize> Default for ConstRailFence<RAILS> {
+    fn default() -> ConstRailFence<RAILS> {
+        ConstRailFence::new()
+    }
+}
+
+impl<const RAILS: usize> ConstRailFence<RAILS> {
+    /// Stops a fence with no rails from compiling
+    const HAS_RAILS: () = assert!(RAILS > 0, "a rail fence needs at least one rail");
+
+    /// Create the fence
+    ///
+    /// A rail count of 0 is rejected when the program is compiled.
+    pub fn new() -> ConstRailFence<RAILS> {
+        let () = Self::HAS_RAILS;
+        ConstRailFence
+    }
+
+    /// The number of rails on this fence
+    pub fn rails(&self) -> usize {
+        RAILS
+    }
+
+    /// The same fence as a `RailFence`, for everything else the crate offers
+    pub fn to_rail_fence(&self) -> RailFence {
+        RailFence::new(RAILS as u32)
+    }
+
+    /// Encode bytes into a caller-provided buffer
+    ///
+    /// # Arguments
+    ///
+    /// * `input`   The clear-text bytes to encode
+    /// * `out`     The buffer to write the cipher-text bytes to
+    ///
+    /// # Returns
+    /// The number of bytes written, which is the length of `input`, or
+    /// `RailFenceError::BufferTooSmall` if `out` is shorter than `input`, in
+    /// which case nothing is written
+    pub fn encode_into_slice(&self, input: &[u8], out: &mut [u8]) -> Result<usize, RailFenceError> {
+        check_room(input, out)?;
+        let mut next = rail_starts::<RAILS>(input.len());
+        for (position, &byte) in input.iter().enumerate() {
+            let rail = rail_at::<RAILS>(position);
+            out[next[rail]] = byte;
+            next[rail] += 1;
+        }
+        Ok(input.len())
+    }
+
+    /// Decode bytes from `encode_into_slice` into a caller-provided buffer
+    ///
+    /// # Arguments
+    ///
+    /// * `input`   The cipher-text bytes to decode
+    /// * `out`     The buffer to write the clear-text bytes to
+    ///
+    /// # Returns
+    /// The number of bytes written, or an error as for `encode_into_slice`
+    pub fn decode_into_slice(&self, input: &[u8], out: &mut [u8]) -> Result<usize, RailFenceError> {
+        check_room(input, out)?;
+        let mut next = rail_starts::<RAILS>(input.len());
+        for (position, byte) in out[..input.len()].iter_mut().enumerate() {
+            let rail = rail_at::<RAILS>(position);
+            *byte = input[next[rail]];
+            next[rail] += 1;
+        }
+        Ok(input.len())
+    }
+}
+
+/// Check that the output buffer can hold the whole input
+fn check_room(input: &[u8], out: &[u8]) -> Result<(), RailFenceError> {
+    if out.len() < input.len() {
+        return Err(RailFenceError::BufferTooSmall {
+            needed: input.len(),
+            available: out.len(),
+        });
+    }
+    Ok(())
+}
+
+/// The rail a position lands on in the plain zig-zag
+fn rail_at<const RAILS: usize>(position: usize) -> usize {
+    if RAILS == 1 {
+        return 0;
+    }
+    let period = 2 * (RAILS - 1);
+    let phase = position % period;
+    if phase < RAILS {
+        phase
+    } else {
+        period - phase
+    }
+}
+
+/// Where each rail starts in the cipher-text of a message
+fn rail_starts<const RAILS: usize>(len: usize) -> [usize; RAILS] {
+    let mut lengths = [0; RAILS];
+    let period = if RAILS == 1 { 1 } else { 2 * (RAILS - 1) };
+    // Every full period visits each rail the same number of times
+    for phase in 0..period {
+        lengths[rail_at::<RAILS>(phase)] += len / period;
+    }
+    for phase in 0..len % period {
+        lengths[rail_at::<RAILS>(phase)] += 1;
+    }
+    let mut starts = [0; RAILS];
+    let mut start = 0;
+    for (rail_start, length) in starts.iter_mut().zip(lengths) {
+        *rail_start = start;
+        start += length;
+    }
+    starts
+}
@@ -0,0 +1,175 @@
+//! Self-describing cipher-text
+//!
+//! A frame is the rail count and the message length in decimal, each
+//! followed by a `:`, and then the cipher-text, e.g. `4:11:RGUSRSIETTA`.
+//! The receiver does not need to know the rail count in advance, and a
+//! frame that has been truncated or corrupted is reported as an error
+//! instead of decoding to garbage.  Other options, such as the turn policy,
+//! are not recorded and must match between the two ends.
+
+use alloc::format;
+use alloc::string::String;
+
+use crate::{RailFence, RailFenceError};
+use core::convert::TryFrom;
+
+/// Ends each field of the frame header
+const FIELD_END: char = ':';
+
+impl RailFence {
+    /// Encode the message into a frame that records the rail count
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    ///
+    /// # Returns
+    /// The header followed by the cipher-text
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// assert_eq!("4:11:RGUSRSIETTA", cipher.encode_framed("RUSTISGREAT"))
+    /// ```
+    pub fn encode_framed(&self, text: &str) -> String {
+        format!(
+            "{}{}{}{}{}",
+            self.rails,
+            FIELD_END,
+            self.encoded_len(text),
+            FIELD_END,
+            self.encode(text)
+        )
+    }
+
+    /// Decode a frame produced by `encode_framed`
+    ///
+    /// The rail count comes from the frame header, not from this fence.
+    /// Decoding takes time and memory in proportion to the length of the
+    /// frame, however many rails the header claims, so a frame from an
+    /// untrusted source cannot make it allocate a count for every rail.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame`   The framed cipher-text
+    ///
+    /// # Returns
+    /// The clear-text message, or an error for a malformed frame:
+    /// * `RailFenceError::BadHeader` if either header field is missing or
+    ///   is not a number
+    /// * `RailFenceError::ZeroRails` if the header gives a rail count of 0
+    /// * `RailFenceError::LengthMismatch` if the cipher-text is not as long
+    ///   as the header says
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(2);
+    /// assert_eq!(Ok(String::from("RUSTISGREAT")), cipher.decode_framed("4:11:RGUSRSIETTA"));
+    /// assert_eq!(Err(RailFenceError::LengthMismatch { expected: 11, found: 10 }), cipher.decode_framed("4:11:RGUSRSIETT"));
+    /// ```
+    pub fn decode_framed(&self, frame: &str) -> Result<String, RailFenceError> {
+        let (rails, rest) = header_field(frame)?;
+        let (length, cipher) = header_field(rest)?;
+        let rails = u32::try_from(rails).map_err(|_| RailFenceError::BadHeader)?;
+        let found = cipher.chars().count();
+        if found != length {
+            return Err(RailFenceError::LengthMismatch { expected: length, found });
+        }
+        let mut fence = self.clone();
+        fence.set_rails(rails)?;
+        Ok(fence.decode(cipher))
+    }
+
+    /// Whether a string is a well-formed frame
+    ///
+    /// A valid frame has a rail count of at least 1 and a length in its
+    /// header, and exactly that many characters after it, so that
+    /// `decode_framed` accepts it.  Whether the clear-text makes sense
+    /// cannot be told without decoding it.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame`   The string to check
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// assert!(cipher.could_be_framed("4:11:RGUSRSIETTA"));
+    /// assert!(!cipher.could_be_framed("4:12:RGUSRSIETTA"));
+    /// assert!(!cipher.could_be_framed("RGUSRSIETTA"));
+    /// ```
+    pub fn could_be_framed(&self, frame: &str) -> bool {
+        let fields = header_field(frame).and_then(|(rails, rest)| Ok((rails, header_field(rest)?)));
+        match fields {
+            Ok((rails, (length, cipher))) => {
+                u32::try_from(rails).is_ok_and(|rails| rails > 0) && cipher.chars().count() == length
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Decode a frame on a best-effort basis, even if it has been damaged
+    ///
+    /// This is lossy and experimental.  A cipher-text that is shorter than
+    /// its header says is assumed to have lost characters from its end,
+    /// which are replaced by `MISSING_CHAR`, and one that is longer is cut
+    /// short.  The rest of the message is then decoded as usual, so one
+    /// lost character damages one character of the clear-text rather than
+    /// scrambling all of it.  Characters lost or added elsewhere still
+    /// garble the clear-text after that point on their rail.  If the header
+    /// itself is unreadable, the whole input is decoded with this fence,
+    /// and if it claims more characters are missing than are present, the
+    /// cipher-text is decoded as it is, without any filled in.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame`   The framed cipher-text
+    ///
+    /// # Returns
+    /// The clear-text, as recovered as possible
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(2);
+    /// // The last cipher-text character was the A on the bottom rail
+    /// assert_eq!("RUSTISGRE\u{fffd}T", cipher.decode_lossy("4:11:RGUSRSIETT"));
+    /// ```
+    pub fn decode_lossy(&self, frame: &str) -> String {
+        let header = header_field(frame).and_then(|(rails, rest)| {
+            let (length, cipher) = header_field(rest)?;
+            let rails = u32::try_from(rails).map_err(|_| RailFenceError::BadHeader)?;
+            let mut fence = self.clone();
+            fence.set_rails(rails)?;
+            Ok((fence, length, cipher))
+        });
+        let (fence, length, cipher) = match header {
+            Ok(header) => header,
+            Err(_) => return self.decode(frame),
+        };
+        let found = cipher.chars().count();
+        if length > found && length - found > found {
+            // More than half of the message would be made up, so the length
+            // is more likely damaged than the cipher-text
+            return fence.decode(cipher);
+        }
+        let repaired: String = cipher.chars().chain(core::iter::repeat(MISSING_CHAR)).take(length).collect();
+        fence.decode(repaired)
+    }
+}
+
+/// Stands in for characters missing from a damaged frame
+pub const MISSING_CHAR: char = '\u{fffd}';
+
+/// Split a decimal field off the front of a frame
+pub(crate) fn header_field(frame: &str) -> Result<(usize, &str), RailFenceError> {
+    let (field, rest) = frame.split_once(FIELD_END).ok_or(RailFenceError::BadHeader)?;
+    if field.is_empty() || !field.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(RailFenceError::BadHeader);
+    }
+    let value = field.parse().map_err(|_| RailFenceError::BadHeader)?;
+    Ok((value, rest))
+}
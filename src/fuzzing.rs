@@ -0,0 +1,115 @@
+//! Fuzzing code built on this crate
+//!
+//! `roundtrips` states the crate's one guarantee about every message, so
+//! fuzz targets can check it directly.  With the `arbitrary` feature,
+//! `RailFence`, `KeySpec` and the option enums implement
+//! `arbitrary::Arbitrary`, so fuzz targets in other crates can take fences
+//! as input without generating them by hand.  Every fence made this way
+//! passes `RailFenceBuilder::validate` and only uses options that keep
+//! every character as it is, so it decodes whatever it encodes.
+//!
+//! # Example
+//! ```
+//! # #[cfg(feature = "arbitrary")]
+//! # {
+//! use arbitrary::{Arbitrary, Unstructured};
+//! use rail_fence_cipher::*;
+//! let mut input = Unstructured::new(b"fuzzer input bytes");
+//! let cipher = RailFence::arbitrary(&mut input).unwrap();
+//! let text = String::arbitrary(&mut input).unwrap();
+//! assert_eq!(text, cipher.decode(cipher.encode(&text)));
+//! # }
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::RailFence;
+#[cfg(feature = "arbitrary")]
+use crate::{KeySpec, RailFenceBuilder, ReadOrder, TurnPolicy, Wave};
+#[cfg(feature = "arbitrary")]
+use arbitrary::{Arbitrary, Unstructured};
+
+/// The most rails an arbitrary fence or key has
+#[cfg(feature = "arbitrary")]
+const MAX_ARBITRARY_RAILS: u32 = 32;
+
+/// Check that a plain fence decodes a message back from its own cipher-text
+///
+/// The crate guarantees this for every rail count of at least 1 and every
+/// text, both through `try_encode` and `try_decode` and through
+/// `encode_bytes` and `decode_bytes` on the text's UTF-8 bytes.  A single
+/// rail, or more rails than the text has characters, leaves the text as it
+/// is, which still counts.  A fence with no rails cannot encode anything,
+/// so 0 rails always gives `false`.
+///
+/// # Arguments
+///
+/// * `rails`   The number of rails for the fence
+/// * `text`    The message to send through it
+///
+/// # Returns
+/// Whether every way through the fence gave back `text`
+///
+/// # Example
+/// ```
+/// use rail_fence_cipher::fuzzing::roundtrips;
+/// assert!(roundtrips(4, "RUSTISGREAT"));
+/// assert!(roundtrips(100, "short"));
+/// assert!(!roundtrips(0, "RUSTISGREAT"));
+/// ```
+pub fn roundtrips(rails: u32, text: &str) -> bool {
+    let fence = RailFence::new(rails);
+    let chars = fence.try_encode(text).and_then(|cipher| fence.try_decode(&cipher));
+    if chars.as_deref() != Ok(text) {
+        return false;
+    }
+    let bytes: Vec<u8> = fence.decode_bytes(&fence.encode_bytes(text.as_bytes()));
+    bytes == text.as_bytes()
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> Arbitrary<'a> for RailFence {
+    /// Build a fence with between 1 and 32 rails, a turn policy or sawtooth
+    /// wave, a start offset within its period and direction, and a read
+    /// order
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<RailFence> {
+        let rails = u.int_in_range(1..=MAX_ARBITRARY_RAILS)?;
+        // A sawtooth wave never turns, so it cannot have a turn policy
+        let (turn, wave) = match u.int_in_range(0..=2)? {
+            0 => (TurnPolicy::Bounce, Wave::Triangle),
+            1 => (TurnPolicy::Dwell, Wave::Triangle),
+            _ => (TurnPolicy::Bounce, Wave::Sawtooth),
+        };
+        let period = RailFence::new(rails).with_turn_policy(turn).with_wave(wave).period();
+        let offset = u.int_in_range(0..=period - 1)?;
+        let builder = RailFenceBuilder::new(rails).turn_policy(turn).wave(wave).offset(offset).upward(u.arbitrary()?);
+        // Column-major reading cannot be combined with alternating periods
+        let builder = match u.int_in_range(0..=2)? {
+            0 => builder.read_order(ReadOrder::ColumnMajor),
+            1 => builder.period_alternating(true),
+            _ => builder,
+        };
+        Ok(builder.build().expect("arbitrary options are always valid"))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> Arbitrary<'a> for KeySpec {
+    /// Pick a key with between 1 and 32 rails, a start offset within the
+    /// period, and sometimes a keyed rail order
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<KeySpec> {
+        let rails = u.int_in_range(1..=MAX_ARBITRARY_RAILS)?;
+        let offset = u.int_in_range(0..=RailFence::new(rails).period() - 1)?;
+        let order = if u.arbitrary()? {
+            let mut order: Vec<u32> = (0..rails).collect();
+            // Fisher-Yates, from the last rail down
+            for i in (1..order.len()).rev() {
+                order.swap(i, u.int_in_range(0..=i)?);
+            }
+            Some(order)
+        } else {
+            None
+        };
+        Ok(KeySpec { rails, offset, order })
+    }
+}
@@ -0,0 +1,66 @@
+//! Transposing whole grapheme clusters
+//!
+//! What a reader sees as one symbol can be several characters: a letter
+//! and its combining accent, a flag made of two regional indicators, or a
+//! family emoji whose people are joined by zero-width joiners.  Moving
+//! these characters independently leaves broken symbols in the
+//! cipher-text, so this mode moves each extended grapheme cluster as a
+//! unit.
+//!
+//! Decoding splits the cipher-text into clusters again, so a message only
+//! round-trips if its clusters cannot merge with their new neighbours.
+//! That holds for ordinary text, but not for stray pieces such as a
+//! combining accent or a single regional indicator with nothing to attach
+//! to in the clear-text.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::RailFence;
+
+impl RailFence {
+    /// Encode the message, moving grapheme clusters instead of characters
+    ///
+    /// Each extended grapheme cluster, including zero-width-joiner emoji
+    /// sequences, keeps its characters together and in order.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    ///
+    /// # Returns
+    /// The cipher-text, made of exactly the grapheme clusters of `text`
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(2);
+    /// assert_eq!("c🇳🇿éa", cipher.encode_graphemes("cé🇳🇿a"));
+    /// ```
+    pub fn encode_graphemes(&self, text: &str) -> String {
+        let clusters: Vec<&str> = text.graphemes(true).collect();
+        self.encode_items(&clusters).concat()
+    }
+
+    /// Decode a message encoded with `encode_graphemes`
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  The cipher-text string to decode
+    ///
+    /// # Returns
+    /// The clear-text message
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(2);
+    /// assert_eq!("cé🇳🇿a", cipher.decode_graphemes("c🇳🇿éa"));
+    /// ```
+    pub fn decode_graphemes(&self, cipher: &str) -> String {
+        let clusters: Vec<&str> = cipher.graphemes(true).collect();
+        self.decode_items(&clusters).concat()
+    }
+}
@@ -0,0 +1,70 @@
+//! Fences whose height changes from one zig-zag to the next
+//!
+//! Each trip down the fence and back up again uses the next height from a
+//! repeating pattern, so with heights `[3, 4, 5, 4, 3]` the first zig-zag
+//! spans three rails, the next four, and so on.  The fence has as many
+//! rails as the tallest height.  Every zig-zag starts at the top rail, so
+//! the whole layout is fixed by the pattern and decoding simply replays it.
+
+use alloc::vec::Vec;
+
+use crate::RailFence;
+
+impl RailFence {
+    /// Create a fence whose zig-zags follow a repeating pattern of heights
+    ///
+    /// A zig-zag of height `h` takes `2 * (h - 1)` characters, going down
+    /// from the top rail to rail `h - 1` and back up, or one character, on
+    /// the top rail, if `h` is 1.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` The height of each zig-zag, repeated as often as needed
+    ///
+    /// # Returns
+    /// A fence with as many rails as the largest height
+    ///
+    /// # Panics
+    /// If the pattern is empty or contains a height of 0, neither of which
+    /// could place any characters
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::with_height_fn(vec![2, 3]);
+    /// assert_eq!(3, cipher.rails());
+    /// // 0 1 | 0 1 2 1 | 0 1 | 0 1 2
+    /// assert_eq!("RSGEUTSRAIT", cipher.encode("RUSTISGREAT"));
+    /// assert_eq!("RUSTISGREAT", cipher.decode("RSGEUTSRAIT"));
+    /// ```
+    pub fn with_height_fn(pattern: Vec<u32>) -> RailFence {
+        assert!(
+            !pattern.is_empty() && !pattern.contains(&0),
+            "a height pattern needs at least one height and every height must be at least 1"
+        );
+        let rails = pattern.iter().copied().max().unwrap_or(1);
+        let lengths: Vec<usize> = pattern.iter().map(|&height| zig_zag_len(height)).collect();
+        let cycle: usize = lengths.iter().sum();
+        RailFence::with_schedule(rails, move |position| {
+            let mut phase = position % cycle;
+            for (&height, &len) in pattern.iter().zip(&lengths) {
+                if phase < len {
+                    let height = height as usize;
+                    let rail = if phase < height { phase } else { len - phase };
+                    return rail as u32;
+                }
+                phase -= len;
+            }
+            unreachable!("the phase is within the pattern")
+        })
+    }
+}
+
+/// The number of characters one zig-zag of this height takes
+fn zig_zag_len(height: u32) -> usize {
+    if height <= 1 {
+        1
+    } else {
+        2 * (height as usize - 1)
+    }
+}
@@ -0,0 +1,142 @@
+//! Encoding and decoding messages that arrive in pieces
+//!
+//! Where a character ends up depends on the length of the whole message:
+//! every rail after the first starts at a point in the cipher-text set by
+//! how many characters the rails above it hold, and the same goes for
+//! reading a cipher-text back.  So no output is available until the last
+//! piece has arrived, and the encoder and decoder only collect the pieces,
+//! holding one copy of the message, until `finalize` transposes it in one
+//! go.  For output as the data arrives, split the stream into blocks with
+//! `RailFence::writer` instead.
+//!
+//! With the `zeroize` feature, the collected text is wiped when the
+//! encoder or decoder is dropped, including when `finalize` consumes it.
+
+use alloc::string::String;
+
+use crate::wipe::push_str;
+use crate::RailFence;
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Collects clear-text in pieces and encodes it all at the end
+#[derive(Debug, Clone)]
+pub struct RailFenceEncoder {
+    fence: RailFence,
+    text: String,
+}
+
+/// Collects cipher-text in pieces and decodes it all at the end
+#[derive(Debug, Clone)]
+pub struct RailFenceDecoder {
+    fence: RailFence,
+    cipher: String,
+}
+
+impl RailFence {
+    /// Start encoding a message that arrives in pieces
+    ///
+    /// # Returns
+    /// An encoder that gives the same cipher-text as `encode` on the whole
+    /// message
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let mut encoder = RailFence::new(4).encoder();
+    /// encoder.update("RUST");
+    /// encoder.update("ISGREAT");
+    /// assert_eq!("RGUSRSIETTA", encoder.finalize());
+    /// ```
+    pub fn encoder(&self) -> RailFenceEncoder {
+        RailFenceEncoder { fence: self.clone(), text: String::new() }
+    }
+
+    /// Start decoding a cipher-text that arrives in pieces
+    ///
+    /// # Returns
+    /// A decoder that gives the same clear-text as `decode` on the whole
+    /// cipher-text
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let mut decoder = RailFence::new(4).decoder();
+    /// decoder.update("RGUSRS");
+    /// decoder.update("IETTA");
+    /// assert_eq!("RUSTISGREAT", decoder.finalize());
+    /// ```
+    pub fn decoder(&self) -> RailFenceDecoder {
+        RailFenceDecoder { fence: self.clone(), cipher: String::new() }
+    }
+}
+
+impl RailFenceEncoder {
+    /// Start encoding on a plain fence with the specified number of rails
+    pub fn new(rails: u32) -> RailFenceEncoder {
+        RailFence::new(rails).encoder()
+    }
+
+    /// Add the next piece of the clear-text
+    pub fn update(&mut self, chunk: &str) {
+        push_str(&mut self.text, chunk);
+    }
+
+    /// Encode everything added so far, as `RailFence::encode` does
+    pub fn finalize(self) -> String {
+        self.fence.encode(&self.text)
+    }
+}
+
+impl RailFenceDecoder {
+    /// Start decoding on a plain fence with the specified number of rails
+    pub fn new(rails: u32) -> RailFenceDecoder {
+        RailFence::new(rails).decoder()
+    }
+
+    /// Add the next piece of the cipher-text
+    pub fn update(&mut self, chunk: &str) {
+        push_str(&mut self.cipher, chunk);
+    }
+
+    /// Decode everything added so far, as `RailFence::decode` does
+    pub fn finalize(self) -> String {
+        self.fence.decode(&self.cipher)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for RailFenceEncoder {
+    /// Wipe the clear-text collected so far, starting the message again
+    fn zeroize(&mut self) {
+        self.text.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for RailFenceEncoder {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl ZeroizeOnDrop for RailFenceEncoder {}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for RailFenceDecoder {
+    /// Wipe the cipher-text collected so far, starting the message again
+    fn zeroize(&mut self) {
+        self.cipher.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for RailFenceDecoder {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl ZeroizeOnDrop for RailFenceDecoder {}
@@ -0,0 +1,136 @@
+//! Lazy iteration over transposed text
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::wipe::{wipe_cow, working};
+use crate::{CharFilter, RailFence};
+
+impl RailFence {
+    /// Encode the message lazily, one cipher-text character at a time
+    ///
+    /// The characters are picked out of the clear-text by their place in
+    /// the permutation, rather than gathered into a string per rail first,
+    /// so the cipher-text can be fed straight into another transform.
+    /// `decode_iter` is the matching decoder.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    ///
+    /// # Returns
+    /// An iterator over the cipher-text characters, which yields exactly
+    /// the characters of `encode(text)`
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// let shouted: String = cipher.encode_chars("rustisgreat").flat_map(char::to_uppercase).collect();
+    /// assert_eq!("RGUSRSIETTA", shouted);
+    /// ```
+    pub fn encode_chars<'a>(&self, text: &'a str) -> impl Iterator<Item = char> + 'a {
+        let normalized = self.normalize(text);
+        let padded = self.pad_to_period(&normalized);
+        let chars = working(padded.chars().collect::<Vec<char>>());
+        wipe_cow(padded);
+        wipe_cow(normalized);
+        let order = if self.char_filter == CharFilter::PreservePositions {
+            letters_only(&chars, |len| self.inverse_permutation(len))
+        } else {
+            self.inverse_permutation(chars.len())
+        };
+        let group_len = self.grouping.unwrap_or(0);
+        order.into_iter().enumerate().flat_map(move |(i, from)| {
+            let space = if group_len > 0 && i > 0 && i.is_multiple_of(group_len) { Some(' ') } else { None };
+            space.into_iter().chain(Some(chars[from]))
+        })
+    }
+
+    /// Decode the message lazily, one clear-text character at a time
+    ///
+    /// The cipher-text is still split up front, because the first clear-text
+    /// characters come from every rail, but the characters are only put back
+    /// in order as they are asked for.  Stopping early, for example to read
+    /// only the start of a message, skips the rest of that work.
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  The cipher-text string to decode
+    ///
+    /// # Returns
+    /// An iterator over the clear-text characters, which yields exactly the
+    /// characters of `decode(cipher)`
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// let start: String = cipher.decode_iter("RGUSRSIETTA").take(4).collect();
+    /// assert_eq!("RUST", start);
+    /// ```
+    pub fn decode_iter<'a>(&self, cipher: &'a str) -> impl Iterator<Item = char> + 'a {
+        let ungrouped = self.ungroup(cipher);
+        let chars = working(ungrouped.chars().collect::<Vec<char>>());
+        wipe_cow(ungrouped);
+        let mut order = if self.char_filter == CharFilter::PreservePositions {
+            letters_only(&chars, |len| self.permutation(len))
+        } else {
+            self.permutation(chars.len())
+        };
+        let padding = self.trailing_fill(order.iter().map(|&from| chars[from]));
+        order.truncate(order.len() - padding);
+        order.into_iter().map(move |from| chars[from])
+    }
+
+    /// Encode the message and hand out the cipher-text in fixed size pieces
+    ///
+    /// Each rail holds characters from the whole message, so all of the
+    /// cipher-text is worked out before the first piece is ready.  What
+    /// this saves is slicing the result up afterwards, for example to fill
+    /// fixed size frames one at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`        The clear-text string to encode
+    /// * `chunk_len`   The number of characters in each piece
+    ///
+    /// # Returns
+    /// An iterator over pieces of `chunk_len` characters which join up to
+    /// `encode(text)`.  The last piece may be shorter, and an empty message
+    /// gives no pieces at all.
+    ///
+    /// # Panics
+    /// If `chunk_len` is 0
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// let chunks: Vec<String> = cipher.encode_chunks("RUSTISGREAT", 4).collect();
+    /// assert_eq!(vec!["RGUS", "RSIE", "TTA"], chunks);
+    /// ```
+    pub fn encode_chunks(&self, text: &str, chunk_len: usize) -> impl Iterator<Item = String> {
+        assert!(chunk_len > 0, "chunk length must be at least 1");
+        let mut chars = self.encode(text).chars().collect::<Vec<char>>().into_iter();
+        core::iter::from_fn(move || {
+            let chunk: String = chars.by_ref().take(chunk_len).collect();
+            if chunk.is_empty() {
+                None
+            } else {
+                Some(chunk)
+            }
+        })
+    }
+}
+
+/// Extend an order over the letters of a text to the whole text, leaving
+/// everything else in place
+fn letters_only(chars: &[char], letter_order: impl FnOnce(usize) -> Vec<usize>) -> Vec<usize> {
+    let letters: Vec<usize> = (0..chars.len()).filter(|&i| chars[i].is_alphabetic()).collect();
+    let mut order: Vec<usize> = (0..chars.len()).collect();
+    for (&to, from) in letters.iter().zip(letter_order(letters.len())) {
+        order[to] = letters[from];
+    }
+    order
+}
@@ -0,0 +1,169 @@
+//! Keys written out as text
+//!
+//! A key is written as comma-separated settings, such as
+//! `rails=4,offset=2,order=2013`, so it can be stored in a config file or
+//! passed on a command line and read back exactly.  `rails` is the number
+//! of rails, `offset` the start offset, as for `RailFence::with_offset`,
+//! and `order` the order the rails are read in, as for
+//! `RailFence::with_rail_order`.  An order is written as one digit per rail
+//! on fences of up to ten rails, and with the rails separated by `-`, such
+//! as `order=10-3-0-...`, on larger ones.  Any setting can be left out
+//! except `rails`, which an `order` stands in for.
+//!
+//! With the `rand` feature, `KeySpec::generate` picks fresh keys for
+//! puzzles.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+use crate::{RailFence, RailFenceError};
+#[cfg(feature = "rand")]
+use rand::RngCore;
+
+/// The settings that make up a rail fence key
+///
+/// # Example
+/// ```
+/// use rail_fence_cipher::*;
+/// let key: KeySpec = "rails=4,offset=2,order=2013".parse().unwrap();
+/// assert_eq!(KeySpec { rails: 4, offset: 2, order: Some(vec![2, 0, 1, 3]) }, key);
+/// assert_eq!("rails=4,offset=2,order=2013", key.to_string());
+/// let cipher = key.to_rail_fence();
+/// assert_eq!("RUSTISGREAT", cipher.decode(cipher.encode("RUSTISGREAT")));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeySpec {
+    /// The number of rails
+    pub rails: u32,
+    /// How far into the zig-zag the first character is placed
+    pub offset: usize,
+    /// The order the rails are read in, if not top to bottom
+    pub order: Option<Vec<u32>>,
+}
+
+impl KeySpec {
+    /// The fence this key describes
+    ///
+    /// # Panics
+    /// If the rail order does not list each rail once, which parsing and
+    /// `generate` never produce
+    pub fn to_rail_fence(&self) -> RailFence {
+        let fence = match &self.order {
+            Some(order) => RailFence::with_rail_order(order),
+            None => RailFence::new(self.rails),
+        };
+        fence.with_offset(self.offset)
+    }
+
+    /// Pick a random key
+    ///
+    /// # Arguments
+    ///
+    /// * `rng`         The source of randomness
+    /// * `max_rails`   The largest rail count to choose, treated as 2 if
+    ///   smaller
+    ///
+    /// # Returns
+    /// A key with between 2 and `max_rails` rails, so that it always moves
+    /// characters, a start offset within the period, and the rails read in
+    /// a random order
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// use rand::SeedableRng;
+    /// let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+    /// let key = KeySpec::generate(&mut rng, 8);
+    /// assert!((2..=8).contains(&key.rails));
+    /// assert_eq!(Ok(key.clone()), key.to_string().parse());
+    /// ```
+    #[cfg(feature = "rand")]
+    pub fn generate(rng: &mut impl RngCore, max_rails: u32) -> KeySpec {
+        let rails = 2 + rng.next_u32() % (max_rails.max(2) - 1);
+        let offset = rng.next_u32() as usize % RailFence::new(rails).period();
+        let mut order: Vec<u32> = (0..rails).collect();
+        // Fisher-Yates, from the last rail down
+        for i in (1..order.len()).rev() {
+            order.swap(i, rng.next_u32() as usize % (i + 1));
+        }
+        KeySpec { rails, offset, order: Some(order) }
+    }
+}
+
+impl FromStr for KeySpec {
+    type Err = RailFenceError;
+
+    /// Read a key written as `rails=4,offset=2,order=2013`
+    ///
+    /// # Returns
+    /// The key, or
+    /// * `RailFenceError::ZeroRails` if it has no rails
+    /// * `RailFenceError::OffsetOutOfRange` if the offset is not within the
+    ///   period, which would only wrap round
+    /// * `RailFenceError::InvalidKey` for any other problem
+    fn from_str(text: &str) -> Result<KeySpec, RailFenceError> {
+        let (mut rails, mut offset, mut order) = (None, None, None);
+        for setting in text.split(',').map(str::trim) {
+            let (name, value) = setting.split_once('=').ok_or(RailFenceError::InvalidKey)?;
+            let slot_taken = match name.trim() {
+                "rails" => rails.replace(value.trim().parse::<u32>().map_err(|_| RailFenceError::InvalidKey)?).is_some(),
+                "offset" => offset.replace(value.trim().parse::<usize>().map_err(|_| RailFenceError::InvalidKey)?).is_some(),
+                "order" => order.replace(parse_order(value.trim())?).is_some(),
+                _ => return Err(RailFenceError::InvalidKey),
+            };
+            if slot_taken {
+                return Err(RailFenceError::InvalidKey);
+            }
+        }
+        let rails = match (rails, &order) {
+            (Some(rails), Some(order)) if order.len() != rails as usize => return Err(RailFenceError::InvalidKey),
+            (Some(rails), _) => rails,
+            (None, Some(order)) => order.len() as u32,
+            (None, None) => return Err(RailFenceError::InvalidKey),
+        };
+        if rails == 0 {
+            return Err(RailFenceError::ZeroRails);
+        }
+        let offset = offset.unwrap_or(0);
+        let period = RailFence::new(rails).period();
+        if offset >= period {
+            return Err(RailFenceError::OffsetOutOfRange { offset, period });
+        }
+        Ok(KeySpec { rails, offset, order })
+    }
+}
+
+impl fmt::Display for KeySpec {
+    /// Write the key in the form `FromStr` reads, leaving out a zero offset
+    /// and the top to bottom order
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rails={}", self.rails)?;
+        if self.offset != 0 {
+            write!(f, ",offset={}", self.offset)?;
+        }
+        if let Some(order) = &self.order {
+            let separator = if order.len() > 10 { "-" } else { "" };
+            let rails: Vec<String> = order.iter().map(u32::to_string).collect();
+            write!(f, ",order={}", rails.join(separator))?;
+        }
+        Ok(())
+    }
+}
+
+/// Read a rail order, checking it lists each rail once
+fn parse_order(value: &str) -> Result<Vec<u32>, RailFenceError> {
+    let order: Option<Vec<u32>> = if value.contains('-') {
+        value.split('-').map(|rail| rail.parse().ok()).collect()
+    } else {
+        value.chars().map(|digit| digit.to_digit(10)).collect()
+    };
+    let order = order.ok_or(RailFenceError::InvalidKey)?;
+    let mut sorted = order.clone();
+    sorted.sort_unstable();
+    if order.is_empty() || !sorted.into_iter().eq(0..order.len() as u32) {
+        return Err(RailFenceError::InvalidKey);
+    }
+    Ok(order)
+}
@@ -0,0 +1,239 @@
+//! Working with only the letters of a message
+//!
+//! Classic rail fence puzzles publish the cipher-text as capital letters
+//! only, usually in groups, with the spaces, punctuation and case of the
+//! original message already thrown away.  When the case matters, it can be
+//! kept aside in a separate mask instead.
+//!
+//! A fence can write its cipher-text in such groups itself, and read back
+//! cipher-text with the spaces and line breaks left in.
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::wipe::{wipe, wipe_cow, working};
+use crate::{RailFence, RailFenceError};
+
+/// Capitalised cipher-text together with the case it lost
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaseMarked {
+    /// The cipher-text of the message with its lowercase letters capitalised
+    pub cipher: String,
+    /// Whether each normalized clear-text character, in clear-text order,
+    /// was a capital
+    pub uppercase: Vec<bool>,
+}
+
+impl RailFence {
+    /// Decode cipher-text that has been reduced to capital letters
+    ///
+    /// Anything that is not a letter, such as the spaces between groups,
+    /// is removed before decoding, and letters are uppercased.  The spacing
+    /// and case of the original message were lost before encoding, so they
+    /// are not restored.
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  The cipher-text, possibly grouped or in mixed case
+    ///
+    /// # Returns
+    /// The clear-text as capital letters only
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3);
+    /// assert_eq!("WEAREDISCOVEREDFLEEATONCE", cipher.decode_letters_only("WECRL TEERD SOEEF EAOCA IVDEN"))
+    /// ```
+    pub fn decode_letters_only(&self, cipher: &str) -> String {
+        let letters: String = cipher
+            .chars()
+            .filter(|c| c.is_alphabetic())
+            .flat_map(char::to_uppercase)
+            .collect();
+        self.decode(working(letters).as_str())
+    }
+
+    /// Encode only the letters, leaving everything else where it is
+    ///
+    /// The letters are taken out, transposed among themselves, and put back
+    /// into the positions letters held, so digits, spaces and punctuation
+    /// keep their places.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    ///
+    /// # Returns
+    /// The message with its letters replaced by their cipher-text
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(2);
+    /// assert_eq!("A-C-E-B-D", cipher.encode_skeleton("A-B-C-D-E"));
+    /// ```
+    pub fn encode_skeleton(&self, text: &str) -> String {
+        let letters = working(text.chars().filter(|c| c.is_alphabetic()).collect::<Vec<char>>());
+        weave(text, self.encode_items(&letters[..]))
+    }
+
+    /// Decode a message encoded with `encode_skeleton`
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  The cipher-text string to decode
+    ///
+    /// # Returns
+    /// The clear-text message
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(2);
+    /// assert_eq!("A-B-C-D-E", cipher.decode_skeleton("A-C-E-B-D"));
+    /// ```
+    pub fn decode_skeleton(&self, cipher: &str) -> String {
+        let letters = working(cipher.chars().filter(|c| c.is_alphabetic()).collect::<Vec<char>>());
+        weave(cipher, self.decode_items(&letters[..]))
+    }
+
+    /// Capitalise the message and encode it, remembering which letters were
+    /// capitals
+    ///
+    /// Only letters with a one-to-one lowercase and capital form are
+    /// capitalised.  Others, such as `ß`, are encoded as they are, so the
+    /// original can always be restored exactly.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    ///
+    /// # Returns
+    /// The capitalised cipher-text and a case mask with one entry per
+    /// character of the normalized clear-text
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3);
+    /// let marked = cipher.encode_case_marked("Rust is Great");
+    /// assert_eq!("R GTUTI RASSE", marked.cipher);
+    /// assert_eq!(Ok(String::from("Rust is Great")), cipher.decode_case_marked(&marked));
+    /// ```
+    pub fn encode_case_marked(&self, text: &str) -> CaseMarked {
+        self.assert_rails();
+        let normalized = self.normalize(text);
+        let uppercase: Vec<bool> = normalized.chars().map(char::is_uppercase).collect();
+        let capitalised = working(
+            normalized
+                .chars()
+                .zip(&uppercase)
+                .map(|(c, &upper)| match single_uppercase(c) {
+                    Some(capital) if !upper && single_lowercase(capital) == Some(c) => capital,
+                    _ => c,
+                })
+                .collect::<String>(),
+        );
+        wipe_cow(normalized);
+        // Already normalized, so only padding and grouping are left to apply
+        let padded = self.pad_to_period(&capitalised);
+        let cipher = self.group(self.encode_normalized(&padded));
+        wipe_cow(padded);
+        CaseMarked { cipher, uppercase }
+    }
+
+    /// Decode a message from `encode_case_marked`, restoring its case
+    ///
+    /// # Arguments
+    ///
+    /// * `marked`  The cipher-text and case mask
+    ///
+    /// # Returns
+    /// The clear-text message in its original case, or
+    /// `RailFenceError::LengthMismatch` if the mask does not have one entry
+    /// per cipher-text character
+    pub fn decode_case_marked(&self, marked: &CaseMarked) -> Result<String, RailFenceError> {
+        let clear_text = self.decode(&marked.cipher);
+        let found = clear_text.chars().count();
+        if found != marked.uppercase.len() {
+            return Err(RailFenceError::LengthMismatch {
+                expected: marked.uppercase.len(),
+                found,
+            });
+        }
+        Ok(clear_text
+            .chars()
+            .zip(&marked.uppercase)
+            .map(|(c, &upper)| match single_lowercase(c) {
+                // Only a capital that lowercases and back again was changed
+                Some(lower) if !upper && lower != c && single_uppercase(lower) == Some(c) => lower,
+                _ => c,
+            })
+            .collect())
+    }
+}
+
+impl RailFence {
+    /// Split cipher-text into the fence's groups, if it has any
+    pub(crate) fn group(&self, mut cipher: String) -> String {
+        let group_len = match self.grouping {
+            Some(group_len) if group_len > 0 => group_len,
+            _ => return cipher,
+        };
+        let chars = cipher.chars().count();
+        let mut grouped = String::with_capacity(cipher.len() + self.grouped_len(chars) - chars);
+        for (i, c) in cipher.chars().enumerate() {
+            if i > 0 && i.is_multiple_of(group_len) {
+                grouped.push(' ');
+            }
+            grouped.push(c);
+        }
+        wipe(&mut cipher);
+        grouped
+    }
+
+    /// The number of characters `group` turns this many characters into
+    pub(crate) fn grouped_len(&self, chars: usize) -> usize {
+        match self.grouping {
+            Some(group_len) if group_len > 0 => chars + chars.saturating_sub(1) / group_len,
+            _ => chars,
+        }
+    }
+
+    /// Remove whitespace from cipher-text, if the fence ignores it
+    pub(crate) fn ungroup<'a>(&self, cipher: &'a str) -> Cow<'a, str> {
+        if !self.ignores_whitespace() || !cipher.contains(char::is_whitespace) {
+            return Cow::Borrowed(cipher);
+        }
+        Cow::Owned(cipher.chars().filter(|c| !c.is_whitespace()).collect())
+    }
+
+    /// Whether decoding removes whitespace from the cipher-text first
+    pub(crate) fn ignores_whitespace(&self) -> bool {
+        self.ignore_whitespace || self.grouping.is_some_and(|group_len| group_len > 0)
+    }
+}
+
+/// Put letters, in order, into the letter positions of a skeleton
+fn weave(skeleton: &str, letters: Vec<char>) -> String {
+    let letters = working(letters);
+    let mut letters = letters.iter().copied();
+    skeleton
+        .chars()
+        .map(|c| if c.is_alphabetic() { letters.next().unwrap_or(c) } else { c })
+        .collect()
+}
+
+/// The capital form of a character, if it is a single character
+fn single_uppercase(c: char) -> Option<char> {
+    let mut upper = c.to_uppercase();
+    upper.next().filter(|_| upper.next().is_none())
+}
+
+/// The lowercase form of a character, if it is a single character
+fn single_lowercase(c: char) -> Option<char> {
+    let mut lower = c.to_lowercase();
+    lower.next().filter(|_| lower.next().is_none())
+}
@@ -1,4 +1,5 @@
 #![crate_name = "rail_fence_cipher"]
+#![no_std]
 //! A library for implementing a rail fence cipher
 //!
 //! The rail-fence cipher is based on setting a number of tracks.  To encode
@@ -16,155 +17,1858 @@
 //! Then the letters are read in order on each line
 //! RGUSRSIETTA
 //!
+//! `RailFence::diagram` draws this picture for any message, and
+//! `RailFence::grid` gives the same layout as rows of cells, for tools
+//! that draw the fence themselves.
+//!
 //! To decode, the letters must be arranged on the rails and read in the
 //! zig-zag fence pattern again.
+//!
+//! The cipher works on characters (Unicode scalar values), not bytes, and
+//! does not treat letters specially: digits, punctuation and non-Latin
+//! characters, including full-width digits such as `１２３`, are moved
+//! around the fence like any letter and come back unchanged on decode.
+//!
+//! Characters are taken in logical order, the order they are stored in,
+//! not the order they are displayed in.  Right-to-left scripts such as
+//! Arabic and Hebrew are therefore transposed starting from the first
+//! character a reader reads, and the cipher-text of mixed left-to-right
+//! and right-to-left text can look rearranged in unexpected ways once the
+//! terminal applies bidirectional layout to it.
+//!
+//! The crate only needs `alloc`, so it builds for `no_std` targets with
+//! `default-features = false`.  The `std` feature, on by default, adds the
+//! parts that need an operating system or floating-point maths, such as
+//! the `std::error::Error` and `std::io::Error` conversions and English
+//! scoring.
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+
+use crate::wipe::{wipe_cow, working};
+
+mod aligned;
+#[cfg(feature = "async")]
+mod async_io;
+mod batch;
+mod blocks;
+mod buffers;
+mod builder;
+mod caesar;
+mod columnar;
+mod comb;
+mod crack;
+mod delimited;
+mod diagram;
+#[cfg(feature = "english")]
+pub mod english;
+mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+mod files;
+mod fixed;
+mod framed;
+pub mod fuzzing;
+#[cfg(feature = "graphemes")]
+mod graphemes;
+mod heights;
+mod incremental;
+mod iter;
+mod key;
+mod letters;
+mod lines;
+mod numbers;
+mod padding;
+mod passphrase;
+mod pattern;
+mod permutation;
+mod pipeline;
+mod precomputed;
+#[cfg(feature = "python")]
+pub mod python;
+mod randomized;
+mod redefence;
+#[cfg(feature = "serde")]
+mod serialize;
+pub mod prelude;
+#[cfg(feature = "std")]
+mod stream;
+#[cfg(feature = "svg")]
+mod svg;
+#[cfg(feature = "testutil")]
+pub mod testutil;
+mod trace;
+mod transposition;
+mod wipe;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "async")]
+pub use async_io::{AsyncRailFenceReader, AsyncRailFenceWriter};
+pub use builder::RailFenceBuilder;
+pub use crack::Candidate;
+pub use error::{ErrorContext, RailFenceError};
+#[cfg(feature = "std")]
+pub use files::NewlinePolicy;
+pub use fixed::ConstRailFence;
+pub use framed::MISSING_CHAR;
+pub use incremental::{RailFenceDecoder, RailFenceEncoder};
+pub use key::KeySpec;
+pub use letters::CaseMarked;
+pub use padding::DEFAULT_FILL;
+pub use pattern::Pattern;
+pub use permutation::MAX_DIFFUSION_RAILS;
+pub use pipeline::Pipeline;
+pub use precomputed::RailSchedule;
+#[cfg(feature = "std")]
+pub use stream::{RailFenceReader, RailFenceWriter};
+#[cfg(feature = "svg")]
+pub use svg::SvgStyle;
+pub use trace::{Heading, Placement};
+pub use transposition::{Scytale, SpiralRoute, TranspositionCipher};
+
+/// Summary of how a message of a given length sits on the fence
+///
+/// Returned by `RailFence::analyze` so that planners and displays can get
+/// all of the layout numbers from a single call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FenceInfo {
+    /// The number of characters in one down-and-up cycle of the zig-zag
+    pub period: usize,
+    /// The number of complete cycles in the message
+    pub full_periods: usize,
+    /// The number of characters left over after the complete cycles
+    pub remainder: usize,
+    /// The number of characters that land on each rail, top to bottom
+    pub rail_lengths: Vec<usize>,
+    /// Whether encoding leaves the message unchanged
+    pub is_degenerate: bool,
+}
+
+/// A caller-supplied rule for which rail each position lands on
+#[derive(Clone)]
+struct Schedule(Arc<dyn Fn(usize) -> u32 + Send + Sync>);
+
+impl fmt::Debug for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Schedule(..)")
+    }
+}
+
+/// What the zig-zag does when it reaches the top or bottom rail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum TurnPolicy {
+    /// Turn around straight away, so each end rail gets one character per
+    /// visit: `0 1 2 1 0 1 2`
+    #[default]
+    Bounce,
+    /// Stay for one extra step, so each end rail gets two consecutive
+    /// characters per visit: `0 0 1 2 2 1 0 0`
+    Dwell,
+}
+
+/// The shape of the path the message takes across the rails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Wave {
+    /// Go down to the bottom rail and back up again: `0 1 2 1 0 1 2`
+    #[default]
+    Triangle,
+    /// Go down to the bottom rail and start again from the top, so the turn
+    /// policy has no effect: `0 1 2 0 1 2 0`
+    Sawtooth,
+}
+
+/// How letters are cased before a message is encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Case {
+    /// Leave every letter as it is
+    #[default]
+    Preserve,
+    /// Convert every letter to uppercase
+    Upper,
+    /// Convert every letter to lowercase
+    Lower,
+}
+
+/// What happens to characters that are not letters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum CharFilter {
+    /// Transpose them along with the letters
+    #[default]
+    Passthrough,
+    /// Remove them before encoding, as classical practice does
+    Strip,
+    /// Leave them where they are and transpose only the letters, on both
+    /// encode and decode
+    PreservePositions,
+}
+
+/// Which way to apply the transposition
+///
+/// Some keyed schemes treat the inverse transposition as encryption, so
+/// `RailFence::transform` lets a protocol choose the direction once instead
+/// of swapping `encode` and `decode` at each call site.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Direction {
+    /// Place the text on the fence and read off the rails, as `encode` does
+    Forward,
+    /// Split the text into rails and read the zig-zag, as `decode` does
+    Inverse,
+}
+
+impl Direction {
+    /// The direction that undoes this one
+    pub fn reverse(self) -> Direction {
+        match self {
+            Direction::Forward => Direction::Inverse,
+            Direction::Inverse => Direction::Forward,
+        }
+    }
+}
+
+/// The order in which the cipher-text is read off the finished fence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum ReadOrder {
+    /// Read each rail from left to right, top rail first, which is the
+    /// rail fence cipher
+    #[default]
+    RailMajor,
+    /// Read each column from top to bottom, left column first.  Each column
+    /// holds exactly one character, in the order it was placed, so this
+    /// gives back the clear-text unchanged.  It is included for teaching,
+    /// to show that the cipher comes entirely from reading by rail.
+    ColumnMajor,
+}
 
 /// Rail fence structure
 ///
 /// This structure holds information pertaining to the rail fence cipher
+#[derive(Debug, Clone)]
 pub struct RailFence {
     /// The number of rails
     rails: u32,
+    /// Replaces the zig-zag when set
+    schedule: Option<Schedule>,
+    /// How the zig-zag turns at the top and bottom rails
+    turn: TurnPolicy,
+    /// Whether the path bounces back up or wraps round to the top rail
+    wave: Wave,
+    /// How the cipher-text is read off the fence
+    read_order: ReadOrder,
+    /// Whether odd periods are read from the bottom rail up
+    period_alternating: bool,
+    /// How far into the zig-zag the first character is placed
+    offset: usize,
+    /// Whether the zig-zag starts heading up from the bottom rail
+    upward: bool,
+    /// The order the rails are read in, if not top to bottom
+    rail_order: Option<Vec<usize>>,
+    /// The only characters `try_encode` accepts, if restricted
+    alphabet: Option<String>,
+    /// How the clear-text is cased before encoding
+    case: Case,
+    /// Whether whitespace is removed from the clear-text before encoding
+    strip_whitespace: bool,
+    /// What happens to characters that are not letters
+    char_filter: CharFilter,
+    /// The filler that pads every message to a whole number of periods, if
+    /// any
+    period_fill: Option<char>,
+    /// The length of the groups the cipher-text is written in, if grouped
+    grouping: Option<usize>,
+    /// Whether whitespace is removed from the cipher-text before decoding
+    ignore_whitespace: bool,
+    /// How many times the transposition is applied
+    rounds: u32,
 }
 
-impl RailFence {
-    /// Create a new fence with the specified number of rails
+impl RailFence {
+    /// Create a new fence with the specified number of rails
+    ///
+    /// # Arguments
+    /// * `rails`   The number of rails for this cipher
+    ///
+    /// # Returns
+    /// A RailFence struct for doing encoding/decoding.  A fence with no
+    /// rails can be made, but `encode` and `decode` panic on it; `try_new`
+    /// refuses it up front, and `try_encode` and `try_decode` report it.
+    pub fn new(rails: u32) -> RailFence {
+        RailFence {
+            rails,
+            schedule: None,
+            turn: TurnPolicy::Bounce,
+            wave: Wave::Triangle,
+            read_order: ReadOrder::RailMajor,
+            period_alternating: false,
+            offset: 0,
+            upward: false,
+            rail_order: None,
+            alphabet: None,
+            case: Case::Preserve,
+            strip_whitespace: false,
+            char_filter: CharFilter::Passthrough,
+            period_fill: None,
+            grouping: None,
+            ignore_whitespace: false,
+            rounds: 1,
+        }
+    }
+
+    /// Create a new fence, refusing a rail count that cannot work
+    ///
+    /// A fence with no rails has nowhere to put the characters, and every
+    /// operation on one fails or panics.  This catches it up front.  Any
+    /// other rail count is fine: a single rail, or more rails than a
+    /// message has characters, leaves the message as it is, and any
+    /// cipher-text length decodes.
+    ///
+    /// # Arguments
+    /// * `rails`   The number of rails for this cipher
+    ///
+    /// # Returns
+    /// A RailFence struct for doing encoding/decoding, or
+    /// `RailFenceError::ZeroRails` if `rails` is 0
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// assert_eq!(Err(RailFenceError::ZeroRails), RailFence::try_new(0).map(|cipher| cipher.rails()));
+    /// let cipher = RailFence::try_new(4).unwrap();
+    /// assert_eq!(Ok(String::from("RGUSRSIETTA")), cipher.try_encode("RUSTISGREAT"));
+    /// ```
+    pub fn try_new(rails: u32) -> Result<RailFence, RailFenceError> {
+        if rails == 0 {
+            return Err(RailFenceError::ZeroRails);
+        }
+        Ok(RailFence::new(rails))
+    }
+
+    /// Create a fence that places characters by a custom schedule
+    ///
+    /// Instead of the zig-zag, `schedule(position)` decides which rail the
+    /// character at each position lands on.  Any schedule can be decoded,
+    /// because decoding replays the same schedule to work out how many
+    /// characters belong on each rail and in which order to take them.
+    ///
+    /// # Arguments
+    /// * `rails`       The number of rails for this cipher
+    /// * `schedule`    Gives the rail, below `rails`, for each position
+    ///
+    /// # Returns
+    /// A RailFence struct for doing encoding/decoding.  Encoding and
+    /// decoding panic if the schedule returns a rail of `rails` or more.
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::with_schedule(3, |position| (position % 3) as u32);
+    /// assert_eq!("RTGAUIRTSSE", cipher.encode("RUSTISGREAT"));
+    /// assert_eq!("RUSTISGREAT", cipher.decode("RTGAUIRTSSE"));
+    /// ```
+    pub fn with_schedule(rails: u32, schedule: impl Fn(usize) -> u32 + Send + Sync + 'static) -> RailFence {
+        RailFence {
+            schedule: Some(Schedule(Arc::new(schedule))),
+            ..RailFence::new(rails)
+        }
+    }
+
+    /// Create a fence that only encodes characters from an alphabet
+    ///
+    /// `try_encode` rejects a message holding any other character.  The
+    /// alphabet lists every allowed character; there is no range syntax, so
+    /// `-` is just another character.  `encode` and decoding are not
+    /// restricted.
+    ///
+    /// # Arguments
+    /// * `rails`       The number of rails for this cipher
+    /// * `alphabet`    The characters a message may contain
+    ///
+    /// # Returns
+    /// A RailFence struct for doing encoding/decoding
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::with_alphabet(4, "ABCDEFGHIJKLMNOPQRSTUVWXYZ");
+    /// assert_eq!(Ok(String::from("RGUSRSIETTA")), cipher.try_encode("RUSTISGREAT"));
+    /// ```
+    pub fn with_alphabet(rails: u32, alphabet: &str) -> RailFence {
+        let mut fence = RailFence::new(rails);
+        fence.alphabet = Some(alphabet.to_string());
+        fence
+    }
+
+    /// Use a different turn-around policy at the top and bottom rails
+    ///
+    /// Encoding and decoding must use the same policy.  It has no effect on
+    /// a fence with a custom schedule.
+    ///
+    /// # Arguments
+    /// * `turn`    How the zig-zag turns at the top and bottom rails
+    ///
+    /// # Returns
+    /// The same fence with the new policy
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3).with_turn_policy(TurnPolicy::Dwell);
+    /// assert_eq!("RUGRSSETIAT", cipher.encode("RUSTISGREAT"));
+    /// assert_eq!("RUSTISGREAT", cipher.decode("RUGRSSETIAT"));
+    /// ```
+    pub fn with_turn_policy(mut self, turn: TurnPolicy) -> RailFence {
+        self.turn = turn;
+        self
+    }
+
+    /// Change the shape of the path across the rails
+    ///
+    /// Encoding and decoding must use the same wave.  It has no effect on a
+    /// fence with a custom schedule.
+    ///
+    /// # Arguments
+    /// * `wave`    Whether to bounce back up or wrap round from the bottom
+    ///
+    /// # Returns
+    /// The same fence with the new wave
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3).with_wave(Wave::Sawtooth);
+    /// assert_eq!("RTGAUIRTSSE", cipher.encode("RUSTISGREAT"));
+    /// assert_eq!("RUSTISGREAT", cipher.decode("RTGAUIRTSSE"));
+    /// ```
+    pub fn with_wave(mut self, wave: Wave) -> RailFence {
+        self.wave = wave;
+        self
+    }
+
+    /// Read the cipher-text off the fence in a different order
+    ///
+    /// This affects `encode`, `decode` and the other methods that produce
+    /// or consume whole cipher-texts.  Methods that work with individual
+    /// rails, such as `encode_rails`, always work rail by rail.
+    ///
+    /// # Arguments
+    /// * `read_order`  How the cipher-text is read off the fence
+    ///
+    /// # Returns
+    /// The same fence with the new read order
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4).with_read_order(ReadOrder::ColumnMajor);
+    /// assert_eq!("RUSTISGREAT", cipher.encode("RUSTISGREAT"));
+    /// ```
+    pub fn with_read_order(mut self, read_order: ReadOrder) -> RailFence {
+        self.read_order = read_order;
+        self
+    }
+
+    /// Read the cipher-text one period at a time, alternating rail order
+    ///
+    /// Each period of the zig-zag is read off on its own, rail by rail.  The
+    /// first period is read from the top rail down, the second from the
+    /// bottom rail up, and so on, with a final partial period counting as a
+    /// period.  This scrambles the message differently from reading whole
+    /// rails.  On a fence with a rail order, as from `with_rail_order`, the
+    /// first period is read in that order and the second in reverse.  It
+    /// has no effect with column-major reading or a custom schedule, which
+    /// has no period.
+    ///
+    /// # Arguments
+    /// * `alternating` Whether to read period by period with alternating
+    ///   rail order
+    ///
+    /// # Returns
+    /// The same fence with the option set
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3).with_period_alternating(true);
+    /// // Periods "RUST", "ISGR" and "EAT", read as "R UT S", "G SR I", "E A T"
+    /// assert_eq!("RUTSGSRIEAT", cipher.encode("RUSTISGREAT"));
+    /// assert_eq!("RUSTISGREAT", cipher.decode("RUTSGSRIEAT"));
+    /// ```
+    pub fn with_period_alternating(mut self, alternating: bool) -> RailFence {
+        self.period_alternating = alternating;
+        self
+    }
+
+    /// Start the zig-zag heading up from the bottom rail
+    ///
+    /// The fence is turned upside down, so the first character goes on the
+    /// bottom rail and the next one above it.  A start offset counts steps
+    /// along the upward zig-zag.  Encoding and decoding must agree.  It has
+    /// no effect on a fence with a custom schedule.
+    ///
+    /// # Arguments
+    /// * `upward`  Whether to start heading up instead of down
+    ///
+    /// # Returns
+    /// The same fence with the new starting direction
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4).with_upward_start(true);
+    /// assert_eq!("TASIETUSRRG", cipher.encode("RUSTISGREAT"));
+    /// assert_eq!("RUSTISGREAT", cipher.decode("TASIETUSRRG"));
+    /// ```
+    pub fn with_upward_start(mut self, upward: bool) -> RailFence {
+        self.upward = upward;
+        self
+    }
+
+    /// Change the case of the letters before encoding
+    ///
+    /// Casing is applied by `encode`, `try_encode` and `normalize`.
+    /// Decoding gives back the cased text, not the original.
+    ///
+    /// # Arguments
+    /// * `case`    How to case the letters of the clear-text
+    ///
+    /// # Returns
+    /// The same fence with the new casing
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4).with_case(Case::Upper);
+    /// assert_eq!("RGUSRSIETTA", cipher.encode("RustIsGreat"));
+    /// ```
+    pub fn with_case(mut self, case: Case) -> RailFence {
+        self.case = case;
+        self
+    }
+
+    /// Remove whitespace from the clear-text before encoding
+    ///
+    /// Stripping is applied by `encode`, `try_encode` and `normalize`.
+    /// Decoding cannot put the whitespace back.
+    ///
+    /// # Arguments
+    /// * `strip`   Whether to remove whitespace
+    ///
+    /// # Returns
+    /// The same fence with the option set
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4).with_whitespace_stripped(true);
+    /// assert_eq!("RGUSRSIETTA", cipher.encode("RUST IS\tGREAT\n"));
+    /// ```
+    pub fn with_whitespace_stripped(mut self, strip: bool) -> RailFence {
+        self.strip_whitespace = strip;
+        self
+    }
+
+    /// Choose what happens to characters that are not letters
+    ///
+    /// `CharFilter::Strip` removes them in `normalize`, so decoding cannot
+    /// put them back.  `CharFilter::PreservePositions` keeps them in place
+    /// in the cipher-text, as `encode_skeleton` does, and `decode` and
+    /// `try_decode` leave them in place again, so the punctuation of a
+    /// message survives the round trip.  `encode_number` and the padded
+    /// modes transpose every character of their digits and headers, so
+    /// neither filter applies to them.
+    ///
+    /// # Arguments
+    /// * `filter`  How to treat characters that are not letters
+    ///
+    /// # Returns
+    /// The same fence with the new filter
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let stripped = RailFence::new(4).with_char_filter(CharFilter::Strip);
+    /// assert_eq!("RGUSRSIETTA", stripped.encode("RUST, IS GREAT!"));
+    /// let kept = RailFence::new(2).with_char_filter(CharFilter::PreservePositions);
+    /// assert_eq!("A-C-E-B-D", kept.encode("A-B-C-D-E"));
+    /// assert_eq!("A-B-C-D-E", kept.decode("A-C-E-B-D"));
+    /// ```
+    pub fn with_char_filter(mut self, filter: CharFilter) -> RailFence {
+        self.char_filter = filter;
+        self
+    }
+
+    /// Pad every message with filler to a whole number of periods
+    ///
+    /// `encode` and `try_encode` append the filler after normalizing, so a
+    /// full period puts the same number of characters on each rail, and
+    /// `decode` and `try_decode` remove it again.  The padding carries no
+    /// length, so decoding drops every trailing filler character up to one
+    /// short of a period, including any the message itself ended with;
+    /// pick a filler that does not end messages, such as `DEFAULT_FILL`.
+    /// With `CharFilter::PreservePositions` only letters count towards the
+    /// period, so the filler should be a letter.  A fence with a custom
+    /// schedule has no period and is never padded.
+    ///
+    /// # Arguments
+    /// * `fill`    The filler character, or `None` to stop padding
+    ///
+    /// # Returns
+    /// The same fence with the new padding
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4).with_period_padding(Some(DEFAULT_FILL));
+    /// assert_eq!("RGUSRXSIETTA", cipher.encode("RUSTISGREAT"));
+    /// assert_eq!("RUSTISGREAT", cipher.decode("RGUSRXSIETTA"));
+    /// ```
+    pub fn with_period_padding(mut self, fill: Option<char>) -> RailFence {
+        self.period_fill = fill;
+        self
+    }
+
+    /// Write the cipher-text in groups of the given length
+    ///
+    /// `encode` and `try_encode` put a space between each group, the way
+    /// classic cipher-texts are published, and the last group may be
+    /// short.  A grouped fence also ignores whitespace when decoding, as
+    /// `with_whitespace_ignored` does, so its own cipher-text decodes.  The
+    /// spaces of the message itself would be lost on the way back, so
+    /// grouping is best paired with `with_whitespace_stripped`.  A length
+    /// of 0 leaves the cipher-text ungrouped.
+    ///
+    /// # Arguments
+    /// * `group_len`   The number of characters in each group, or `None`
+    ///   to stop grouping
+    ///
+    /// # Returns
+    /// The same fence with the new grouping
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4).with_grouping(Some(5));
+    /// assert_eq!("RGUSR SIETT A", cipher.encode("RUSTISGREAT"));
+    /// assert_eq!("RUSTISGREAT", cipher.decode("RGUSR SIETT A"));
+    /// ```
+    pub fn with_grouping(mut self, group_len: Option<usize>) -> RailFence {
+        self.grouping = group_len;
+        self
+    }
+
+    /// Remove whitespace from the cipher-text before decoding
+    ///
+    /// Cipher-texts copied from a page often arrive in groups or broken
+    /// across lines.  With this set, `decode` and `try_decode` drop every
+    /// whitespace character first, so they decode as if the cipher-text
+    /// had been written out in one piece.
+    ///
+    /// # Arguments
+    /// * `ignore`  Whether to remove whitespace
+    ///
+    /// # Returns
+    /// The same fence with the option set
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4).with_whitespace_ignored(true);
+    /// assert_eq!("RUSTISGREAT", cipher.decode("RGUSR\nSIETT A\n"));
+    /// ```
+    pub fn with_whitespace_ignored(mut self, ignore: bool) -> RailFence {
+        self.ignore_whitespace = ignore;
+        self
+    }
+
+    /// Apply the transposition several times over
+    ///
+    /// `encode` puts the message through the fence `rounds` times and
+    /// `decode` takes it back out as many times, the way puzzles ask for a
+    /// fence applied twice or thrice.  The rounds are combined into one
+    /// reordering, so the message is only moved once.  Every encoder and
+    /// decoder follows the rounds, including `encode_number`, the padded
+    /// and framed modes, `encode_with_capacity`, the cipher-text from
+    /// `encode_with_grid` and in-place transposition.  The views of the
+    /// layout itself, such as `encode_rails` and `decode_from_rails`,
+    /// diagrams and `encode_trace`'s rails, show the first round.
+    /// Normalizing, padding and grouping apply once, around all of the
+    /// rounds, and 0 rounds leave the message as it is.
+    ///
+    /// # Arguments
+    /// * `rounds`  The number of times to apply the transposition
+    ///
+    /// # Returns
+    /// The same fence with the new number of rounds
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4).with_rounds(2);
+    /// assert_eq!(RailFence::new(4).encode("RGUSRSIETTA"), cipher.encode("RUSTISGREAT"));
+    /// assert_eq!("RUSTISGREAT", cipher.decode(cipher.encode("RUSTISGREAT")));
+    /// ```
+    pub fn with_rounds(mut self, rounds: u32) -> RailFence {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Start the zig-zag part way through its period
+    ///
+    /// The first character is placed where the character at position
+    /// `offset` would be on an unshifted fence, so an offset of 1 on three
+    /// rails starts on the middle rail heading down.  Offsets wrap around at
+    /// the period.  Encoding and decoding must use the same offset.  It has
+    /// no effect on a fence with a custom schedule.
+    ///
+    /// Keys given as a starting rail and direction convert directly: to
+    /// start on rail `r` heading down, use an offset of `r`, and to start on
+    /// rail `r` heading up, use `period() - r`.
+    ///
+    /// # Arguments
+    /// * `offset`  The number of zig-zag steps to skip before the first
+    ///   character
+    ///
+    /// # Returns
+    /// The same fence with the new start offset
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3).with_offset(1);
+    /// assert_eq!("TRRSIGETUSA", cipher.encode("RUSTISGREAT"));
+    /// assert_eq!("RUSTISGREAT", cipher.decode("TRRSIGETUSA"));
+    /// ```
+    pub fn with_offset(mut self, offset: usize) -> RailFence {
+        self.offset = offset;
+        self
+    }
+
+    /// The number of rails on this fence
+    pub fn rails(&self) -> u32 {
+        self.rails
+    }
+
+    /// Change the number of rails used by this fence
+    ///
+    /// # Arguments
+    /// * `rails`   The new number of rails for this cipher
+    ///
+    /// # Returns
+    /// An error, with the fence left unchanged, if
+    /// * `rails` is 0, giving `RailFenceError::ZeroRails`
+    /// * the fence reads its rails in a keyed order, which only covers its
+    ///   current rails, giving `RailFenceError::ConflictingOptions`
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let mut cipher = RailFence::new(2);
+    /// cipher.set_rails(4).unwrap();
+    /// assert_eq!("RGUSRSIETTA", cipher.encode("RUSTISGREAT"))
+    /// ```
+    pub fn set_rails(&mut self, rails: u32) -> Result<(), RailFenceError> {
+        if rails == 0 {
+            return Err(RailFenceError::ZeroRails);
+        }
+        if self.rail_order.is_some() && rails != self.rails {
+            return Err(RailFenceError::ConflictingOptions {
+                first: "a rail order",
+                second: "a different number of rails",
+            });
+        }
+        self.rails = rails;
+        Ok(())
+    }
+
+    /// Encode the message in text using the fence rails
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode, such as a `&str` or `String`
+    ///
+    /// # Returns
+    /// The cipher-text message
+    ///
+    /// # Panics
+    /// If the fence has no rails, or a custom schedule places a character on
+    /// a rail the fence does not have.  `try_encode` reports these as errors
+    /// instead.
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// let cipher_text = cipher.encode("RUSTISGREAT");
+    /// let expected = String::from("RGUSRSIETTA");
+    /// assert_eq!(expected, cipher_text)
+    /// ```
+    pub fn encode(&self, text: impl AsRef<str>) -> String {
+        self.assert_rails();
+        let normalized = self.normalize(text.as_ref());
+        let padded = self.pad_to_period(&normalized);
+        let encoded = self.group(self.encode_normalized(&padded));
+        wipe_cow(padded);
+        wipe_cow(normalized);
+        encoded
+    }
+
+    /// Panic with a clear message on a fence with no rails
+    pub(crate) fn assert_rails(&self) {
+        if self.rails == 0 {
+            panic!("{}", RailFenceError::ZeroRails);
+        }
+    }
+
+    /// Transpose every character of text as it is
+    ///
+    /// None of the options that change the text apply: there is no
+    /// normalizing, filtering, period padding or grouping, so the result
+    /// has exactly the characters of text.  The rail layout, rail order
+    /// and rounds all apply.  Wrappers that promise an exact length or
+    /// parse what they decode are built on this and `untranspose`.
+    pub(crate) fn transpose(&self, text: &str) -> String {
+        self.assert_rails();
+        if text.is_ascii() {
+            return ascii_string(self.encode_items(text.as_bytes()));
+        }
+        sized_string(self.encode_items(&working(char_vec(text))[..]), text.len())
+    }
+
+    /// Undo `transpose`
+    pub(crate) fn untranspose(&self, cipher: &str) -> String {
+        self.assert_rails();
+        if cipher.is_ascii() {
+            return ascii_string(self.decode_items(cipher.as_bytes()));
+        }
+        sized_string(self.decode_chars(&working(char_vec(cipher))), cipher.len())
+    }
+
+    /// Encode text that has already been normalized
+    pub(crate) fn encode_normalized(&self, text: &str) -> String {
+        #[cfg(feature = "tracing")]
+        self.trace_layout("encode", text);
+        if self.char_filter == CharFilter::PreservePositions {
+            return self.encode_skeleton(text);
+        }
+        if text.is_ascii() {
+            // Every character is a single byte, so skip decoding UTF-8
+            return ascii_string(self.encode_items(text.as_bytes()));
+        }
+        sized_string(self.encode_items(&working(char_vec(text))[..]), text.len())
+    }
+
+    /// Encode the message in text, reporting bad input instead of panicking
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    ///
+    /// # Returns
+    /// The cipher-text message, or
+    /// * `RailFenceError::ZeroRails` if the fence has no rails
+    /// * `RailFenceError::ScheduleOutOfRange` if a custom schedule places a
+    ///   character on a rail the fence does not have
+    /// * `RailFenceError::InvalidCharacter` for the first character that is
+    ///   not in the fence's alphabet, if it has one, counting positions in
+    ///   the normalized text
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::with_alphabet(4, "ABCDEFGHIJKLMNOPQRSTUVWXYZ");
+    /// assert_eq!(Err(RailFenceError::InvalidCharacter { ch: ' ', position: 4 }), cipher.try_encode("RUST IS GREAT"));
+    /// ```
+    pub fn try_encode(&self, text: &str) -> Result<String, RailFenceError> {
+        if self.rails == 0 {
+            return Err(RailFenceError::ZeroRails);
+        }
+        let text = self.normalize(text);
+        self.check_schedule(self.fence_len(&text))?;
+        if let Some(alphabet) = &self.alphabet {
+            if let Some((position, ch)) = text.chars().enumerate().find(|&(_, ch)| !alphabet.contains(ch)) {
+                return Err(RailFenceError::InvalidCharacter { ch, position });
+            }
+        }
+        let padded = self.pad_to_period(&text);
+        let encoded = self.group(self.encode_normalized(&padded));
+        wipe_cow(padded);
+        wipe_cow(text);
+        Ok(encoded)
+    }
+
+    /// Prepare clear-text for encoding, as `encode` does
+    ///
+    /// Whitespace is removed first, if the fence strips it, along with
+    /// anything else that is not a letter under `CharFilter::Strip`, and
+    /// then the letters are cased as the fence asks.  A fence with none of
+    /// these options set returns the text as it is.  Only `encode` and
+    /// `try_encode` normalize for themselves; call this before the other
+    /// encoding methods to get the same treatment.  Decoding cannot restore
+    /// what normalizing removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to prepare
+    ///
+    /// # Returns
+    /// The text that would be placed on the fence
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4).with_case(Case::Upper).with_whitespace_stripped(true);
+    /// assert_eq!("RUSTISGREAT", cipher.normalize("Rust is great"));
+    /// assert_eq!("RGUSRSIETTA", cipher.encode("Rust is great"));
+    /// ```
+    pub fn normalize<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let strip_others = self.char_filter == CharFilter::Strip;
+        if self.case == Case::Preserve && !self.strip_whitespace && !strip_others {
+            return Cow::Borrowed(text);
+        }
+        let kept = text
+            .chars()
+            .filter(|c| !(self.strip_whitespace && c.is_whitespace()))
+            .filter(|c| !strip_others || c.is_alphabetic());
+        Cow::Owned(match self.case {
+            Case::Preserve => kept.collect(),
+            Case::Upper => kept.flat_map(char::to_uppercase).collect(),
+            Case::Lower => kept.flat_map(char::to_lowercase).collect(),
+        })
+    }
+
+    /// Apply the transposition in the given direction
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The string to transform
+    /// * `dir`     `Forward` to encode or `Inverse` to decode
+    ///
+    /// # Returns
+    /// The transformed string, which `dir.reverse()` turns back into text
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// let scrambled = cipher.transform("RUSTISGREAT", Direction::Inverse);
+    /// assert_eq!(cipher.decode("RUSTISGREAT"), scrambled);
+    /// assert_eq!("RUSTISGREAT", cipher.transform(scrambled, Direction::Forward))
+    /// ```
+    pub fn transform(&self, text: impl AsRef<str>, dir: Direction) -> String {
+        match dir {
+            Direction::Forward => self.encode(text),
+            Direction::Inverse => self.decode(text),
+        }
+    }
+
+    /// Re-encode cipher-text from this fence for a different fence
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  Cipher-text produced by this fence
+    /// * `to`      The fence to produce cipher-text for
+    ///
+    /// # Returns
+    /// The same message as encoded by `to`
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let three = RailFence::new(3);
+    /// let four = RailFence::new(4);
+    /// assert_eq!("RGUSRSIETTA", three.transcode("RIEUTSRASGT", &four))
+    /// ```
+    pub fn transcode(&self, cipher: impl AsRef<str>, to: &RailFence) -> String {
+        to.encode(self.decode(cipher))
+    }
+
+    /// Place the message in text on the fence rails without joining them
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    ///
+    /// # Returns
+    /// The characters on each rail, from the top rail to the bottom rail.
+    /// Concatenating them gives the cipher-text from `encode`.
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// assert_eq!(vec!["RG", "USR", "SIET", "TA"], cipher.encode_rails("RUSTISGREAT"))
+    /// ```
+    pub fn encode_rails(&self, text: &str) -> Vec<String> {
+        // Sized for one byte per character, which is exact for ASCII
+        let mut rails: Vec<String> = self
+            .rail_lengths(text.chars().count())
+            .into_iter()
+            .map(String::with_capacity)
+            .collect();
+        for (i, c) in text.chars().enumerate() {
+            rails[self.rail_at(i)].push(c);
+        }
+        rails
+    }
+
+    /// Decode the message in text using the fence rails
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The cipher-text string to decode, such as a `&str` or `String`
+    ///
+    /// # Returns
+    /// The clear-text message
+    ///
+    /// # Panics
+    /// If the fence has no rails, or a custom schedule places a character on
+    /// a rail the fence does not have.  `try_decode` reports these as errors
+    /// instead.
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// let clear_text = cipher.decode("RGUSRSIETTA");
+    /// let expected = String::from("RUSTISGREAT");
+    /// assert_eq!(expected, clear_text)
+    /// ```
+    pub fn decode(&self, cipher: impl AsRef<str>) -> String {
+        match self.try_decode(cipher.as_ref()) {
+            Ok(clear_text) => clear_text,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Decode the message in text, reporting a bad fence instead of panicking
+    ///
+    /// Any string decodes on a fence with at least one rail, so the only
+    /// errors come from the fence itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  The cipher-text string to decode
+    ///
+    /// # Returns
+    /// The clear-text message, or
+    /// * `RailFenceError::ZeroRails` if the fence has no rails
+    /// * `RailFenceError::ScheduleOutOfRange` if a custom schedule places a
+    ///   character on a rail the fence does not have
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// assert_eq!(Ok(String::from("RUSTISGREAT")), RailFence::new(4).try_decode("RGUSRSIETTA"));
+    /// assert_eq!(Err(RailFenceError::ZeroRails), RailFence::new(0).try_decode("RGUSRSIETTA"));
+    /// ```
+    pub fn try_decode(&self, cipher: &str) -> Result<String, RailFenceError> {
+        self.try_decode_padded(cipher).map(|clear_text| self.strip_period_padding(clear_text))
+    }
+
+    /// Decode the message in text as `try_decode` does, but keep any period
+    /// padding
+    pub(crate) fn try_decode_padded(&self, cipher: &str) -> Result<String, RailFenceError> {
+        if self.rails == 0 {
+            return Err(RailFenceError::ZeroRails);
+        }
+        let ungrouped = self.ungroup(cipher);
+        let cipher = &*ungrouped;
+        self.check_schedule(self.fence_len(cipher))?;
+        #[cfg(feature = "tracing")]
+        self.trace_layout("decode", cipher);
+        let clear_text = self.decode_normalized(cipher);
+        wipe_cow(ungrouped);
+        Ok(clear_text)
+    }
+
+    /// Decode cipher-text that has already been ungrouped, keeping any
+    /// period padding
+    pub(crate) fn decode_normalized(&self, cipher: &str) -> String {
+        if self.char_filter == CharFilter::PreservePositions {
+            self.decode_skeleton(cipher)
+        } else if cipher.is_ascii() {
+            ascii_string(self.decode_items(cipher.as_bytes()))
+        } else {
+            sized_string(self.decode_chars(&working(char_vec(cipher))), cipher.len())
+        }
+    }
+
+    /// The number of characters of a text that are placed on the fence
+    fn fence_len(&self, text: &str) -> usize {
+        match self.char_filter {
+            CharFilter::PreservePositions => text.chars().filter(|c| c.is_alphabetic()).count(),
+            _ => text.chars().count(),
+        }
+    }
+
+    /// Check that a custom schedule keeps a message of this length on the fence
+    fn check_schedule(&self, len: usize) -> Result<(), RailFenceError> {
+        if let Some(Schedule(schedule)) = &self.schedule {
+            if let Some((position, rail)) =
+                (0..len).map(|position| (position, schedule(position))).find(|&(_, rail)| rail >= self.rails)
+            {
+                return Err(RailFenceError::ScheduleOutOfRange { position, rail, rails: self.rails });
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a string could be a plain cipher-text from this fence
+    ///
+    /// Encoding keeps every character and only reorders them, so any string
+    /// of any length is a possible cipher-text, and this only checks that
+    /// the fence itself can decode it, as `try_decode` does.  Modes that add
+    /// structure have stricter checks, such as `could_be_framed` for frames.
+    ///
+    /// # Arguments
+    ///
+    /// * `s`       The string to check
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// assert!(RailFence::new(4).could_be_ciphertext("any text at all"));
+    /// assert!(!RailFence::new(0).could_be_ciphertext("any text at all"));
+    /// ```
+    pub fn could_be_ciphertext(&self, s: &str) -> bool {
+        if self.rails == 0 {
+            return false;
+        }
+        match &self.schedule {
+            Some(Schedule(schedule)) => (0..s.chars().count()).all(|position| schedule(position) < self.rails),
+            None => true,
+        }
+    }
+
+    /// Decode a cipher-text of known length, ignoring anything after it
+    ///
+    /// Trailing noise, such as padding or a stray newline, would otherwise
+    /// be decoded as part of the message and scramble all of it.
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`      The cipher-text string, possibly followed by junk
+    /// * `plain_len`   The number of characters in the clear-text
+    ///
+    /// # Returns
+    /// The clear-text decoded from the first `plain_len` characters, or
+    /// `RailFenceError::LengthMismatch` if `cipher` is shorter than that
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// assert_eq!(Ok(String::from("RUSTISGREAT")), cipher.decode_len("RGUSRSIETTA~~junk~~", 11));
+    /// ```
+    pub fn decode_len(&self, cipher: &str, plain_len: usize) -> Result<String, RailFenceError> {
+        let end = match cipher.char_indices().nth(plain_len) {
+            Some((end, _)) => end,
+            None => {
+                let found = cipher.chars().count();
+                if found < plain_len {
+                    return Err(RailFenceError::LengthMismatch { expected: plain_len, found });
+                }
+                cipher.len()
+            }
+        };
+        Ok(self.decode(&cipher[..end]))
+    }
+
+    /// The number of characters `encode` produces for a message
+    ///
+    /// This follows the same steps as `encode`: the message is normalized,
+    /// filled out to a whole period if the fence pads, and counted with the
+    /// separators grouping puts between groups.  The wrappers that add
+    /// characters of their own have their own length methods, such as
+    /// `encoded_padded_len` for `encode_padded_to`.
     ///
     /// # Arguments
-    /// * `rails`   The number of rails for this cipher
+    ///
+    /// * `text`    The clear-text string that would be encoded
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3);
+    /// assert_eq!(10, cipher.encoded_len("古池蛙飛び込む水の音"));
+    /// let grouped = RailFence::new(3).with_whitespace_stripped(true).with_period_padding(Some('X')).with_grouping(Some(5));
+    /// assert_eq!(14, grouped.encoded_len("RUST IS GREAT"));
+    /// assert_eq!(grouped.encode("RUST IS GREAT").chars().count(), grouped.encoded_len("RUST IS GREAT"));
+    /// ```
+    pub fn encoded_len(&self, text: &str) -> usize {
+        let normalized = self.normalize(text);
+        let len = normalized.chars().count() + self.period_shortfall(&normalized);
+        wipe_cow(normalized);
+        self.grouped_len(len)
+    }
+
+    /// The length of one down-and-up cycle of the zig-zag
     ///
     /// # Returns
-    /// A RailFence struct for doing encoding/decoding
-    pub fn new(rails: u32) -> RailFence {
-        RailFence {rails}
+    /// `2 * (rails - 1)`, or `2 * rails` when dwelling at the turns, or
+    /// `rails` for a sawtooth wave, or 1 for a single rail where every
+    /// character lands on the same rail.  A custom schedule has no known
+    /// period and reports 0.
+    pub fn period(&self) -> usize {
+        if self.schedule.is_some() {
+            0
+        } else if self.rails <= 1 {
+            1
+        } else if self.wave == Wave::Sawtooth {
+            self.rails as usize
+        } else {
+            match self.turn {
+                TurnPolicy::Bounce => 2 * (self.rails as usize - 1),
+                TurnPolicy::Dwell => 2 * self.rails as usize,
+            }
+        }
     }
 
-    /// Encode the message in text using the fence rails
+    /// Count how many times the zig-zag changes direction over a message
     ///
-    /// # Arguments
+    /// A turnaround is counted each time the path that joins consecutive
+    /// characters switches between heading down and heading up.  Steps that
+    /// stay on a rail, as in a dwell, do not change the direction.  With
+    /// one rail the path never moves, so there are no turnarounds.  On a
+    /// plain fence this is `(len - 2) / (rails - 1)` for messages of at
+    /// least two characters.
     ///
-    /// * `text`    The clear-text string to encode
+    /// # Arguments
     ///
-    /// # Returns
-    /// The cipher-text message
+    /// * `len`     The number of characters in the message
     ///
     /// # Example
     /// ```
     /// use rail_fence_cipher::*;
-    /// let cipher = RailFence::new(4);
-    /// let cipher_text = cipher.encode("RUSTISGREAT");
-    /// let expected = String::from("RGUSRSIETTA");
-    /// assert_eq!(expected, cipher_text)
+    /// // R-U-S-T down, I-S-G up, R-E-A down, T up
+    /// assert_eq!(3, RailFence::new(4).turnarounds(11));
+    /// assert_eq!(0, RailFence::new(1).turnarounds(11));
     /// ```
-    pub fn encode(&self, text: &str) -> String {
-        let mut rails = vec![String::from(""); self.rails as usize];
-        let mut f:usize = 0; // The rail to check
-        let message = String::from(text);
-        for (i, c) in message.chars().enumerate() {
-            rails[f].push(c);
-            f = match i as u32 / (self.rails - 1) % 2 {
-                0 => f + 1,
-                1 => f - 1,
-                _ => f // Won't happen because of % 2, but necessary to match all u32 cases
-            };
-        };
-        let mut result = String::new();
-        for part in rails {
-            result.push_str(part.as_str());
+    pub fn turnarounds(&self, len: usize) -> usize {
+        let mut turns = 0;
+        let mut heading_down = None;
+        for position in 1..len {
+            let (from, to) = (self.rail_at(position - 1), self.rail_at(position));
+            if from == to {
+                continue;
+            }
+            let down = to > from;
+            if heading_down == Some(!down) {
+                turns += 1;
+            }
+            heading_down = Some(down);
         }
-        result
+        turns
     }
 
-    /// Decode the message in text using the fence rails
+    /// Count how many characters of a message land on each rail
     ///
     /// # Arguments
     ///
-    /// * `text`    The cipher-text string to decode
+    /// * `len`     The number of characters in the message
     ///
     /// # Returns
-    /// The clear-text message
+    /// One count per rail, from the top rail to the bottom rail
     ///
     /// # Example
     /// ```
     /// use rail_fence_cipher::*;
     /// let cipher = RailFence::new(4);
-    /// let clear_text = cipher.decode("RGUSRSIETTA");
-    /// let expected = String::from("RUSTISGREAT");
-    /// assert_eq!(expected, clear_text)
+    /// assert_eq!(vec![2, 3, 4, 2], cipher.rail_lengths("RUSTISGREAT".len()))
     /// ```
-    pub fn decode(&self, cipher: &str) -> String {
-        if self.rails == 1 {
-            return String::from(cipher)
-        }
-        let mut rails = vec![String::from(""); self.rails as usize];
-        let mut start = vec![0_usize; self.rails as usize];
-        let cipher_text = String::from(cipher);
-
-        // Identify the start of each new row
-        let period = 2 * (self.rails - 1);
-        let cipher_length = cipher_text.chars().count();
-        let section = cipher_length as u32 / period;
-        let remainder = cipher_length as u32 % period;
-        start[0] = 0;
-        if self.rails > 1 {
-            if remainder > 0 {
-                start[1] = (section + 1) as usize;
-            } else {
-                start[1] = section as usize;
+    pub fn rail_lengths(&self, len: usize) -> Vec<usize> {
+        let rails = self.rails as usize;
+        if self.schedule.is_some() {
+            let mut lengths = vec![0; rails];
+            for position in 0..len {
+                lengths[self.rail_at(position)] += 1;
             }
-        } else {
-            return cipher_text; // Simple to decode in the clear
+            return lengths;
         }
-        for i in 2..(self.rails as usize) {
-            start[i] = start[i-1] + 2 * section as usize;
-            if remainder > (i-1) as u32 {
-                start[i] += 1;
-            }
-            if remainder + (i-1) as u32 + 1 >= 2 * self.rails {
-                start[i] += 1;
+        if rails <= 1 {
+            return vec![len; rails];
+        }
+        let period = self.period();
+        let mut lengths = vec![0; rails];
+        if len < period {
+            // The message ends before the first period does
+            for position in 0..len {
+                lengths[self.rail_at(position)] += 1;
             }
+            return lengths;
+        }
+        // Every full period visits each rail the same number of times
+        let full_periods = len / period;
+        for position in 0..period {
+            lengths[self.rail_at(position)] += full_periods;
+        }
+        for position in 0..(len % period) {
+            lengths[self.rail_at(position)] += 1;
+        }
+        lengths
+    }
+
+    /// Whether encoding a message of this length leaves it unchanged
+    ///
+    /// A single rail, or a message no longer than the number of rails (which
+    /// never turns back up), is read off in its original order.
+    ///
+    /// # Arguments
+    ///
+    /// * `len`     The number of characters in the message
+    pub fn is_degenerate(&self, len: usize) -> bool {
+        if self.rounds != 1 {
+            // Rounds that move characters can still bring them all back
+            return self.permutation(len).into_iter().enumerate().all(|(from, to)| from == to);
         }
+        if self.schedule.is_some() || self.turn != TurnPolicy::Bounce || self.wave != Wave::Triangle || self.upward || !self.offset.is_multiple_of(self.period().max(1)) {
+            // Reading rail by rail keeps the order only if no position
+            // lands on a higher rail than the one after it
+            return (1..len).all(|position| self.rail_at(position - 1) <= self.rail_at(position));
+        }
+        self.rails <= 1 || len <= self.rails as usize
+    }
 
-        // Split the data into the corresponding rows
-        for (i, it) in rails.iter_mut().enumerate() {
-            if (self.rails - 1) as usize == i {
-                it.push_str(&cipher_text[start[i]..]);
-            } else {
-                it.push_str(&cipher_text[start[i]..start[i+1]]);
+    /// Whether `other` decodes what this fence encodes
+    ///
+    /// The settings that decide where characters go are compared: the rail
+    /// count, the wave, the turn policy, the read order, alternating
+    /// periods, the rail order, the starting direction, the number of
+    /// rounds and the start offset, counted within one period since a
+    /// whole period further on lays the message out the same way.  So are
+    /// the settings decoding undoes or relies on: the character filter, the
+    /// period padding, the grouping and whether whitespace is ignored.  The
+    /// case and whitespace stripping only change the message before it is
+    /// encoded, so they are not compared.
+    ///
+    /// A custom schedule cannot be inspected, so two fences with schedules
+    /// only match when one was cloned from the other, and then the zig-zag
+    /// settings the schedule replaces are ignored.  Fences that happen to
+    /// agree on some message lengths despite different settings are still
+    /// reported as incompatible.
+    ///
+    /// # Arguments
+    ///
+    /// * `other`   The fence that would decode the cipher-text
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3).with_offset(1);
+    /// assert!(cipher.is_compatible_with(&RailFence::new(3).with_offset(5)));
+    /// assert!(!cipher.is_compatible_with(&RailFence::new(3)));
+    /// ```
+    pub fn is_compatible_with(&self, other: &RailFence) -> bool {
+        let layout_matches = match (&self.schedule, &other.schedule) {
+            (Some(Schedule(mine)), Some(Schedule(theirs))) => Arc::ptr_eq(mine, theirs),
+            (None, None) => {
+                let period = self.period();
+                self.wave == other.wave
+                    && (self.wave == Wave::Sawtooth || self.turn == other.turn)
+                    && self.upward == other.upward
+                    && self.period_alternating == other.period_alternating
+                    && self.offset % period == other.offset % period
             }
+            _ => false,
+        };
+        layout_matches
+            && self.rails == other.rails
+            && self.read_order == other.read_order
+            && self.rail_order == other.rail_order
+            && self.rounds == other.rounds
+            && self.char_filter == other.char_filter
+            && self.period_fill == other.period_fill
+            && self.grouping == other.grouping
+            && self.ignore_whitespace == other.ignore_whitespace
+    }
+
+    /// Describe the layout of a message of the given length on the fence
+    ///
+    /// # Arguments
+    ///
+    /// * `len`     The number of characters in the message
+    ///
+    /// # Returns
+    /// A FenceInfo with the period, cycle counts and rail lengths
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let info = RailFence::new(3).analyze(25);
+    /// assert_eq!(4, info.period);
+    /// assert_eq!(6, info.full_periods);
+    /// assert_eq!(1, info.remainder);
+    /// assert_eq!(vec![7, 12, 6], info.rail_lengths);
+    /// assert!(!info.is_degenerate);
+    /// ```
+    pub fn analyze(&self, len: usize) -> FenceInfo {
+        let period = self.period();
+        FenceInfo {
+            period,
+            full_periods: len.checked_div(period).unwrap_or(0),
+            remainder: len.checked_rem(period).unwrap_or(len),
+            rail_lengths: self.rail_lengths(len),
+            is_degenerate: self.is_degenerate(len),
+        }
+    }
+
+    /// Report the layout of a message about to be transposed
+    ///
+    /// The fields are only worked out if a subscriber wants the event.
+    #[cfg(feature = "tracing")]
+    fn trace_layout(&self, operation: &'static str, text: &str) {
+        let len = text.chars().count();
+        if self.rails as usize > len {
+            // Most of the rails are empty, so leave out their lengths
+            tracing::debug!(operation, rails = self.rails, len, period = self.period(), "rail fence layout");
+            return;
+        }
+        tracing::debug!(
+            operation,
+            rails = self.rails,
+            len,
+            period = self.period(),
+            rail_lengths = ?self.rail_lengths(len),
+            "rail fence layout"
+        );
+    }
+
+    /// The rail that the character at a position is placed on
+    fn rail_at(&self, position: usize) -> usize {
+        if let Some(Schedule(schedule)) = &self.schedule {
+            let rail = schedule(position);
+            assert!(
+                rail < self.rails,
+                "the schedule placed position {} on rail {} of a {}-rail fence",
+                position,
+                rail,
+                self.rails
+            );
+            return rail as usize;
+        }
+        let rails = self.rails as usize;
+        let period = self.period();
+        let phase = (position % period + self.offset % period) % period;
+        let rail = match (self.wave, self.turn) {
+            (Wave::Sawtooth, _) => phase,
+            (Wave::Triangle, TurnPolicy::Bounce) if phase < rails => phase,
+            (Wave::Triangle, TurnPolicy::Bounce) => period - phase,
+            // The first phase is the second step of the dwell at the top
+            (Wave::Triangle, TurnPolicy::Dwell) if phase == 0 => 0,
+            (Wave::Triangle, TurnPolicy::Dwell) if phase <= rails => phase - 1,
+            (Wave::Triangle, TurnPolicy::Dwell) => period - phase,
+        };
+        // Heading up from the bottom is heading down from the top, upside down
+        if self.upward {
+            rails - 1 - rail
+        } else {
+            rail
         }
+    }
 
-        // Pull off letters in the zig-zag pattern to make the decoded message
-        let mut clear_text = String::from("");
-        let mut f:u32 = 0; // The rail to check
+    /// Decode a message whose rails are already separated
+    ///
+    /// The rails are read back in the zig-zag fence pattern, which reverses
+    /// `encode_rails`.
+    ///
+    /// # Arguments
+    ///
+    /// * `rails`   The characters on each rail, from the top rail down
+    ///
+    /// # Returns
+    /// The clear-text message, or an error if the rails could not have come
+    /// from `encode_rails`:
+    /// * `RailFenceError::RailCountMismatch` if the number of rails supplied
+    ///   differs from the number of fence rails
+    /// * `RailFenceError::RailLengthMismatch` if a rail holds a different
+    ///   number of characters than `rail_lengths` gives for the combined
+    ///   length of all the rails
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// let clear_text = cipher.decode_from_rails(&["RG", "USR", "SIET", "TA"]);
+    /// assert_eq!(Ok(String::from("RUSTISGREAT")), clear_text)
+    /// ```
+    pub fn decode_from_rails<S: AsRef<str>>(&self, rails: &[S]) -> Result<String, RailFenceError> {
+        if rails.len() != self.rails as usize {
+            return Err(RailFenceError::RailCountMismatch {
+                expected: self.rails as usize,
+                found: rails.len(),
+            });
+        }
+        // The rails must be exactly what encode_rails would give for a
+        // message of their combined length
+        let found: Vec<usize> = rails.iter().map(|rail| rail.as_ref().chars().count()).collect();
+        let expected = self.rail_lengths(found.iter().sum());
+        for (rail, (&expected, &found)) in expected.iter().zip(&found).enumerate() {
+            if expected != found {
+                return Err(RailFenceError::RailLengthMismatch { rail, expected, found });
+            }
+        }
+        let mut clear_text = String::with_capacity(rails.iter().map(|rail| rail.as_ref().len()).sum());
+        let mut rails: Vec<_> = rails.iter().map(|rail| rail.as_ref().chars()).collect();
         let mut i = 0; // The letter index
-        while !rails[f as usize].is_empty() {
-            let ch = rails[f as usize].remove(0);
+        while let Some(ch) = rails[self.rail_at(i)].next() {
             clear_text.push(ch);
-            f = match i / (self.rails - 1) % 2 {
-                0 => f + 1,
-                1 => f - 1,
-                _ => f // Won't happen because of % 2, but necessary to match all u32 cases
-            };
             i += 1;
         }
-        clear_text
+        Ok(clear_text)
+    }
+
+    /// Decode cipher-text sent together with the length of each rail
+    ///
+    /// The cipher-text is cut into rails at the given lengths and read back
+    /// as for `decode_from_rails`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  The cipher-text string to decode
+    /// * `lengths` The number of characters on each rail, from the top down
+    ///
+    /// # Returns
+    /// The clear-text message, or an error if the lengths do not fit:
+    /// * `RailFenceError::RailCountMismatch` if there is not one length per
+    ///   rail
+    /// * `RailFenceError::LengthMismatch` if the lengths do not add up to
+    ///   the length of the cipher-text
+    /// * `RailFenceError::RailLengthMismatch` if a length differs from what
+    ///   `rail_lengths` gives
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// assert_eq!(Ok(String::from("RUSTISGREAT")), cipher.decode_with_lengths("RGUSRSIETTA", &[2, 3, 4, 2]));
+    /// ```
+    pub fn decode_with_lengths(&self, cipher: &str, lengths: &[usize]) -> Result<String, RailFenceError> {
+        if lengths.len() != self.rails as usize {
+            return Err(RailFenceError::RailCountMismatch {
+                expected: self.rails as usize,
+                found: lengths.len(),
+            });
+        }
+        let found = cipher.chars().count();
+        let expected = lengths.iter().sum();
+        if found != expected {
+            return Err(RailFenceError::LengthMismatch { expected, found });
+        }
+        let mut rest = cipher;
+        let mut rails = Vec::with_capacity(lengths.len());
+        for &length in lengths {
+            let end = rest.char_indices().nth(length).map_or(rest.len(), |(end, _)| end);
+            let (rail, tail) = rest.split_at(end);
+            rails.push(rail);
+            rest = tail;
+        }
+        self.decode_from_rails(&rails)
+    }
+
+    /// Encode raw binary data
+    ///
+    /// The bytes are transposed exactly as the characters of a string
+    /// would be, using the same positions as `encode`, so the two cannot
+    /// disagree.  A multi-byte UTF-8 character is split into its bytes
+    /// here, which is what `encode` avoids.
+    ///
+    /// # Arguments
+    ///
+    /// * `data`    The clear-text bytes to encode
+    ///
+    /// # Returns
+    /// The bytes read off the rails
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3);
+    /// assert_eq!(vec![0x00, 0xff, 0x10, 0x80, 0x7f, 0x01], cipher.encode_bytes(&[0x00, 0x10, 0x01, 0x80, 0xff, 0x7f]))
+    /// ```
+    pub fn encode_bytes(&self, data: &[u8]) -> Vec<u8> {
+        self.encode_items(data)
+    }
+
+    /// Decode raw binary data encoded with `encode_bytes`
+    ///
+    /// # Arguments
+    ///
+    /// * `data`    The cipher-text bytes to decode
+    ///
+    /// # Returns
+    /// The bytes in their original order
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3);
+    /// assert_eq!(vec![0x00, 0x10, 0x01, 0x80, 0xff, 0x7f], cipher.decode_bytes(&[0x00, 0xff, 0x10, 0x80, 0x7f, 0x01]))
+    /// ```
+    pub fn decode_bytes(&self, data: &[u8]) -> Vec<u8> {
+        self.decode_items(data)
+    }
+
+    /// Encode a sequence of integers, such as tokenizer ids
+    ///
+    /// The integers are transposed exactly as the characters of a string
+    /// would be.
+    ///
+    /// # Arguments
+    ///
+    /// * `data`    The values to encode
+    ///
+    /// # Returns
+    /// The values read off the rails
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3);
+    /// assert_eq!(vec![10, 50, 20, 40, 60, 30], cipher.encode_u32(&[10, 20, 30, 40, 50, 60]))
+    /// ```
+    pub fn encode_u32(&self, data: &[u32]) -> Vec<u32> {
+        self.encode_items(data)
+    }
+
+    /// Decode a sequence of integers produced by `encode_u32`
+    ///
+    /// # Arguments
+    ///
+    /// * `data`    The encoded values
+    ///
+    /// # Returns
+    /// The values in their original order
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3);
+    /// assert_eq!(vec![10, 20, 30, 40, 50, 60], cipher.decode_u32(&[10, 50, 20, 40, 60, 30]))
+    /// ```
+    pub fn decode_u32(&self, data: &[u32]) -> Vec<u32> {
+        self.decode_items(data)
+    }
+
+    /// Encode a sequence of any kind of item, such as tokens or words
+    ///
+    /// The items are transposed exactly as the characters of a string
+    /// would be; the text options of the fence, which only make sense for
+    /// characters, are not applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `items`   The items to encode
+    ///
+    /// # Returns
+    /// The items read off the rails
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(2);
+    /// let words: Vec<&str> = "we are discovered flee at once".split(' ').collect();
+    /// assert_eq!(vec!["we", "discovered", "at", "are", "flee", "once"], cipher.encode_slice(&words));
+    /// ```
+    pub fn encode_slice<T: Clone>(&self, items: &[T]) -> Vec<T> {
+        self.encode_items(items)
+    }
+
+    /// Decode a sequence of items produced by `encode_slice`
+    ///
+    /// # Arguments
+    ///
+    /// * `items`   The encoded items
+    ///
+    /// # Returns
+    /// The items in their original order
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(2);
+    /// let words = ["we", "discovered", "at", "are", "flee", "once"];
+    /// assert_eq!("we are discovered flee at once", cipher.decode_slice(&words).join(" "));
+    /// ```
+    pub fn decode_slice<T: Clone>(&self, items: &[T]) -> Vec<T> {
+        self.decode_items(items)
+    }
+
+    /// Decode a message that is already split into characters
+    ///
+    /// This is the character-level core of `decode`, for callers that go on
+    /// to work with the characters and have no use for a `String`.  `decode`
+    /// gives the same characters, collected into a string.
+    ///
+    /// # Arguments
+    ///
+    /// * `chars`   The cipher-text characters
+    ///
+    /// # Returns
+    /// The clear-text characters
+    ///
+    /// # Panics
+    /// On the same fences as `decode`
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// let chars: Vec<char> = "RGUSRSIETTA".chars().collect();
+    /// assert_eq!("RUSTISGREAT".chars().collect::<Vec<char>>(), cipher.decode_chars(&chars))
+    /// ```
+    pub fn decode_chars(&self, chars: &[char]) -> Vec<char> {
+        self.decode_items(chars)
+    }
+
+    /// Move each item to its cipher-text position
+    pub(crate) fn encode_items<T: Clone>(&self, items: &[T]) -> Vec<T> {
+        self.inverse_permutation(items.len())
+            .into_iter()
+            .map(|from| items[from].clone())
+            .collect()
+    }
+
+    /// Move each item back to its clear-text position
+    pub(crate) fn decode_items<T: Clone>(&self, items: &[T]) -> Vec<T> {
+        self.permutation(items.len())
+            .into_iter()
+            .map(|from| items[from].clone())
+            .collect()
+    }
+
+    /// Encode only the characters of text within range, leaving the rest
+    ///
+    /// The characters before and after the range are copied verbatim, and
+    /// the selected characters are transposed in place between them.  The
+    /// range keeps its length, so the fence's normalizing, period padding
+    /// and grouping are not applied to it; with
+    /// `CharFilter::PreservePositions` only its letters move.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to partially encode
+    /// * `range`   The character (not byte) indices to encode
+    ///
+    /// # Returns
+    /// The message with only the range replaced by its cipher-text
+    ///
+    /// # Panics
+    /// If the range is reversed or extends past the number of characters
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// let partial = cipher.encode_range("id=RUSTISGREAT;", 3..14);
+    /// assert_eq!("id=RGUSRSIETTA;", partial)
+    /// ```
+    pub fn encode_range(&self, text: &str, range: Range<usize>) -> String {
+        self.assert_rails();
+        let (prefix, middle, suffix) = split_range(text, range);
+        prefix + &self.encode_normalized(&middle) + &suffix
+    }
+
+    /// Decode only the characters of text within range, leaving the rest
+    ///
+    /// This reverses `encode_range` when given the same range.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The partially encoded string
+    /// * `range`   The character (not byte) indices to decode
+    ///
+    /// # Returns
+    /// The message with only the range replaced by its clear-text
+    ///
+    /// # Panics
+    /// If the range is reversed or extends past the number of characters
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// let clear = cipher.decode_range("id=RGUSRSIETTA;", 3..14);
+    /// assert_eq!("id=RUSTISGREAT;", clear)
+    /// ```
+    pub fn decode_range(&self, text: &str, range: Range<usize>) -> String {
+        self.assert_rails();
+        let (prefix, middle, suffix) = split_range(text, range);
+        prefix + &self.decode_normalized(&middle) + &suffix
     }
 }
+
+/// Turn transposed ASCII bytes back into a string
+fn ascii_string(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes).expect("a transposition of ASCII text is still ASCII")
+}
+
+/// The characters of text, in a vector allocated once
+fn char_vec(text: &str) -> Vec<char> {
+    let mut chars = Vec::with_capacity(text.chars().count());
+    chars.extend(text.chars());
+    chars
+}
+
+/// Collect transposed characters into a string of a known byte length
+///
+/// A transposition keeps every character, so the output has exactly as
+/// many bytes as the input and can be allocated once.
+fn sized_string(chars: Vec<char>, byte_len: usize) -> String {
+    let chars = working(chars);
+    let mut result = String::with_capacity(byte_len);
+    result.extend(chars.iter());
+    result
+}
+
+/// Split text into the characters before, within and after a char range
+fn split_range(text: &str, range: Range<usize>) -> (String, String, String) {
+    let length = text.chars().count();
+    assert!(
+        range.start <= range.end && range.end <= length,
+        "range {:?} is out of bounds for a text of {} characters",
+        range,
+        length
+    );
+    let prefix = text.chars().take(range.start).collect();
+    let middle = text.chars().skip(range.start).take(range.end - range.start).collect();
+    let suffix = text.chars().skip(range.end).collect();
+    (prefix, middle, suffix)
+}
 /* Explanation of the tracking calculation:
 0     6
  1   5 7
   2 4   8
    3     9
 
-7 / 3 = 2 r 1
-2 % 2 = 0 (add)
+period = 2 * (rails - 1) = 6
+phase = index % period
+
+7 % 6 = 1, 1 < 4 (on the way down, rail 1)
 
-5 / 3 = 1 r 1
-1 % 2 = 1 (subtract)
+5 % 6 = 5, 5 >= 4 (on the way up, rail 6 - 5 = 1)
 
-6 / 3 = 2 r 0
-2 % 2 = 0 (add)
+6 % 6 = 0, 0 < 4 (back at the top, rail 0)
 
-(index / (rails-1))%2
- */
\ No newline at end of file
+phase < rails ? phase : period - phase
+ */
@@ -0,0 +1,70 @@
+//! Encoding each line of a document on its own
+//!
+//! Each line is a message of its own and its line break, `\n` or `\r\n`,
+//! is kept where it was and as it was, so a multi-line document keeps its
+//! shape through the cipher.  `encode_file` and `decode_file` work through
+//! files the same way.
+
+use alloc::string::String;
+
+use crate::wipe::{push_str, wipe};
+use crate::RailFence;
+
+impl RailFence {
+    /// Encode each line of a message on its own
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text, which may have several lines
+    ///
+    /// # Returns
+    /// The cipher-text of each line, with the line breaks of `text`
+    ///
+    /// # Panics
+    /// On the same fences as `encode`
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(4);
+    /// assert_eq!("RGUSRSIETTA\r\nESXIEECSR\n", cipher.encode_lines("RUSTISGREAT\r\nEXERCISES\n"));
+    /// assert_eq!("RUSTISGREAT\r\nEXERCISES\n", cipher.decode_lines("RGUSRSIETTA\r\nESXIEECSR\n"));
+    /// ```
+    pub fn encode_lines(&self, text: impl AsRef<str>) -> String {
+        map_lines(text.as_ref(), |line| self.encode(line))
+    }
+
+    /// Decode a message encoded with `encode_lines`
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  The cipher-text, one line per line of the clear-text
+    ///
+    /// # Returns
+    /// The clear-text of each line, with the line breaks of `cipher`
+    ///
+    /// # Panics
+    /// On the same fences as `decode`
+    pub fn decode_lines(&self, cipher: impl AsRef<str>) -> String {
+        map_lines(cipher.as_ref(), |line| self.decode(line))
+    }
+}
+
+/// Transform each line of text, keeping its line break
+fn map_lines(text: &str, transform: impl Fn(&str) -> String) -> String {
+    let mut mapped = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let (line, ending) = split_line_ending(line);
+        let mut line = transform(line);
+        push_str(&mut mapped, &line);
+        push_str(&mut mapped, ending);
+        wipe(&mut line);
+    }
+    mapped
+}
+
+/// Split the line break, if any, off the end of a line
+pub(crate) fn split_line_ending(line: &str) -> (&str, &str) {
+    let text = line.strip_suffix('\n').map_or(line, |text| text.strip_suffix('\r').unwrap_or(text));
+    line.split_at(text.len())
+}
@@ -0,0 +1,54 @@
+//! Transposing the digits of a number
+
+use alloc::string::{String, ToString};
+
+use crate::{RailFence, RailFenceError};
+
+impl RailFence {
+    /// Encode the decimal digits of a number
+    ///
+    /// The digits are transposed as they are.  The fence's normalizing,
+    /// character filter, period padding and grouping are not applied, so
+    /// the cipher-text always has as many digits as the number.
+    ///
+    /// # Arguments
+    ///
+    /// * `n`       The number to encode
+    ///
+    /// # Returns
+    /// The cipher-text of `n` written in decimal, without leading zeros
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// assert_eq!("135246", RailFence::new(2).encode_number(123456));
+    /// ```
+    pub fn encode_number(&self, n: u64) -> String {
+        self.transpose(&n.to_string())
+    }
+
+    /// Decode the digits of a number encoded with `encode_number`
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  The cipher-text of the digits
+    ///
+    /// # Returns
+    /// The number, or `RailFenceError::InvalidNumber` if the cipher-text is
+    /// empty, holds anything but ASCII digits, or decodes to a number too
+    /// large for a `u64`
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(2);
+    /// assert_eq!(Ok(123456), cipher.decode_number("135246"));
+    /// assert_eq!(Err(RailFenceError::InvalidNumber), cipher.decode_number("13x246"));
+    /// ```
+    pub fn decode_number(&self, cipher: &str) -> Result<u64, RailFenceError> {
+        if cipher.is_empty() || !cipher.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(RailFenceError::InvalidNumber);
+        }
+        self.untranspose(cipher).parse().map_err(|_| RailFenceError::InvalidNumber)
+    }
+}
@@ -0,0 +1,233 @@
+//! Length-hiding padding
+//!
+//! A padded message is the clear-text length in decimal, a `:` and the
+//! clear-text, followed by random letters up to the requested length.  The
+//! whole frame is then transposed, so every message padded to the same
+//! target produces a cipher-text of the same length.  Only the clear-text
+//! is normalized, and the frame is transposed as it is, without the
+//! character filter, period padding or grouping, which would change its
+//! length or break up the header.
+//!
+//! Messages can also be padded with a fixed character up to the next power
+//! of two in length, for storage that needs aligned sizes.
+//!
+//! A fence can instead pad every message it encodes with filler up to a
+//! whole number of periods, the way textbooks often draw the cipher, so
+//! that every rail of a full period holds the same number of characters.
+//! That padding has no header, and decoding simply drops trailing filler.
+//!
+//! This only hides how long the message was.  It is not encryption, and the
+//! rail fence itself remains trivial to break.
+
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::String;
+
+use crate::wipe::{wipe, wipe_cow};
+use crate::{RailFence, RailFenceError};
+#[cfg(feature = "rand")]
+use rand::RngCore;
+
+/// Separates the length header from the clear-text
+const HEADER_END: char = ':';
+
+/// The filler textbooks use to pad a message to a whole number of periods
+pub const DEFAULT_FILL: char = 'X';
+
+impl RailFence {
+    /// Pad the message with random letters, then encode it
+    ///
+    /// # Arguments
+    ///
+    /// * `text`        The clear-text string to encode
+    /// * `target_len`  The number of characters the cipher-text should have
+    /// * `rng`         The source of the random padding letters
+    ///
+    /// # Returns
+    /// The cipher-text of the padded message.  If the header and text are
+    /// already longer than `target_len`, no padding is added and the result
+    /// is longer than requested.
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// use rand::SeedableRng;
+    /// let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+    /// let cipher = RailFence::new(3);
+    /// let cipher_text = cipher.encode_padded_to("ATTACK", 20, &mut rng);
+    /// assert_eq!(20, cipher_text.chars().count());
+    /// assert_eq!(Ok(String::from("ATTACK")), cipher.decode_padded(&cipher_text));
+    /// ```
+    #[cfg(feature = "rand")]
+    pub fn encode_padded_to(&self, text: &str, target_len: usize, rng: &mut impl RngCore) -> String {
+        let mut padded = self.padding_frame(text);
+        let padding = target_len.saturating_sub(padded.chars().count());
+        for _ in 0..padding {
+            padded.push((b'A' + (rng.next_u32() % 26) as u8) as char);
+        }
+        let encoded = self.transpose(&padded);
+        wipe(&mut padded);
+        encoded
+    }
+
+    /// The number of characters `encode_padded_to` produces for a message
+    ///
+    /// # Arguments
+    ///
+    /// * `text`        The clear-text string that would be encoded
+    /// * `target_len`  The requested cipher-text length
+    ///
+    /// # Returns
+    /// `target_len`, unless the length header and normalized text alone are
+    /// longer
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3);
+    /// assert_eq!(20, cipher.encoded_padded_len("ATTACK", 20));
+    /// assert_eq!(8, cipher.encoded_padded_len("ATTACK", 4));
+    /// ```
+    pub fn encoded_padded_len(&self, text: &str, target_len: usize) -> usize {
+        let mut frame = self.padding_frame(text);
+        let unpadded = frame.chars().count();
+        wipe(&mut frame);
+        unpadded.max(target_len)
+    }
+
+    /// Pad the message with a fill character to a power-of-two length, then
+    /// encode it
+    ///
+    /// The padded message uses the same frame as `encode_padded_to`: the
+    /// clear-text length in decimal, a `:`, the clear-text, and then `fill`
+    /// until the whole frame is a power of two characters long.  The header
+    /// records the length, so fill characters at the end of the message
+    /// itself are kept on decode.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    /// * `fill`    The character to pad with
+    ///
+    /// # Returns
+    /// The cipher-text of the padded message, whose length in characters is
+    /// a power of two
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3);
+    /// // "11:RUSTISGREAT" is 14 characters, padded to 16
+    /// let cipher_text = cipher.encode_pow2("RUSTISGREAT", 'X');
+    /// assert_eq!(16, cipher_text.chars().count());
+    /// assert_eq!(Ok(String::from("RUSTISGREAT")), cipher.decode_pow2(&cipher_text));
+    /// ```
+    pub fn encode_pow2(&self, text: &str, fill: char) -> String {
+        let mut padded = self.padding_frame(text);
+        let len = padded.chars().count();
+        padded.extend(core::iter::repeat_n(fill, len.next_power_of_two() - len));
+        let encoded = self.transpose(&padded);
+        wipe(&mut padded);
+        encoded
+    }
+
+    /// Decode a message padded by `encode_pow2` and strip the padding
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  The cipher-text produced by `encode_pow2`
+    ///
+    /// # Returns
+    /// The original clear-text, or an error as for `decode_padded`
+    pub fn decode_pow2(&self, cipher: &str) -> Result<String, RailFenceError> {
+        self.decode_padded(cipher)
+    }
+
+    /// Decode a padded message and strip the padding again
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  The cipher-text produced by `encode_padded_to`
+    ///
+    /// # Returns
+    /// The original clear-text, or `RailFenceError::InvalidPadding` if the
+    /// decoded length header is missing or longer than the text after it
+    pub fn decode_padded(&self, cipher: &str) -> Result<String, RailFenceError> {
+        let mut padded = self.untranspose(cipher);
+        let clear_text = frame_text(&padded);
+        wipe(&mut padded);
+        clear_text
+    }
+
+    /// The length header and the normalized clear-text, which the padded
+    /// modes fill out and transpose
+    fn padding_frame(&self, text: &str) -> String {
+        let normalized = self.normalize(text);
+        let frame = padding_header(&normalized) + &normalized;
+        wipe_cow(normalized);
+        frame
+    }
+}
+
+impl RailFence {
+    /// Pad normalized clear-text with the fence's filler to a whole number
+    /// of periods
+    pub(crate) fn pad_to_period<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let short = self.period_shortfall(text);
+        let fill = match self.period_fill {
+            Some(fill) if short > 0 => fill,
+            _ => return Cow::Borrowed(text),
+        };
+        let mut padded = String::with_capacity(text.len() + short * fill.len_utf8());
+        padded.push_str(text);
+        padded.extend(core::iter::repeat_n(fill, short));
+        Cow::Owned(padded)
+    }
+
+    /// How many filler characters `pad_to_period` adds to normalized text
+    pub(crate) fn period_shortfall(&self, text: &str) -> usize {
+        let period = self.period();
+        if self.period_fill.is_none() || period == 0 {
+            return 0;
+        }
+        (period - self.fence_len(text) % period) % period
+    }
+
+    /// Drop the filler `pad_to_period` may have added
+    pub(crate) fn strip_period_padding(&self, mut clear_text: String) -> String {
+        for _ in 0..self.trailing_fill(clear_text.chars()) {
+            clear_text.pop();
+        }
+        clear_text
+    }
+
+    /// How many characters at the end of a decoded message could be the
+    /// filler `pad_to_period` added, which is always less than a period
+    pub(crate) fn trailing_fill(&self, clear_text: impl DoubleEndedIterator<Item = char>) -> usize {
+        match self.period_fill {
+            Some(fill) => clear_text
+                .rev()
+                .take(self.period().saturating_sub(1))
+                .take_while(|&c| c == fill)
+                .count(),
+            None => 0,
+        }
+    }
+}
+
+/// The clear-text in a decoded padding frame
+fn frame_text(padded: &str) -> Result<String, RailFenceError> {
+    let (header, rest) = padded
+        .split_once(HEADER_END)
+        .ok_or(RailFenceError::InvalidPadding)?;
+    let length: usize = header.parse().map_err(|_| RailFenceError::InvalidPadding)?;
+    if rest.chars().count() < length {
+        return Err(RailFenceError::InvalidPadding);
+    }
+    Ok(rest.chars().take(length).collect())
+}
+
+/// The length header written in front of a padded message
+fn padding_header(text: &str) -> String {
+    format!("{}{}", text.chars().count(), HEADER_END)
+}
@@ -0,0 +1,51 @@
+//! Keying the rail order with a passphrase
+//!
+//! A passphrase is easier to remember than a permutation of the rails.  The
+//! fence it gives lays the message out on the usual zig-zag and reads the
+//! rails off in an order derived from the passphrase.
+
+use alloc::vec::Vec;
+
+use crate::RailFence;
+
+impl RailFence {
+    /// Create a fence whose rails are read in an order set by a passphrase
+    ///
+    /// Each rail gets a 64-bit FNV-1a hash of the passphrase's UTF-8 bytes
+    /// followed by the rail number as four little-endian bytes, and the
+    /// rails are read in increasing order of hash, the lower rail first on
+    /// a tie.  The derivation does not depend on the platform or the crate
+    /// version, so the same passphrase and rail count always give the same
+    /// order.
+    ///
+    /// # Arguments
+    /// * `rails`       The number of rails for this cipher
+    /// * `passphrase`  The key the rail order is derived from
+    ///
+    /// # Returns
+    /// A RailFence struct for doing encoding/decoding
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::from_passphrase(4, "correct horse");
+    /// let encoded = cipher.encode("RUSTISGREAT");
+    /// assert_eq!(encoded, RailFence::from_passphrase(4, "correct horse").encode("RUSTISGREAT"));
+    /// assert_eq!("RUSTISGREAT", cipher.decode(&encoded));
+    /// ```
+    pub fn from_passphrase(rails: u32, passphrase: &str) -> RailFence {
+        let mut order: Vec<usize> = (0..rails as usize).collect();
+        order.sort_by_key(|&rail| (rail_hash(passphrase, rail as u32), rail));
+        let mut fence = RailFence::new(rails);
+        fence.rail_order = Some(order);
+        fence
+    }
+}
+
+/// The 64-bit FNV-1a hash of the passphrase followed by the rail number
+fn rail_hash(passphrase: &str, rail: u32) -> u64 {
+    passphrase
+        .bytes()
+        .chain(rail.to_le_bytes().iter().copied())
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3))
+}
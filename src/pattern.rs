@@ -0,0 +1,136 @@
+//! Fences that walk a repeating pattern of rails
+//!
+//! Puzzle collections have many variants of the rail fence that trace some
+//! other shape than the simple zig-zag.  A `Pattern` gives one round of
+//! the walk, either as the rails themselves or by naming a common shape,
+//! and the fence repeats it for as long as the message runs.  Decoding
+//! replays the same walk, so every pattern that visits each of its rails
+//! can be decoded.
+
+use alloc::vec::Vec;
+
+use crate::{RailFence, RailFenceError};
+
+/// One round of a walk over the rails, repeated along the message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pattern {
+    /// The rails given, such as `[0, 1, 2, 3, 1, 0, 2]`, counting from the
+    /// top
+    Sequence(Vec<u32>),
+    /// The plain zig-zag over this many rails, as `RailFence::new` walks it
+    ZigZag(u32),
+    /// Down to the bottom rail, up to the middle one, down to the bottom
+    /// again and back up to the top, tracing the letter W
+    W(u32),
+    /// A zig-zag over the top half of the rails followed by one over the
+    /// bottom half, the top half taking the middle rail of an odd count
+    DoubleZigZag(u32),
+}
+
+impl Pattern {
+    /// The rails of one round of the walk, in order
+    ///
+    /// # Returns
+    /// The rails, or nothing for a named shape over no rails
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// assert_eq!(vec![0, 1, 2, 3, 2, 1], Pattern::ZigZag(4).sequence());
+    /// assert_eq!(vec![0, 1, 2, 3, 4, 3, 2, 3, 4, 3, 2, 1], Pattern::W(5).sequence());
+    /// assert_eq!(vec![0, 1, 2, 1, 3, 4, 5, 4], Pattern::DoubleZigZag(6).sequence());
+    /// ```
+    pub fn sequence(&self) -> Vec<u32> {
+        match *self {
+            Pattern::Sequence(ref rails) => rails.clone(),
+            Pattern::ZigZag(rails) => zig_zag(0, rails),
+            Pattern::W(0) => Vec::new(),
+            Pattern::W(rails) => {
+                let (bottom, middle) = (rails - 1, (rails - 1) / 2);
+                let mut sequence: Vec<u32> = (0..=bottom).collect();
+                sequence.extend((middle..bottom).rev());
+                sequence.extend(middle + 1..=bottom);
+                sequence.extend((1..bottom).rev());
+                sequence
+            }
+            Pattern::DoubleZigZag(rails) => {
+                let top = rails.div_ceil(2);
+                let mut sequence = zig_zag(0, top);
+                sequence.extend(zig_zag(top, rails - top));
+                sequence
+            }
+        }
+    }
+}
+
+impl RailFence {
+    /// Create a fence that walks a repeating pattern of rails
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` One round of the walk
+    ///
+    /// # Returns
+    /// A fence with one rail more than the lowest rail of the pattern
+    ///
+    /// # Panics
+    /// If the pattern is not usable, as `try_with_pattern` reports
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::with_pattern(Pattern::Sequence(vec![0, 1, 2, 3, 1, 0, 2]));
+    /// assert_eq!(4, cipher.rails());
+    /// assert_eq!("RSRUIESGATT", cipher.encode("RUSTISGREAT"));
+    /// assert_eq!("RUSTISGREAT", cipher.decode("RSRUIESGATT"));
+    /// ```
+    pub fn with_pattern(pattern: Pattern) -> RailFence {
+        RailFence::try_with_pattern(pattern).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Create a fence that walks a repeating pattern of rails, reporting a
+    /// pattern that cannot be used instead of panicking
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` One round of the walk
+    ///
+    /// # Returns
+    /// The fence, or
+    /// * `RailFenceError::ZeroRails` if a named shape has no rails
+    /// * `RailFenceError::InvalidPattern` if a sequence is empty or leaves
+    ///   out a rail above its lowest one, which would stay empty
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// assert_eq!(Err(RailFenceError::InvalidPattern), RailFence::try_with_pattern(Pattern::Sequence(vec![0, 3, 2])).map(|_| ()));
+    /// assert_eq!(Err(RailFenceError::InvalidPattern), RailFence::try_with_pattern(Pattern::Sequence(Vec::new())).map(|_| ()));
+    /// assert_eq!(Err(RailFenceError::ZeroRails), RailFence::try_with_pattern(Pattern::W(0)).map(|_| ()));
+    /// ```
+    pub fn try_with_pattern(pattern: Pattern) -> Result<RailFence, RailFenceError> {
+        let sequence = pattern.sequence();
+        if sequence.is_empty() {
+            return match pattern {
+                Pattern::Sequence(_) => Err(RailFenceError::InvalidPattern),
+                _ => Err(RailFenceError::ZeroRails),
+            };
+        }
+        let mut rails = sequence.clone();
+        rails.sort_unstable();
+        rails.dedup();
+        if !rails.iter().copied().eq(0..rails.len() as u32) {
+            return Err(RailFenceError::InvalidPattern);
+        }
+        let rails = rails.len() as u32;
+        Ok(RailFence::with_schedule(rails, move |position| sequence[position % sequence.len()]))
+    }
+}
+
+/// One zig-zag down and back up over `height` rails, starting at rail
+/// `first`
+fn zig_zag(first: u32, height: u32) -> Vec<u32> {
+    let mut sequence: Vec<u32> = (first..first + height).collect();
+    sequence.extend((first + 1..(first + height).saturating_sub(1)).rev());
+    sequence
+}
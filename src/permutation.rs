@@ -0,0 +1,396 @@
+//! The transposition as a permutation of positions
+//!
+//! Encoding moves the character at each clear-text position to a position
+//! in the cipher-text that depends only on the fence and the length of the
+//! message.  These helpers expose that mapping for analysis.
+//!
+//! `inverse_permutation` is the gather order of `encode`: output position
+//! `i` takes the input at `inverse_permutation[i]`.  `permutation` is the
+//! gather order of `decode` in the same way, so composing the fence with
+//! other transforms only needs these two index lists.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{RailFence, ReadOrder};
+
+/// The most rails `best_diffusion_rails` and `scrambling_rails` consider
+pub const MAX_DIFFUSION_RAILS: u32 = 32;
+
+impl RailFence {
+    /// Where each clear-text position ends up in the cipher-text
+    ///
+    /// # Arguments
+    ///
+    /// * `len`     The number of characters in the message
+    ///
+    /// # Returns
+    /// For each clear-text index `i`, the cipher-text index of that
+    /// character after every round, so `encode(text)[permutation[i]] ==
+    /// text[i]`
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3);
+    /// // "ABCDEF" encodes to "AEBDFC"
+    /// assert_eq!(vec![0, 2, 5, 3, 1, 4], cipher.permutation(6))
+    /// ```
+    pub fn permutation(&self, len: usize) -> Vec<usize> {
+        let permutation = self.round_permutation(len);
+        if self.rounds == 1 {
+            return permutation;
+        }
+        power(&permutation, self.rounds)
+    }
+
+    /// Where each clear-text position ends up after a single round
+    fn round_permutation(&self, len: usize) -> Vec<usize> {
+        if self.read_order == ReadOrder::ColumnMajor {
+            return (0..len).collect();
+        }
+        if self.period_alternating && self.period() > 0 {
+            return invert(&self.alternating_read_order(len));
+        }
+        if self.rail_order.is_none() && self.rails as usize > len {
+            // Most rails stay empty, so sort the positions by rail rather
+            // than keeping a count for every rail
+            let rails: Vec<usize> = (0..len).map(|i| self.rail_at(i)).collect();
+            let mut order: Vec<usize> = (0..len).collect();
+            order.sort_by_key(|&i| rails[i]);
+            return invert(&order);
+        }
+        // Each rail's characters start where the rails read before it end
+        let lengths = self.rail_lengths(len);
+        let mut next = vec![0; lengths.len()];
+        let mut start = 0;
+        let mut place = |rail: usize| {
+            next[rail] = start;
+            start += lengths[rail];
+        };
+        match &self.rail_order {
+            Some(order) => order.iter().for_each(|&rail| place(rail)),
+            None => (0..lengths.len()).for_each(place),
+        }
+        (0..len)
+            .map(|i| {
+                let rail = self.rail_at(i);
+                next[rail] += 1;
+                next[rail] - 1
+            })
+            .collect()
+    }
+
+    /// The positions that encoding leaves where they are
+    ///
+    /// # Arguments
+    ///
+    /// * `len`     The number of characters in the message
+    ///
+    /// # Returns
+    /// The clear-text indices whose character has the same index in the
+    /// cipher-text, in increasing order.  With a single rail this is every
+    /// position.
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// assert_eq!(vec![0, 3], RailFence::new(3).fixed_points(6));
+    /// assert_eq!(vec![0, 1, 2], RailFence::new(1).fixed_points(3));
+    /// ```
+    pub fn fixed_points(&self, len: usize) -> Vec<usize> {
+        self.permutation(len)
+            .into_iter()
+            .enumerate()
+            .filter(|&(from, to)| from == to)
+            .map(|(from, _)| from)
+            .collect()
+    }
+
+    /// The smallest rail count that encodes a message of this length the same
+    ///
+    /// A zig-zag that never turns back up reads the message off in its
+    /// original order, so every rail count of at least the message length
+    /// collapses to a single rail, as does any fence which leaves the
+    /// message unchanged.  Otherwise each rail count gives its own
+    /// transposition and the fence's own count is returned.  All other
+    /// options are kept, except a rail order, which only fits the fence's
+    /// own count: the smaller counts are read from the top rail down.
+    ///
+    /// # Arguments
+    ///
+    /// * `len`     The number of characters in the message
+    ///
+    /// # Returns
+    /// The smallest number of rails giving the same cipher-text as this
+    /// fence for every message of length `len`.  A fence with a custom
+    /// schedule always reports its own rail count.
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// assert_eq!(1, RailFence::new(50).effective_rails(5));
+    /// assert_eq!(3, RailFence::new(3).effective_rails(5));
+    /// ```
+    pub fn effective_rails(&self, len: usize) -> u32 {
+        if self.schedule.is_some() || self.rails <= 1 {
+            return self.rails;
+        }
+        let permutation = self.permutation(len);
+        let mut candidate = self.clone();
+        candidate.rail_order = None;
+        (1..self.rails)
+            .find(|&rails| {
+                candidate.rails = rails;
+                candidate.permutation(len) == permutation
+            })
+            .unwrap_or(self.rails)
+    }
+
+    /// A rail count that scrambles a message of this length well
+    ///
+    /// This is a heuristic.  Each rail count from 2 up to one less than the
+    /// length, but no more than `MAX_DIFFUSION_RAILS`, is scored by how many
+    /// characters encoding moves, and then by how far they move in total.
+    /// The first character never moves, so a perfect score is impossible.
+    /// Use `scrambling_rails` to care only about characters left in place.
+    ///
+    /// # Arguments
+    ///
+    /// * `len`     The number of characters in the message
+    ///
+    /// # Returns
+    /// The best scoring rail count, the smaller on a tie.  Messages too
+    /// short to scramble get 2.
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let rails = RailFence::best_diffusion_rails(25);
+    /// assert_eq!(1, RailFence::new(rails).fixed_points(25).len());
+    /// ```
+    pub fn best_diffusion_rails(len: usize) -> u32 {
+        let most = len.saturating_sub(1).min(MAX_DIFFUSION_RAILS as usize).max(2) as u32;
+        let mut best = (2, (0, 0));
+        for rails in 2..=most {
+            let permutation = RailFence::new(rails).permutation(len);
+            let moved = permutation.iter().enumerate().filter(|&(from, &to)| from != to).count();
+            let distance: usize = permutation.iter().enumerate().map(|(from, &to)| from.abs_diff(to)).sum();
+            if (moved, distance) > best.1 {
+                best = (rails, (moved, distance));
+            }
+        }
+        best.0
+    }
+
+    /// The fewest rails that leave as few characters in place as possible
+    ///
+    /// The same rail counts are tried as for `best_diffusion_rails`, but
+    /// only the number of `fixed_points` counts, and ties go to the smaller
+    /// rail count.  `best_diffusion_rails` breaks those ties by how far the
+    /// characters move instead, so the two can disagree, although both
+    /// leave the same number of characters unmoved.  The first character
+    /// never moves, so the best possible is one fixed point.
+    ///
+    /// # Arguments
+    ///
+    /// * `len`     The number of characters in the message
+    ///
+    /// # Returns
+    /// The smallest rail count with the fewest fixed points.  Messages too
+    /// short to scramble get 2.
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// // Two rails leave only the first of eleven characters in place
+    /// assert_eq!(2, RailFence::scrambling_rails(11));
+    /// ```
+    pub fn scrambling_rails(len: usize) -> u32 {
+        let most = len.saturating_sub(1).min(MAX_DIFFUSION_RAILS as usize).max(2) as u32;
+        (2..=most)
+            .min_by_key(|&rails| RailFence::new(rails).fixed_points(len).len())
+            .unwrap_or(2)
+    }
+
+    /// Where each cipher-text position comes from in the clear-text
+    ///
+    /// This is the order encoding reads the clear-text in, and the inverse
+    /// of `permutation`, so `permutation[inverse[i]] == i` for every `i`.
+    ///
+    /// # Arguments
+    ///
+    /// * `len`     The number of characters in the message
+    ///
+    /// # Returns
+    /// For each cipher-text index `i`, the clear-text index of that
+    /// character, so `encode(text)[i] == text[inverse[i]]`
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3);
+    /// // "ABCDEF" encodes to "AEBDFC"
+    /// assert_eq!(vec![0, 4, 1, 3, 5, 2], cipher.inverse_permutation(6))
+    /// ```
+    pub fn inverse_permutation(&self, len: usize) -> Vec<usize> {
+        invert(&self.permutation(len))
+    }
+
+    /// Where the next character typed onto the end of a message will go
+    ///
+    /// Appending a character only adds it to the end of one rail, so its
+    /// cipher-text position can be found from the rail lengths of the
+    /// message so far, without encoding it again.  The new character goes
+    /// in part way through the cipher-text, and every character already at
+    /// or after the returned offset moves along by one.  Inserting the new
+    /// character at that offset turns the old cipher-text into the new one.
+    ///
+    /// # Arguments
+    ///
+    /// * `current_len` The number of characters typed so far
+    /// * `_c`          The character being typed, which does not affect
+    ///   where it goes
+    ///
+    /// # Returns
+    /// The rail the character lands on and its character offset in the
+    /// cipher-text of the longer message
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3);
+    /// let mut shown: Vec<char> = cipher.encode("WEARE").chars().collect();
+    /// let (rail, offset) = cipher.append_char_position(5, 'D');
+    /// assert_eq!((1, 4), (rail, offset));
+    /// shown.insert(offset, 'D');
+    /// assert_eq!(cipher.encode("WEARED"), shown.into_iter().collect::<String>());
+    /// ```
+    pub fn append_char_position(&self, current_len: usize, _c: char) -> (u32, usize) {
+        let rail = self.rail_at(current_len);
+        if self.read_order == ReadOrder::ColumnMajor {
+            return (rail as u32, current_len);
+        }
+        if (self.period_alternating && self.period() > 0) || self.rounds != 1 {
+            // Later periods are read in a different order, or later rounds
+            // move the character on again, so there is no shortcut
+            return (rail as u32, self.permutation(current_len + 1)[current_len]);
+        }
+        let lengths = self.rail_lengths(current_len);
+        let before: usize = match &self.rail_order {
+            Some(order) => order.iter().take_while(|&&other| other != rail).map(|&other| lengths[other]).sum(),
+            None => lengths[..rail].iter().sum(),
+        };
+        (rail as u32, before + lengths[rail])
+    }
+
+    /// How many times encoding has to be repeated to get the message back
+    ///
+    /// Encoding moves each character around a cycle of positions, and all
+    /// of the cycles are back where they started after the least common
+    /// multiple of their lengths.  Repeating the encoding any multiple of
+    /// this many times is the same as not encoding at all.
+    ///
+    /// # Arguments
+    ///
+    /// * `len`     The number of characters in the message
+    ///
+    /// # Returns
+    /// The order of the transposition for messages of `len` characters,
+    /// which is 1 for a fence that leaves them unchanged, or `None` when
+    /// the order is too large to fit in a `usize`.  The cycle lengths grow
+    /// with the message, and their least common multiple overflows for
+    /// messages of a few thousand characters on a 3-rail fence.
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3);
+    /// assert_eq!(Some(4), cipher.cycle_length(6));
+    /// let mut text = "ABCDEF".to_string();
+    /// for _ in 0..4 {
+    ///     text = cipher.encode(&text);
+    /// }
+    /// assert_eq!("ABCDEF", text);
+    /// ```
+    pub fn cycle_length(&self, len: usize) -> Option<usize> {
+        let permutation = self.permutation(len);
+        let mut seen = vec![false; len];
+        let mut order = 1;
+        for start in 0..len {
+            let mut cycle = 0;
+            let mut at = start;
+            while !seen[at] {
+                seen[at] = true;
+                at = permutation[at];
+                cycle += 1;
+            }
+            if cycle > 0 {
+                order = (order / gcd(order, cycle)).checked_mul(cycle)?;
+            }
+        }
+        Some(order)
+    }
+
+    /// The read order when the rail order flips from period to period
+    ///
+    /// Even periods read the rails in the fence's rail order, or from the
+    /// top when it has none, and odd periods read them in reverse.
+    fn alternating_read_order(&self, len: usize) -> Vec<usize> {
+        let period = self.period();
+        let bottom = self.rails as usize - 1;
+        // Where each rail comes in the rail order
+        let ranks = self.rail_order.as_ref().map(|order| invert(order));
+        let mut order: Vec<usize> = (0..len).collect();
+        order.sort_by_key(|&i| {
+            let block = i / period;
+            let rail = self.rail_at(i);
+            let rank = ranks.as_ref().map_or(rail, |ranks| ranks[rail]);
+            let rank = if block % 2 == 1 { bottom - rank } else { rank };
+            (block, rank, i)
+        });
+        order
+    }
+}
+
+/// The greatest common divisor of two numbers
+fn gcd(mut a: usize, mut b: usize) -> usize {
+    while b != 0 {
+        let rest = a % b;
+        a = b;
+        b = rest;
+    }
+    a
+}
+
+/// A permutation applied `times` times over
+///
+/// Each position only moves round its own cycle, so it ends up `times`
+/// steps further round it, however large `times` is.
+fn power(permutation: &[usize], times: u32) -> Vec<usize> {
+    let mut powered = vec![0; permutation.len()];
+    let mut seen = vec![false; permutation.len()];
+    let mut cycle = Vec::new();
+    for start in 0..permutation.len() {
+        cycle.clear();
+        let mut at = start;
+        while !seen[at] {
+            seen[at] = true;
+            cycle.push(at);
+            at = permutation[at];
+        }
+        for (i, &position) in cycle.iter().enumerate() {
+            powered[position] = cycle[(i + times as usize % cycle.len()) % cycle.len()];
+        }
+    }
+    powered
+}
+
+/// The permutation that undoes a permutation
+fn invert(permutation: &[usize]) -> Vec<usize> {
+    let mut inverse = vec![0; permutation.len()];
+    for (from, &to) in permutation.iter().enumerate() {
+        inverse[to] = from;
+    }
+    inverse
+}
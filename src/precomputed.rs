@@ -0,0 +1,186 @@
+//! Transpositions precomputed for one message length
+//!
+//! Every message of a given length is moved around the fence in the same
+//! way, so a server with fixed-size records can work out the permutation
+//! once, store it, and apply it without the fence.
+//!
+//! The stored form is the permutation as a sequence of LEB128 varints,
+//! each the zig-zag encoded difference from the previous entry, starting
+//! from 0.  Neighbouring clear-text characters usually land on neighbouring
+//! rails, so the differences, and the bytes, stay small.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{RailFence, RailFenceError};
+
+/// The permutation of a fence for messages of one length
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RailSchedule {
+    permutation: Vec<usize>,
+}
+
+impl RailFence {
+    /// Precompute the transposition for messages of one length
+    ///
+    /// # Arguments
+    ///
+    /// * `len`     The number of characters in the messages
+    ///
+    /// # Returns
+    /// A schedule that encodes and decodes exactly like this fence for
+    /// messages of `len` characters
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let schedule = RailFence::new(4).rail_schedule(11);
+    /// assert_eq!(Ok(String::from("RGUSRSIETTA")), schedule.encode("RUSTISGREAT"));
+    /// ```
+    pub fn rail_schedule(&self, len: usize) -> RailSchedule {
+        RailSchedule { permutation: self.permutation(len) }
+    }
+}
+
+impl RailSchedule {
+    /// The number of characters in the messages this schedule handles
+    pub fn len(&self) -> usize {
+        self.permutation.len()
+    }
+
+    /// Whether this schedule is for empty messages
+    pub fn is_empty(&self) -> bool {
+        self.permutation.is_empty()
+    }
+
+    /// Where each clear-text position ends up, as for `RailFence::permutation`
+    pub fn permutation(&self) -> &[usize] {
+        &self.permutation
+    }
+
+    /// Encode a message of the scheduled length
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    ///
+    /// # Returns
+    /// The cipher-text, or `RailFenceError::LengthMismatch` if `text` does
+    /// not have `len()` characters
+    pub fn encode(&self, text: &str) -> Result<String, RailFenceError> {
+        let chars = self.checked_chars(text)?;
+        let mut encoded = vec!['\0'; chars.len()];
+        for (&to, c) in self.permutation.iter().zip(chars) {
+            encoded[to] = c;
+        }
+        Ok(encoded.into_iter().collect())
+    }
+
+    /// Decode a message of the scheduled length
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  The cipher-text string to decode
+    ///
+    /// # Returns
+    /// The clear-text message, or `RailFenceError::LengthMismatch` if
+    /// `cipher` does not have `len()` characters
+    pub fn decode(&self, cipher: &str) -> Result<String, RailFenceError> {
+        let chars = self.checked_chars(cipher)?;
+        Ok(self.permutation.iter().map(|&from| chars[from]).collect())
+    }
+
+    /// The compact stored form of this schedule
+    ///
+    /// # Returns
+    /// The permutation as zig-zag encoded varint differences
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let schedule = RailFence::new(3).rail_schedule(6);
+    /// // The permutation 0, 2, 5, 3, 1, 4 steps by 0, +2, +3, -2, -2, +3
+    /// assert_eq!(vec![0, 4, 6, 3, 3, 6], schedule.to_bytes());
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.permutation.len());
+        let mut previous = 0;
+        for &position in &self.permutation {
+            let delta = position as i64 - previous as i64;
+            let mut zigzag = ((delta << 1) ^ (delta >> 63)) as u64;
+            while zigzag >= 0x80 {
+                bytes.push((zigzag as u8 & 0x7f) | 0x80);
+                zigzag >>= 7;
+            }
+            bytes.push(zigzag as u8);
+            previous = position;
+        }
+        bytes
+    }
+
+    /// Load a schedule stored with `to_bytes`
+    ///
+    /// # Arguments
+    ///
+    /// * `len`     The message length the schedule was made for
+    /// * `bytes`   The stored form of the schedule
+    ///
+    /// # Returns
+    /// The schedule, or `RailFenceError::InvalidSchedule` if the bytes do
+    /// not hold a permutation of `len` positions
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let schedule = RailFence::new(3).rail_schedule(6);
+    /// assert_eq!(Ok(schedule.clone()), RailSchedule::from_bytes(6, &schedule.to_bytes()));
+    /// assert_eq!(Err(RailFenceError::InvalidSchedule), RailSchedule::from_bytes(5, &schedule.to_bytes()));
+    /// ```
+    pub fn from_bytes(len: usize, bytes: &[u8]) -> Result<RailSchedule, RailFenceError> {
+        // Every entry takes at least one byte
+        if bytes.len() < len {
+            return Err(RailFenceError::InvalidSchedule);
+        }
+        let mut permutation = Vec::with_capacity(len);
+        let mut seen = vec![false; len];
+        let mut previous: i64 = 0;
+        let mut bytes = bytes.iter();
+        while permutation.len() < len {
+            let mut zigzag: u64 = 0;
+            let mut shift = 0;
+            loop {
+                let &byte = bytes.next().ok_or(RailFenceError::InvalidSchedule)?;
+                if shift > 63 {
+                    return Err(RailFenceError::InvalidSchedule);
+                }
+                zigzag |= u64::from(byte & 0x7f) << shift;
+                shift += 7;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+            let delta = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+            let position = previous.checked_add(delta).ok_or(RailFenceError::InvalidSchedule)?;
+            if position < 0 || position as usize >= len || seen[position as usize] {
+                return Err(RailFenceError::InvalidSchedule);
+            }
+            seen[position as usize] = true;
+            permutation.push(position as usize);
+            previous = position;
+        }
+        if bytes.next().is_some() {
+            return Err(RailFenceError::InvalidSchedule);
+        }
+        Ok(RailSchedule { permutation })
+    }
+
+    /// The characters of text, if it has the scheduled length
+    fn checked_chars(&self, text: &str) -> Result<Vec<char>, RailFenceError> {
+        let chars: Vec<char> = text.chars().collect();
+        if chars.len() != self.len() {
+            return Err(RailFenceError::LengthMismatch { expected: self.len(), found: chars.len() });
+        }
+        Ok(chars)
+    }
+}
@@ -0,0 +1,95 @@
+//! Encoding and decoding in the crate's default element type
+//!
+//! `prelude::encode` and `prelude::decode` work on characters by default.
+//! With the `bytes-default` cargo feature they work on bytes instead, for
+//! projects that mostly transpose binary data.  The methods on `RailFence`
+//! are not affected by the feature, so character encoding is always
+//! available as `RailFence::encode`.
+//!
+//! Cargo features are unified across a build, so if any crate in the
+//! dependency graph enables `bytes-default`, every user of the prelude sees
+//! the byte version.  Libraries should call the `RailFence` methods
+//! directly and leave the choice of prelude to the final application.
+//! Byte mode also splits multi-byte UTF-8 characters apart, so its output
+//! is generally not valid UTF-8 even when the input was.
+//!
+//! # Example
+//! ```
+//! use rail_fence_cipher::prelude::*;
+//! let cipher = RailFence::new(4);
+//! # #[cfg(not(feature = "bytes-default"))]
+//! assert_eq!("RGUSRSIETTA", encode(&cipher, "RUSTISGREAT"));
+//! # #[cfg(feature = "bytes-default")]
+//! assert_eq!(b"RGUSRSIETTA".to_vec(), encode(&cipher, b"RUSTISGREAT"));
+//! ```
+
+#[cfg(not(feature = "bytes-default"))]
+use alloc::string::String;
+#[cfg(feature = "bytes-default")]
+use alloc::vec::Vec;
+
+pub use crate::{RailFence, RailFenceError};
+
+/// The unit that `encode` and `decode` transpose
+#[cfg(not(feature = "bytes-default"))]
+pub type Element = char;
+
+/// The unit that `encode` and `decode` transpose
+#[cfg(feature = "bytes-default")]
+pub type Element = u8;
+
+/// Encode the characters of text with the given fence
+///
+/// # Arguments
+///
+/// * `fence`   The cipher configuration to use
+/// * `text`    The clear-text string to encode
+///
+/// # Returns
+/// The cipher-text message
+#[cfg(not(feature = "bytes-default"))]
+pub fn encode(fence: &RailFence, text: &str) -> String {
+    fence.encode(text)
+}
+
+/// Decode the characters of cipher with the given fence
+///
+/// # Arguments
+///
+/// * `fence`   The cipher configuration to use
+/// * `cipher`  The cipher-text string to decode
+///
+/// # Returns
+/// The clear-text message
+#[cfg(not(feature = "bytes-default"))]
+pub fn decode(fence: &RailFence, cipher: &str) -> String {
+    fence.decode(cipher)
+}
+
+/// Encode the bytes of data with the given fence
+///
+/// # Arguments
+///
+/// * `fence`   The cipher configuration to use
+/// * `data`    The clear-text bytes to encode
+///
+/// # Returns
+/// The cipher-text bytes
+#[cfg(feature = "bytes-default")]
+pub fn encode(fence: &RailFence, data: &[u8]) -> Vec<u8> {
+    fence.encode_bytes(data)
+}
+
+/// Decode the bytes of data with the given fence
+///
+/// # Arguments
+///
+/// * `fence`   The cipher configuration to use
+/// * `data`    The cipher-text bytes to decode
+///
+/// # Returns
+/// The clear-text bytes
+#[cfg(feature = "bytes-default")]
+pub fn decode(fence: &RailFence, data: &[u8]) -> Vec<u8> {
+    fence.decode_bytes(data)
+}
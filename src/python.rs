@@ -0,0 +1,58 @@
+//! Python bindings through pyo3
+//!
+//! The extension module is called `rail_fence_cipher` and holds a single
+//! class, `RailFence`, so Python code reads like the Rust:
+//! `RailFence(4).encode("RUSTISGREAT")`.  A fence with no rails raises
+//! `ValueError` when it is created.  Wheels are built with maturin from
+//! the `pyproject.toml` at the root of the repository, which links this
+//! module into the small `cdylib` crate in `python/`.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::RailFence;
+
+/// A rail fence cipher, exposed to Python as `RailFence(rails)`
+#[pyclass(name = "RailFence", frozen)]
+pub struct PyRailFence {
+    fence: RailFence,
+}
+
+#[pymethods]
+impl PyRailFence {
+    #[new]
+    fn new(rails: u32) -> PyResult<PyRailFence> {
+        RailFence::try_new(rails)
+            .map(|fence| PyRailFence { fence })
+            .map_err(|error| PyValueError::new_err(error.to_string()))
+    }
+
+    /// The number of rails on this fence
+    #[getter]
+    fn rails(&self) -> u32 {
+        self.fence.rails()
+    }
+
+    /// Encode the message in text using the fence rails
+    fn encode(&self, text: &str) -> String {
+        self.fence.encode(text)
+    }
+
+    /// Decode the message in text using the fence rails
+    fn decode(&self, cipher: &str) -> String {
+        self.fence.decode(cipher)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("RailFence({})", self.fence.rails())
+    }
+}
+
+/// The `rail_fence_cipher` extension module
+#[pymodule]
+fn rail_fence_cipher(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyRailFence>()
+}
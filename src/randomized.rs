@@ -0,0 +1,75 @@
+//! Hiding repeated messages behind a random start offset
+//!
+//! A randomized message is encoded from a random point in the zig-zag, and
+//! that offset, in decimal followed by a `:`, is put in front of the result
+//! before the whole thing is encoded again from the fence's usual start.
+//! The receiver undoes the outer encoding to find the offset, then decodes
+//! the rest with it, so the same message sent twice usually looks different
+//! on the wire.
+//!
+//! This is obfuscation, not cryptography.  There are only as many offsets
+//! as the fence has phases, and anyone who knows the format can try them
+//! all.
+
+#[cfg(feature = "rand")]
+use alloc::format;
+use alloc::string::String;
+
+use crate::framed::header_field;
+use crate::{RailFence, RailFenceError};
+#[cfg(feature = "rand")]
+use rand::RngCore;
+
+impl RailFence {
+    /// Encode the message from a random start offset, recording the offset
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to encode
+    /// * `rng`     The source of the random offset
+    ///
+    /// # Returns
+    /// The cipher-text, which is a few characters longer than `text`
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// use rand::SeedableRng;
+    /// let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+    /// let cipher = RailFence::new(3);
+    /// let cipher_text = cipher.encode_randomized("ATTACKATDAWN", &mut rng);
+    /// assert_eq!(Ok(String::from("ATTACKATDAWN")), cipher.decode_randomized(&cipher_text));
+    /// ```
+    #[cfg(feature = "rand")]
+    pub fn encode_randomized(&self, text: &str, rng: &mut impl RngCore) -> String {
+        let phase = rng.next_u32() as usize % self.period().max(1);
+        let shifted = self.clone().with_offset(self.offset + phase).encode(text);
+        self.encode(format!("{}:{}", phase, shifted))
+    }
+
+    /// Decode a message produced by `encode_randomized`
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher`  The cipher-text produced by `encode_randomized`
+    ///
+    /// # Returns
+    /// The clear-text message, or `RailFenceError::BadHeader` if no valid
+    /// offset is recorded, which usually means the fence differs from the
+    /// one that encoded the message
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::new(3);
+    /// assert_eq!(Err(RailFenceError::BadHeader), cipher.decode_randomized("RUSTISGREAT"));
+    /// ```
+    pub fn decode_randomized(&self, cipher: &str) -> Result<String, RailFenceError> {
+        let frame = self.decode(cipher);
+        let (phase, shifted) = header_field(&frame)?;
+        if phase >= self.period().max(1) {
+            return Err(RailFenceError::BadHeader);
+        }
+        Ok(self.clone().with_offset(self.offset + phase).decode(shifted))
+    }
+}
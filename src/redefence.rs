@@ -0,0 +1,52 @@
+//! Reading the rails in a keyed order
+//!
+//! The redefence variant lays the message out on the usual zig-zag but
+//! reads the rails off in the order given by a key, instead of from the top
+//! rail down.  Decoding hands each rail its share of the cipher-text in the
+//! same keyed order.
+
+use alloc::vec;
+
+use crate::RailFence;
+
+impl RailFence {
+    /// Create a fence that reads its rails in the given order
+    ///
+    /// # Arguments
+    ///
+    /// * `order`   The rails, counting the top rail as 0, in the order they
+    ///   are read.  The fence has one rail per entry.
+    ///
+    /// # Returns
+    /// A RailFence struct for doing encoding/decoding
+    ///
+    /// # Panics
+    /// If `order` is empty or is not an arrangement of the rails
+    /// `0..order.len()`, each exactly once
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let cipher = RailFence::with_rail_order(&[2, 0, 3, 1]);
+    /// // Rails "RG", "USR", "SIET" and "TA" are read 2, 0, 3, 1
+    /// assert_eq!("SIETRGTAUSR", cipher.encode("RUSTISGREAT"));
+    /// assert_eq!("RUSTISGREAT", cipher.decode("SIETRGTAUSR"));
+    /// ```
+    pub fn with_rail_order(order: &[u32]) -> RailFence {
+        let mut seen = vec![false; order.len()];
+        for &rail in order {
+            let rail = rail as usize;
+            assert!(
+                rail < order.len() && !seen[rail],
+                "a rail order must list each of the rails 0 to {} once, not {:?}",
+                order.len().saturating_sub(1),
+                order
+            );
+            seen[rail] = true;
+        }
+        assert!(!order.is_empty(), "a rail order needs at least one rail");
+        let mut fence = RailFence::new(order.len() as u32);
+        fence.rail_order = Some(order.iter().map(|&rail| rail as usize).collect());
+        fence
+    }
+}
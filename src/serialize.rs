@@ -0,0 +1,120 @@
+//! Storing fence configurations with serde
+//!
+//! A fence is written as a map of its settings, leaving out nothing, and
+//! read back from a map in which every setting except `rails` may be left
+//! out to take its default.  Reading checks the settings the way
+//! `RailFenceBuilder::validate` does, so a stored configuration that could
+//! not have been built fails to load instead of producing a broken fence.
+//! A fence with a custom schedule holds code rather than settings and
+//! cannot be written.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use serde::de::Error as _;
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Case, CharFilter, RailFence, RailFenceBuilder, ReadOrder, TurnPolicy, Wave};
+
+/// The stored form of a fence
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Config {
+    rails: u32,
+    #[serde(default)]
+    turn: TurnPolicy,
+    #[serde(default)]
+    wave: Wave,
+    #[serde(default)]
+    read_order: ReadOrder,
+    #[serde(default)]
+    period_alternating: bool,
+    #[serde(default)]
+    offset: usize,
+    #[serde(default)]
+    upward: bool,
+    #[serde(default)]
+    rail_order: Option<Vec<usize>>,
+    #[serde(default)]
+    alphabet: Option<String>,
+    #[serde(default)]
+    case: Case,
+    #[serde(default)]
+    strip_whitespace: bool,
+    #[serde(default)]
+    char_filter: CharFilter,
+    #[serde(default)]
+    period_padding: Option<char>,
+    #[serde(default)]
+    grouping: Option<usize>,
+    #[serde(default)]
+    ignore_whitespace: bool,
+    #[serde(default = "one_round")]
+    rounds: u32,
+}
+
+/// The number of rounds a stored fence has if it does not say
+fn one_round() -> u32 {
+    1
+}
+
+impl Serialize for RailFence {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.schedule.is_some() {
+            return Err(S::Error::custom("a fence with a custom schedule cannot be serialized"));
+        }
+        Config {
+            rails: self.rails,
+            turn: self.turn,
+            wave: self.wave,
+            read_order: self.read_order,
+            period_alternating: self.period_alternating,
+            offset: self.offset,
+            upward: self.upward,
+            rail_order: self.rail_order.clone(),
+            alphabet: self.alphabet.clone(),
+            case: self.case,
+            strip_whitespace: self.strip_whitespace,
+            char_filter: self.char_filter,
+            period_padding: self.period_fill,
+            grouping: self.grouping,
+            ignore_whitespace: self.ignore_whitespace,
+            rounds: self.rounds,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for RailFence {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<RailFence, D::Error> {
+        let config = Config::deserialize(deserializer)?;
+        // A rail too big for a u32 cannot be on the fence, and u32::MAX
+        // fails validation the same way
+        let rail_order = config
+            .rail_order
+            .map(|order| order.into_iter().map(|rail| u32::try_from(rail).unwrap_or(u32::MAX)).collect());
+        let built = RailFenceBuilder::new(config.rails)
+            .turn_policy(config.turn)
+            .wave(config.wave)
+            .read_order(config.read_order)
+            .period_alternating(config.period_alternating)
+            .offset(config.offset)
+            .upward(config.upward)
+            .rail_order(rail_order)
+            .alphabet(config.alphabet.as_deref())
+            .case(config.case)
+            .strip_whitespace(config.strip_whitespace)
+            .char_filter(config.char_filter)
+            .period_padding(config.period_padding)
+            .grouping(config.grouping)
+            .ignore_whitespace(config.ignore_whitespace)
+            .rounds(config.rounds)
+            .build();
+        built.map_err(|problems| {
+            let problems: Vec<String> = problems.iter().map(ToString::to_string).collect();
+            D::Error::custom(problems.join(", "))
+        })
+    }
+}
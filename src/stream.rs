@@ -0,0 +1,238 @@
+//! Streaming bytes through the fence with `std::io`
+//!
+//! The permutation of a message depends on its length, so a stream is cut
+//! into blocks and each block is encoded on its own, exactly as
+//! `encode_bytes` would encode it.  Each block is a whole number of
+//! periods long, so every block starts at the top of the zig-zag and has
+//! the same rail lengths as every other full block; only the last block
+//! of a stream can be shorter.  The result is not the cipher-text of the
+//! whole stream as one message, and it is only read back by a reader with
+//! the same fence and block size.
+//!
+//! Blocks are cut by bytes, so multi-byte UTF-8 characters can be split
+//! across them, and the cipher-text of text is not generally valid UTF-8.
+//!
+//! With the `zeroize` feature, each block is wiped once it has been passed
+//! on, and the writer and reader wipe what they still hold when dropped.
+
+use std::io::{self, Read, Write};
+use std::vec::Vec;
+
+use crate::wipe::wipe;
+use crate::RailFence;
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Encodes everything written to it, one block at a time
+///
+/// A block is only encoded once it is full, or when the writer is
+/// finished, so `flush` passes on the blocks written so far but keeps a
+/// partly filled block back.  Dropping the writer writes any partial block
+/// too, ignoring errors; call `finish` to see them.
+#[derive(Debug)]
+pub struct RailFenceWriter<W: Write> {
+    fence: RailFence,
+    inner: Option<W>,
+    block_len: usize,
+    block: Vec<u8>,
+}
+
+/// Decodes a stream written by a `RailFenceWriter`, one block at a time
+#[derive(Debug)]
+pub struct RailFenceReader<R: Read> {
+    fence: RailFence,
+    inner: Option<R>,
+    block_len: usize,
+    block: Vec<u8>,
+    position: usize,
+}
+
+impl RailFence {
+    /// Wrap a writer so that everything written to it is encoded
+    ///
+    /// # Arguments
+    ///
+    /// * `inner`           Where the cipher-text is written
+    /// * `block_periods`   The number of periods in each block
+    ///
+    /// # Returns
+    /// A writer that encodes blocks of `block_periods * period()` bytes
+    ///
+    /// # Panics
+    /// If `block_periods` is 0, or the fence has a custom schedule and so
+    /// no period
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// use std::io::Write;
+    /// let cipher = RailFence::new(3);
+    /// let mut writer = cipher.writer(Vec::new(), 2);
+    /// writer.write_all(b"WEAREDISCOVERED").unwrap();
+    /// let encoded = writer.finish().unwrap();
+    /// // Blocks of two periods, 8 bytes, are encoded on their own
+    /// assert_eq!(cipher.encode("WEAREDIS") + &cipher.encode("COVERED"), String::from_utf8(encoded.clone()).unwrap());
+    ///
+    /// let mut decoded = Vec::new();
+    /// std::io::copy(&mut cipher.reader(&encoded[..], 2), &mut decoded).unwrap();
+    /// assert_eq!(b"WEAREDISCOVERED".to_vec(), decoded);
+    /// ```
+    pub fn writer<W: Write>(&self, inner: W, block_periods: usize) -> RailFenceWriter<W> {
+        let block_len = self.stream_block_len(block_periods);
+        RailFenceWriter {
+            fence: self.clone(),
+            inner: Some(inner),
+            block_len,
+            block: Vec::with_capacity(block_len),
+        }
+    }
+
+    /// Wrap a reader of cipher-text from `writer` so that reading from it
+    /// gives the clear-text
+    ///
+    /// # Arguments
+    ///
+    /// * `inner`           Where the cipher-text is read from
+    /// * `block_periods`   The number of periods in each block, as given
+    ///   to `writer`
+    ///
+    /// # Panics
+    /// If `block_periods` is 0, or the fence has a custom schedule and so
+    /// no period
+    pub fn reader<R: Read>(&self, inner: R, block_periods: usize) -> RailFenceReader<R> {
+        let block_len = self.stream_block_len(block_periods);
+        RailFenceReader {
+            fence: self.clone(),
+            inner: Some(inner),
+            block_len,
+            block: Vec::with_capacity(block_len),
+            position: 0,
+        }
+    }
+
+    /// The number of bytes in each block of a stream
+    pub(crate) fn stream_block_len(&self, block_periods: usize) -> usize {
+        assert!(block_periods > 0, "a block must be at least one period long");
+        assert!(self.period() > 0, "a fence with a custom schedule has no period to block by");
+        block_periods * self.period()
+    }
+}
+
+impl<W: Write> RailFenceWriter<W> {
+    /// The number of bytes in each block
+    pub fn block_len(&self) -> usize {
+        self.block_len
+    }
+
+    /// The writer the cipher-text goes to
+    pub fn get_ref(&self) -> &W {
+        self.inner.as_ref().expect("the writer is only taken by finish")
+    }
+
+    /// Encode and write any partial block, and give back the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_block()?;
+        Ok(self.inner.take().expect("the writer is only taken by finish"))
+    }
+
+    /// Encode and write the buffered block, if there is one
+    fn write_block(&mut self) -> io::Result<()> {
+        if self.block.is_empty() {
+            return Ok(());
+        }
+        let mut encoded = self.fence.encode_bytes(&self.block);
+        self.inner.as_mut().expect("the writer is only taken by finish").write_all(&encoded)?;
+        wipe(&mut encoded);
+        wipe(&mut self.block);
+        self.block.clear();
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for RailFenceWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if self.block.len() == self.block_len {
+            self.write_block()?;
+        }
+        let taken = data.len().min(self.block_len - self.block.len());
+        self.block.extend_from_slice(&data[..taken]);
+        Ok(taken)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.block.len() == self.block_len {
+            self.write_block()?;
+        }
+        self.inner.as_mut().expect("the writer is only taken by finish").flush()
+    }
+}
+
+impl<W: Write> Drop for RailFenceWriter<W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            let _ = self.write_block();
+        }
+        // Anything left failed to write
+        wipe(&mut self.block);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<W: Write> Zeroize for RailFenceWriter<W> {
+    /// Wipe the partly filled block, which is then never written
+    fn zeroize(&mut self) {
+        self.block.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<W: Write> ZeroizeOnDrop for RailFenceWriter<W> {}
+
+impl<R: Read> RailFenceReader<R> {
+    /// The number of bytes in each block
+    pub fn block_len(&self) -> usize {
+        self.block_len
+    }
+
+    /// Give back the inner reader, dropping any decoded bytes not yet read
+    pub fn into_inner(mut self) -> R {
+        self.inner.take().expect("the reader is only taken by into_inner")
+    }
+}
+
+impl<R: Read> Read for RailFenceReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.block.len() && !out.is_empty() {
+            let mut cipher = Vec::with_capacity(self.block_len);
+            let inner = self.inner.as_mut().expect("the reader is only taken by into_inner");
+            inner.take(self.block_len as u64).read_to_end(&mut cipher)?;
+            wipe(&mut self.block);
+            self.block = self.fence.decode_bytes(&cipher);
+            wipe(&mut cipher);
+            self.position = 0;
+        }
+        let read = out.len().min(self.block.len() - self.position);
+        out[..read].copy_from_slice(&self.block[self.position..self.position + read]);
+        self.position += read;
+        Ok(read)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<R: Read> Drop for RailFenceReader<R> {
+    fn drop(&mut self) {
+        self.block.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<R: Read> Zeroize for RailFenceReader<R> {
+    /// Wipe the decoded bytes not yet read, which are then skipped
+    fn zeroize(&mut self) {
+        self.block.zeroize();
+        self.position = 0;
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<R: Read> ZeroizeOnDrop for RailFenceReader<R> {}
@@ -0,0 +1,125 @@
+//! Drawing the fence as an SVG image
+//!
+//! The image has the same grid as `diagram`, one row per rail and one
+//! column per character, with a framed cell around each placed character
+//! and nothing drawn in the empty cells.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::RailFence;
+
+/// The sizes and font of an SVG drawing of the fence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SvgStyle {
+    /// The width and height of each cell, in pixels
+    pub cell_size: u32,
+    /// The font size of the characters, in pixels
+    pub font_size: u32,
+    /// The CSS font family of the characters
+    pub font_family: String,
+}
+
+impl Default for SvgStyle {
+    fn default() -> SvgStyle {
+        SvgStyle { cell_size: 24, font_size: 16, font_family: String::from("monospace") }
+    }
+}
+
+impl RailFence {
+    /// Draw the message on the fence as an SVG image, in the default style
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to draw
+    ///
+    /// # Returns
+    /// A standalone SVG document
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let svg = RailFence::new(2).to_svg("RUST");
+    /// assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"96\" height=\"48\""));
+    /// assert_eq!(4, svg.matches("<text").count());
+    /// ```
+    pub fn to_svg(&self, text: &str) -> String {
+        self.to_svg_with(text, &SvgStyle::default())
+    }
+
+    /// Draw the message on the fence as an SVG image
+    ///
+    /// Characters that are special in XML are escaped, so any message can
+    /// be drawn.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text string to draw
+    /// * `style`   The cell size and font to draw with
+    ///
+    /// # Returns
+    /// A standalone SVG document
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let style = SvgStyle { cell_size: 40, ..SvgStyle::default() };
+    /// let svg = RailFence::new(3).to_svg_with("RUST", &style);
+    /// assert!(svg.contains("width=\"160\" height=\"120\""));
+    /// ```
+    pub fn to_svg_with(&self, text: &str, style: &SvgStyle) -> String {
+        let grid = self.grid(text);
+        let cell = style.cell_size;
+        let width = grid.first().map_or(0, Vec::len) as u32 * cell;
+        let height = grid.len() as u32 * cell;
+        let mut svg = String::new();
+        // Writing to a String cannot fail
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">",
+            width, height, width, height
+        );
+        let _ = writeln!(
+            svg,
+            "<g font-family=\"{}\" font-size=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">",
+            escape(&style.font_family),
+            style.font_size
+        );
+        for (row, rail) in grid.iter().enumerate() {
+            for (column, c) in rail.iter().enumerate().filter_map(|(column, cell)| cell.map(|c| (column, c))) {
+                let (x, y) = (column as u32 * cell, row as u32 * cell);
+                let _ = writeln!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"black\"/>",
+                    x, y, cell, cell
+                );
+                let _ = writeln!(
+                    svg,
+                    "<text x=\"{}\" y=\"{}\">{}</text>",
+                    x + cell / 2,
+                    y + cell / 2,
+                    escape(&c.to_string())
+                );
+            }
+        }
+        svg.push_str("</g>\n</svg>\n");
+        svg
+    }
+}
+
+/// Escape the characters that cannot appear as-is in XML text or attributes
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,49 @@
+//! Random fences for testing code built on this crate
+//!
+//! Property tests in other crates often want to run against many different
+//! fences.  Every fence produced here passes `RailFenceBuilder::validate`,
+//! so callers do not have to repeat those rules.
+
+use rand::RngCore;
+
+use crate::{RailFence, RailFenceBuilder, ReadOrder, TurnPolicy, Wave};
+
+/// Build a fence with random, valid options
+///
+/// # Arguments
+///
+/// * `rng`         The source of randomness
+/// * `max_rails`   The largest rail count to choose, treated as 1 if 0
+///
+/// # Returns
+/// A fence with between 1 and `max_rails` rails, a random turn policy or
+/// sawtooth wave, start offset within its period, and read order
+///
+/// # Example
+/// ```
+/// use rail_fence_cipher::testutil::random_fence;
+/// use rand::SeedableRng;
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+/// let cipher = random_fence(&mut rng, 8);
+/// assert!((1..=8).contains(&cipher.rails()));
+/// assert_eq!("RUSTISGREAT", cipher.decode(cipher.encode("RUSTISGREAT")));
+/// ```
+pub fn random_fence(rng: &mut impl RngCore, max_rails: u32) -> RailFence {
+    let rails = 1 + rng.next_u32() % max_rails.max(1);
+    // A sawtooth wave never turns, so it cannot have a turn policy
+    let (turn, wave) = match rng.next_u32() % 3 {
+        0 => (TurnPolicy::Bounce, Wave::Triangle),
+        1 => (TurnPolicy::Dwell, Wave::Triangle),
+        _ => (TurnPolicy::Bounce, Wave::Sawtooth),
+    };
+    let period = RailFence::new(rails).with_turn_policy(turn).with_wave(wave).period();
+    let offset = rng.next_u32() as usize % period;
+    let builder = RailFenceBuilder::new(rails).turn_policy(turn).wave(wave).offset(offset);
+    // Column-major reading cannot be combined with alternating periods
+    let builder = match rng.next_u32() % 3 {
+        0 => builder.read_order(ReadOrder::ColumnMajor),
+        1 => builder.period_alternating(true),
+        _ => builder,
+    };
+    builder.build().expect("random options are always valid")
+}
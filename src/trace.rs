@@ -0,0 +1,108 @@
+//! Following the encoding one character at a time
+//!
+//! `encode_trace` reports where encoding puts each character: the rail it
+//! is placed on, the way the zig-zag heads from there, and where it ends up
+//! in the cipher-text.  The rails come from the same code that `encode`
+//! uses, so a display built on the trace always matches the cipher-text.
+
+use alloc::vec::Vec;
+
+use crate::wipe::{wipe_cow, working};
+use crate::{CharFilter, RailFence};
+
+/// Which way the zig-zag heads from one character to the next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Heading {
+    /// To a lower rail
+    Down,
+    /// To a higher rail, including the jump from the bottom rail back to
+    /// the top of a sawtooth wave
+    Up,
+    /// To the same rail, as in a dwell at a turn or on a single rail
+    Level,
+}
+
+/// One character placed on the fence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    /// The character's index in the normalized and padded clear-text, in
+    /// characters
+    pub input_index: usize,
+    /// The character placed
+    pub ch: char,
+    /// The rail it is placed on in the first round, counting from the top
+    pub rail: usize,
+    /// Which way the zig-zag heads after it
+    pub heading: Heading,
+    /// Its index in the cipher-text from `encode`, in characters, counting
+    /// any group separators
+    pub output_index: usize,
+}
+
+impl RailFence {
+    /// Encode a message step by step, reporting each character placed
+    ///
+    /// The message is normalized and padded as `encode` does it, and with
+    /// `CharFilter::PreservePositions` only its letters are placed.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`    The clear-text message
+    ///
+    /// # Returns
+    /// A placement for each character put on the fence, in the order they
+    /// are placed.  The heading of the last one is where the zig-zag would
+    /// go next, or `Heading::Level` on a fence with a custom schedule.
+    ///
+    /// # Panics
+    /// On the same fences as `encode`
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let trace = RailFence::new(3).encode_trace("WEARE");
+    /// let rails: Vec<usize> = trace.iter().map(|placement| placement.rail).collect();
+    /// assert_eq!(vec![0, 1, 2, 1, 0], rails);
+    /// assert_eq!(Heading::Up, trace[2].heading);
+    /// // "WEARE" encodes to "WEERA"
+    /// assert_eq!(Placement { input_index: 2, ch: 'A', rail: 2, heading: Heading::Up, output_index: 4 }, trace[2]);
+    /// ```
+    pub fn encode_trace(&self, text: impl AsRef<str>) -> Vec<Placement> {
+        let normalized = self.normalize(text.as_ref());
+        let padded = self.pad_to_period(&normalized);
+        let chars = working(padded.chars().collect::<Vec<char>>());
+        // The clear-text positions of the characters that go on the fence
+        let placed: Vec<usize> = if self.char_filter == CharFilter::PreservePositions {
+            (0..chars.len()).filter(|&i| chars[i].is_alphabetic()).collect()
+        } else {
+            (0..chars.len()).collect()
+        };
+        let group_len = self.grouping.filter(|&group_len| group_len > 0);
+        let permutation = self.permutation(placed.len());
+        let trace: Vec<Placement> = (0..placed.len())
+            .map(|step| {
+                let rail = self.rail_at(step);
+                let next = if self.schedule.is_none() || step + 1 < placed.len() {
+                    self.rail_at(step + 1)
+                } else {
+                    rail
+                };
+                let heading = match next {
+                    next if next > rail => Heading::Down,
+                    next if next < rail => Heading::Up,
+                    _ => Heading::Level,
+                };
+                let output_index = placed[permutation[step]];
+                let output_index = match group_len {
+                    Some(group_len) => output_index + output_index / group_len,
+                    None => output_index,
+                };
+                Placement { input_index: placed[step], ch: chars[placed[step]], rail, heading, output_index }
+            })
+            .collect();
+        drop(chars);
+        wipe_cow(padded);
+        wipe_cow(normalized);
+        trace
+    }
+}
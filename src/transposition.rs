@@ -0,0 +1,239 @@
+//! Transposition ciphers behind a common trait
+//!
+//! The rail fence is one of a family of ciphers that only reorder the
+//! characters of a message.  `TranspositionCipher` lets code such as a
+//! teaching tool hold any of them and swap one for another.  The other
+//! members here are the scytale, which reads a message written in rows
+//! round a rod by its columns, and a route cipher that reads the rows in a
+//! spiral.  Like `encode_2d`, both skip the missing cells of a short last
+//! row instead of padding, so the cipher-text is as long as the message.
+//! Any of them can be chained into a `Pipeline` with `then`.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::columnar::column_order;
+use crate::{Case, CharFilter, Pipeline, RailFence, RailFenceError};
+
+/// A cipher that encodes by reordering the characters of a message
+pub trait TranspositionCipher {
+    /// Encode the message in text
+    fn encode(&self, text: &str) -> String;
+
+    /// Decode the message in cipher
+    fn decode(&self, cipher: &str) -> String;
+
+    /// Encode the message in text, reporting a bad key instead of panicking
+    ///
+    /// The default suits ciphers whose keys are always usable.
+    fn try_encode(&self, text: &str) -> Result<String, RailFenceError> {
+        Ok(self.encode(text))
+    }
+
+    /// Decode the message in cipher, reporting a bad key instead of
+    /// panicking
+    ///
+    /// The default suits ciphers whose keys are always usable.
+    fn try_decode(&self, cipher: &str) -> Result<String, RailFenceError> {
+        Ok(self.decode(cipher))
+    }
+
+    /// For each cipher-text position, the clear-text position encoding
+    /// takes it from, if encoding a message of `len` characters only
+    /// reorders them
+    ///
+    /// A `Pipeline` uses this to combine its stages into a single
+    /// reordering.  The default of `None` makes it run the stage on its
+    /// own instead.
+    fn encode_order(&self, len: usize) -> Option<Vec<usize>> {
+        let _ = len;
+        None
+    }
+
+    /// Chain another cipher after this one
+    ///
+    /// # Example
+    /// ```
+    /// use rail_fence_cipher::*;
+    /// let pipeline = RailFence::new(3).then(RailFence::new(5));
+    /// let cipher_text = pipeline.encode("WEAREDISCOVERED");
+    /// assert_eq!(RailFence::new(5).encode(RailFence::new(3).encode("WEAREDISCOVERED")), cipher_text);
+    /// assert_eq!("WEAREDISCOVERED", pipeline.decode(&cipher_text));
+    /// ```
+    fn then<C>(self, next: C) -> Pipeline
+    where
+        Self: Sized + Send + Sync + 'static,
+        C: TranspositionCipher + Send + Sync + 'static,
+    {
+        Pipeline::new().then(self).then(next)
+    }
+}
+
+impl TranspositionCipher for RailFence {
+    fn encode(&self, text: &str) -> String {
+        RailFence::encode(self, text)
+    }
+
+    fn decode(&self, cipher: &str) -> String {
+        RailFence::decode(self, cipher)
+    }
+
+    fn try_encode(&self, text: &str) -> Result<String, RailFenceError> {
+        RailFence::try_encode(self, text)
+    }
+
+    fn try_decode(&self, cipher: &str) -> Result<String, RailFenceError> {
+        RailFence::try_decode(self, cipher)
+    }
+
+    fn encode_order(&self, len: usize) -> Option<Vec<usize>> {
+        // The text options can change the length or keep characters put
+        let plain = self.case == Case::Preserve
+            && !self.strip_whitespace
+            && self.char_filter == CharFilter::Passthrough
+            && self.period_fill.is_none()
+            && !self.ignores_whitespace();
+        if self.rails == 0 || !plain {
+            return None;
+        }
+        Some(self.inverse_permutation(len))
+    }
+}
+
+/// The scytale: the message is written in rows along a rod with a given
+/// number of faces, and the strip wound round it is read off column by
+/// column
+///
+/// # Example
+/// ```
+/// use rail_fence_cipher::*;
+/// let scytale = Scytale::new(4);
+/// // Rows "WEAREDI", "SCOVERE", "DFLEEAT" and "ONCE"
+/// assert_eq!("WSDOECFNAOLCRVEEEEEDRAIET", scytale.encode("WEAREDISCOVEREDFLEEATONCE"));
+/// assert_eq!("WEAREDISCOVEREDFLEEATONCE", scytale.decode("WSDOECFNAOLCRVEEEEEDRAIET"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scytale {
+    faces: usize,
+}
+
+impl Scytale {
+    /// Create a scytale with the specified number of faces, where 0 is
+    /// treated as 1
+    pub fn new(faces: usize) -> Scytale {
+        Scytale { faces: faces.max(1) }
+    }
+
+    /// The number of faces, and so of rows
+    pub fn faces(&self) -> usize {
+        self.faces
+    }
+
+    /// The clear-text positions in the order the cipher-text takes them
+    fn order(&self, len: usize) -> Vec<usize> {
+        column_order(len, len.div_ceil(self.faces)).collect()
+    }
+}
+
+impl TranspositionCipher for Scytale {
+    fn encode(&self, text: &str) -> String {
+        gather(text, |len| self.order(len))
+    }
+
+    fn decode(&self, cipher: &str) -> String {
+        scatter(cipher, |len| self.order(len))
+    }
+
+    fn encode_order(&self, len: usize) -> Option<Vec<usize>> {
+        Some(self.order(len))
+    }
+}
+
+/// A route cipher: the message is written in rows of a given width and
+/// read in a clockwise spiral from the top left corner
+///
+/// # Example
+/// ```
+/// use rail_fence_cipher::*;
+/// let route = SpiralRoute::new(3);
+/// // Rows "ABC", "DEF" and "GH"
+/// assert_eq!("ABCFHGDE", route.encode("ABCDEFGH"));
+/// assert_eq!("ABCDEFGH", route.decode("ABCFHGDE"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpiralRoute {
+    cols: usize,
+}
+
+impl SpiralRoute {
+    /// Create a route cipher with rows of the specified width, where 0 is
+    /// treated as 1
+    pub fn new(cols: usize) -> SpiralRoute {
+        SpiralRoute { cols: cols.max(1) }
+    }
+
+    /// The width of the rows
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// The clear-text positions in the order the spiral visits them
+    fn order(&self, len: usize) -> Vec<usize> {
+        let cols = self.cols;
+        let rows = len.div_ceil(cols);
+        let mut order = Vec::with_capacity(len);
+        let (mut top, mut bottom, mut left, mut right) = (0, rows, 0, cols);
+        let mut visit = |row: usize, col: usize| {
+            let position = row * cols + col;
+            if position < len {
+                order.push(position);
+            }
+        };
+        while top < bottom && left < right {
+            (left..right).for_each(|col| visit(top, col));
+            (top + 1..bottom).for_each(|row| visit(row, right - 1));
+            if top + 1 < bottom {
+                (left..right - 1).rev().for_each(|col| visit(bottom - 1, col));
+            }
+            if left + 1 < right {
+                (top + 1..bottom - 1).rev().for_each(|row| visit(row, left));
+            }
+            top += 1;
+            bottom -= 1;
+            left += 1;
+            right -= 1;
+        }
+        order
+    }
+}
+
+impl TranspositionCipher for SpiralRoute {
+    fn encode(&self, text: &str) -> String {
+        gather(text, |len| self.order(len))
+    }
+
+    fn decode(&self, cipher: &str) -> String {
+        scatter(cipher, |len| self.order(len))
+    }
+
+    fn encode_order(&self, len: usize) -> Option<Vec<usize>> {
+        Some(self.order(len))
+    }
+}
+
+/// Take the characters of text in the order given
+pub(crate) fn gather(text: &str, order: impl FnOnce(usize) -> Vec<usize>) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    order(chars.len()).into_iter().map(|from| chars[from]).collect()
+}
+
+/// Put the characters of text back where `gather` took them from
+pub(crate) fn scatter(text: &str, order: impl FnOnce(usize) -> Vec<usize>) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut clear = vec!['\0'; chars.len()];
+    for (c, to) in chars.into_iter().zip(order(clear.len())) {
+        clear[to] = c;
+    }
+    clear.into_iter().collect()
+}
@@ -0,0 +1,47 @@
+//! Bindings for JavaScript through wasm-bindgen
+//!
+//! Each function takes the rail count as a plain number and works on JS
+//! strings.  Problems that the `RailFence` methods would panic on, such as
+//! a fence with no rails, are thrown as JS `Error`s instead.
+//!
+//! The bindings are exported from whichever `cdylib` links this crate, so
+//! a browser playground only needs a small crate of its own that depends
+//! on this one with the `wasm` feature and is built with `wasm-pack`.
+
+use alloc::string::String;
+
+use wasm_bindgen::prelude::*;
+
+use crate::RailFence;
+
+/// Encode a message on a fence with the given number of rails
+///
+/// # Errors
+/// Throws if `rails` is 0
+#[wasm_bindgen]
+pub fn encode(text: &str, rails: u32) -> Result<String, JsError> {
+    Ok(RailFence::try_new(rails)?.try_encode(text)?)
+}
+
+/// Decode a message from a fence with the given number of rails
+///
+/// # Errors
+/// Throws if `rails` is 0
+#[wasm_bindgen]
+pub fn decode(cipher: &str, rails: u32) -> Result<String, JsError> {
+    Ok(RailFence::try_new(rails)?.try_decode(cipher)?)
+}
+
+/// Decode a message without its key, trying every rail count up to
+/// `max_rails` and keeping the most English-like clear-text
+///
+/// # Errors
+/// Throws if `max_rails` is 0
+#[wasm_bindgen]
+pub fn crack(cipher: &str, max_rails: u32) -> Result<String, JsError> {
+    RailFence::crack(cipher, max_rails)
+        .into_iter()
+        .next()
+        .map(|candidate| candidate.clear_text)
+        .ok_or_else(|| JsError::new("max_rails must be at least 1"))
+}
@@ -0,0 +1,83 @@
+//! Wiping working copies of messages
+//!
+//! With the `zeroize` feature, the copies of a message that encoding and
+//! decoding make along the way, such as the normalized clear-text or the
+//! characters of a message split out for transposing, are overwritten with
+//! zeros before their memory is freed, and so are the buffers of the types
+//! that collect a message over time.  Buffers that grow are moved to a
+//! larger allocation by hand, so the old one can be wiped too.  Without the
+//! feature these helpers do nothing.
+//!
+//! Only the crate's own copies are wiped.  The text passed in and the
+//! result handed back belong to the caller.
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+/// Message text the crate can wipe, which with the `zeroize` feature is
+/// anything that implements `Zeroize`
+#[cfg(feature = "zeroize")]
+pub(crate) trait Wipe: Zeroize {}
+
+#[cfg(feature = "zeroize")]
+impl<T: Zeroize + ?Sized> Wipe for T {}
+
+/// Message text the crate can wipe, which without the `zeroize` feature is
+/// anything, since nothing is wiped
+#[cfg(not(feature = "zeroize"))]
+pub(crate) trait Wipe {}
+
+#[cfg(not(feature = "zeroize"))]
+impl<T: ?Sized> Wipe for T {}
+
+/// A working copy of message text, wiped when it is dropped
+#[cfg(feature = "zeroize")]
+pub(crate) type Working<T> = zeroize::Zeroizing<T>;
+
+/// A working copy of message text, wiped when it is dropped
+#[cfg(not(feature = "zeroize"))]
+pub(crate) type Working<T> = T;
+
+/// Mark a copy of message text to be wiped when it is dropped
+#[cfg(feature = "zeroize")]
+pub(crate) fn working<T: Wipe>(copy: T) -> Working<T> {
+    zeroize::Zeroizing::new(copy)
+}
+
+/// Mark a copy of message text to be wiped when it is dropped
+#[cfg(not(feature = "zeroize"))]
+pub(crate) fn working<T: Wipe>(copy: T) -> Working<T> {
+    copy
+}
+
+/// Wipe a working copy of message text
+#[cfg(feature = "zeroize")]
+pub(crate) fn wipe<T: Wipe + ?Sized>(buffer: &mut T) {
+    buffer.zeroize();
+}
+
+/// Wipe a working copy of message text
+#[cfg(not(feature = "zeroize"))]
+pub(crate) fn wipe<T: Wipe + ?Sized>(_buffer: &mut T) {}
+
+/// Wipe a working copy of message text, if a copy was made
+pub(crate) fn wipe_cow(text: Cow<'_, str>) {
+    if let Cow::Owned(mut text) = text {
+        wipe(&mut text);
+    }
+}
+
+/// Append to a buffer of message text, wiping its old allocation if it has
+/// to grow
+pub(crate) fn push_str(buffer: &mut String, chunk: &str) {
+    #[cfg(feature = "zeroize")]
+    if buffer.capacity() - buffer.len() < chunk.len() {
+        let mut grown = String::with_capacity((buffer.len() + chunk.len()).max(2 * buffer.capacity()));
+        grown.push_str(buffer);
+        core::mem::swap(buffer, &mut grown);
+        grown.zeroize();
+    }
+    buffer.push_str(chunk);
+}
@@ -0,0 +1,75 @@
+//! Allocation counts for rail-fence-cipher
+//!
+//! The whole test binary shares one counting allocator, so this file holds
+//! a single test to keep other threads from allocating during the count.
+
+use rail_fence_cipher::*;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counts allocations and reallocations made through the global allocator
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// The number of allocations made while running f
+fn allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    let result = f();
+    let after = ALLOCATIONS.load(Ordering::SeqCst);
+    drop(result);
+    after - before
+}
+
+#[test]
+/// large inputs are transposed without growing the output buffers
+fn test_large_inputs_do_not_reallocate() {
+    let rail_fence = RailFence::new(5);
+    let ascii = "WEAREDISCOVEREDFLEEATONCE".repeat(4000);
+    let wide = "古池蛙飛び込む水の音".repeat(10000);
+    let ascii_cipher = rail_fence.encode(&ascii);
+    let wide_cipher = rail_fence.encode(&wide);
+
+    // The rail lengths, the list of rails and one buffer per rail
+    assert_eq!(allocations(|| rail_fence.encode_rails(&ascii)), 2 + 5);
+    // The rail lengths, the rail starts, the permutation, its inverse for
+    // encoding, and the output
+    assert_eq!(allocations(|| rail_fence.encode(&ascii)), 5);
+    assert_eq!(allocations(|| rail_fence.decode(&ascii_cipher)), 4);
+    // The same, plus the input and output characters before the output
+    // string is built
+    assert_eq!(allocations(|| rail_fence.encode(&wide)), 5 + 2);
+    assert_eq!(allocations(|| rail_fence.decode(&wide_cipher)), 4 + 2);
+
+    // In-place transposition needs nothing beyond the buffer itself
+    let mut buf = String::from("古池蛙飛び込む水の音 WEAREDISCOVERED");
+    assert_eq!(allocations(|| rail_fence.encode_in_place(&mut buf)), 0);
+    assert_eq!(allocations(|| rail_fence.decode_in_place(&mut buf)), 0);
+
+    // Neither does a fence with its rails fixed at compile time
+    let fixed = ConstRailFence::<5>::new();
+    let mut out = vec![0u8; ascii.len()];
+    assert_eq!(allocations(|| fixed.encode_into_slice(ascii.as_bytes(), &mut out)), 0);
+    let mut back = vec![0u8; ascii.len()];
+    assert_eq!(allocations(|| fixed.decode_into_slice(&out, &mut back)), 0);
+    assert_eq!(back, ascii.as_bytes());
+}
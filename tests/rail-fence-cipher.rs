@@ -109,3 +109,3247 @@ fn test_decode_with_six_rails() {
 fn test_encode_wide_characters() {
     process_encode_case("古池蛙飛び込む水の音", 3, "古びの池飛込水音蛙む");
 }
+
+#[test]
+/// megabytes of cipher-text decode back to the clear-text
+fn test_decode_large_text() {
+    let text: String = "WEAREDISCOVEREDFLEEATONCE古池".chars().cycle().take(2 * 1024 * 1024).collect();
+    let rail_fence = RailFence::new(7);
+    let cipher = rail_fence.encode(&text);
+    assert_eq!(rail_fence.decode(&cipher), text);
+}
+
+#[test]
+/// decoding characters gives the characters of decode
+fn test_decode_chars_matches_decode() {
+    for rails in 1..8 {
+        let rail_fence = RailFence::new(rails).with_offset(1);
+        for cipher in &["", "RGUSRSIETTA", "古池蛙飛び込む水の音", "Zoë, 古池"] {
+            let chars: Vec<char> = cipher.chars().collect();
+            let decoded = rail_fence.decode_chars(&chars);
+            assert_eq!(decoded.iter().collect::<String>(), rail_fence.decode(cipher));
+            assert_eq!(rail_fence.encode(decoded.iter().collect::<String>()), *cipher);
+        }
+    }
+}
+
+// ranges
+
+#[test]
+/// encode a range in the middle of a record
+fn test_encode_range_keeps_prefix_and_suffix() {
+    let rail_fence = RailFence::new(3);
+    let encoded = rail_fence.encode_range("name=WEAREDISCOVERED;", 5..19);
+    assert_eq!(encoded, format!("name={};", rail_fence.encode("WEAREDISCOVERED")));
+}
+
+#[test]
+/// decode a range reverses encode of the same range
+fn test_decode_range_round_trip() {
+    let rail_fence = RailFence::new(4);
+    let text = "key: EXERCISMISAWESOME, done";
+    let encoded = rail_fence.encode_range(text, 5..22);
+    assert_eq!(rail_fence.decode_range(&encoded, 5..22), text);
+}
+
+#[test]
+#[should_panic]
+/// a range past the end of the text is rejected
+fn test_encode_range_out_of_bounds() {
+    RailFence::new(3).encode_range("SHORT", 2..9);
+}
+
+// analysis
+
+#[test]
+/// rail lengths add up to the message length
+fn test_rail_lengths_sum_to_length() {
+    for rails in 1..8 {
+        let rail_fence = RailFence::new(rails);
+        for len in 0..40 {
+            let lengths = rail_fence.rail_lengths(len);
+            assert_eq!(lengths.len(), rails as usize);
+            assert_eq!(lengths.iter().sum::<usize>(), len);
+        }
+    }
+}
+
+#[test]
+/// rail lengths match the rails actually produced by encode
+fn test_rail_lengths_match_encode() {
+    let info = RailFence::new(4).analyze("EXERCISES".len());
+    assert_eq!(info.period, 6);
+    assert_eq!(info.full_periods, 1);
+    assert_eq!(info.remainder, 3);
+    // "ESXIEECSR" is read off as "ES", "XIE", "ECS" and "R"
+    assert_eq!(info.rail_lengths, vec![2, 3, 3, 1]);
+}
+
+#[test]
+/// short messages and single rails are left unchanged
+fn test_is_degenerate() {
+    assert!(RailFence::new(1).is_degenerate(10));
+    assert!(RailFence::new(5).is_degenerate(5));
+    assert!(!RailFence::new(5).is_degenerate(6));
+    assert!(RailFence::new(6).analyze(4).is_degenerate);
+}
+
+#[test]
+/// turnarounds follow the period arithmetic on a plain fence
+fn test_turnarounds() {
+    for rails in 2..8u32 {
+        for len in 2..40usize {
+            assert_eq!(RailFence::new(rails).turnarounds(len), (len - 2) / (rails as usize - 1));
+        }
+    }
+    assert_eq!(RailFence::new(3).turnarounds(0), 0);
+    assert_eq!(RailFence::new(3).turnarounds(1), 0);
+    assert_eq!(RailFence::new(1).turnarounds(100), 0);
+    // Dwelling on the end rails does not add turnarounds
+    assert_eq!(RailFence::new(3).with_turn_policy(TurnPolicy::Dwell).turnarounds(12), 3);
+}
+
+// padding
+
+#[test]
+#[cfg(feature = "rand")]
+/// padded messages of different lengths encode to the same length
+fn test_encode_padded_to_uniform_length() {
+    use rand::SeedableRng;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+    let rail_fence = RailFence::new(4);
+    for text in &["", "HI", "WEAREDISCOVERED", "A MESSAGE: WITH 2 COLONS:"] {
+        let encoded = rail_fence.encode_padded_to(text, 40, &mut rng);
+        assert_eq!(encoded.chars().count(), 40);
+        assert_eq!(rail_fence.decode_padded(&encoded).unwrap(), *text);
+    }
+}
+
+#[test]
+#[cfg(feature = "rand")]
+/// a message longer than the target is not truncated
+fn test_encode_padded_to_short_target() {
+    use rand::SeedableRng;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+    let rail_fence = RailFence::new(3);
+    let encoded = rail_fence.encode_padded_to("WEAREDISCOVERED", 5, &mut rng);
+    assert_eq!(rail_fence.decode_padded(&encoded).unwrap(), "WEAREDISCOVERED");
+}
+
+#[test]
+/// power-of-two padding keeps trailing fill characters of the message
+fn test_pow2_round_trip() {
+    let rail_fence = RailFence::new(4);
+    for text in &["", "A", "RUSTISGREAT", "ENDS WITH XX", "XXXXXX", "é🦀:"] {
+        let encoded = rail_fence.encode_pow2(text, 'X');
+        assert!(encoded.chars().count().is_power_of_two());
+        assert_eq!(rail_fence.decode_pow2(&encoded).unwrap(), *text);
+    }
+    // "14:" and 13 characters are exactly 16 already
+    assert_eq!(rail_fence.encode_pow2("THIRTEENCHARS", 'X').chars().count(), 16);
+}
+
+#[test]
+/// cipher-text without a length header is rejected
+fn test_decode_padded_without_header() {
+    let rail_fence = RailFence::new(3);
+    let encoded = rail_fence.encode("NOHEADER");
+    assert_eq!(rail_fence.decode_padded(&encoded), Err(RailFenceError::InvalidPadding));
+    let encoded = rail_fence.encode("99:SHORT");
+    assert_eq!(rail_fence.decode_padded(&encoded), Err(RailFenceError::InvalidPadding));
+}
+
+// cracking
+
+#[test]
+/// decode_all tries every rail count in order
+fn test_decode_all() {
+    let candidates = RailFence::decode_all("ESXIEECSR", 5);
+    assert_eq!(candidates.len(), 5);
+    assert_eq!(candidates[0], (1, String::from("ESXIEECSR")));
+    assert_eq!(candidates[3], (4, String::from("EXERCISES")));
+}
+
+#[test]
+/// encode_range_of_rails gives one cipher-text per rail count
+fn test_encode_range_of_rails() {
+    let outputs = RailFence::encode_range_of_rails("WEAREDISCOVEREDFLEEATONCE", 1..=5).unwrap();
+    assert_eq!(outputs.len(), 5);
+    for (rails, encoded) in &outputs {
+        assert_eq!(*encoded, RailFence::new(*rails).encode("WEAREDISCOVEREDFLEEATONCE"));
+    }
+    assert_eq!(RailFence::encode_range_of_rails("ABC", 0..=0), Err(RailFenceError::ZeroRails));
+}
+
+#[test]
+/// best_rails accepts any scoring function
+fn test_best_rails_custom_score() {
+    let cipher = RailFence::new(5).encode("THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG");
+    let score = |text: &str| text.matches("FOX").count() as f64;
+    let (rails, clear_text) = RailFence::best_rails(&cipher, 10, score);
+    assert_eq!(rails, 5);
+    assert_eq!(clear_text, "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG");
+}
+
+#[test]
+#[cfg(feature = "english")]
+/// the built-in scorer recovers an English message
+fn test_best_rails_english() {
+    use rail_fence_cipher::english::english_score;
+    let text = "THEDEVILISINTHEDETAILS";
+    for rails in 2..6 {
+        let cipher = RailFence::new(rails).encode(text);
+        assert_eq!(RailFence::best_rails(&cipher, 8, english_score), (rails, String::from(text)));
+    }
+}
+
+#[test]
+/// rank_rails sorts every rail count by score, keeping ties in rail order
+fn test_rank_rails() {
+    let cipher = RailFence::new(4).encode("EXERCISES");
+    let candidates = RailFence::rank_rails(&cipher, 6, |text: &str| if text == "EXERCISES" { 1.0 } else { 0.0 });
+    let rails: Vec<u32> = candidates.iter().map(|candidate| candidate.rails).collect();
+    assert_eq!(rails, vec![4, 1, 2, 3, 5, 6]);
+    assert_eq!(candidates[0], Candidate { rails: 4, clear_text: String::from("EXERCISES"), score: 1.0 });
+    assert!(RailFence::rank_rails("ABC", 0, |_: &str| 0.0).is_empty());
+}
+
+#[test]
+#[cfg(feature = "english")]
+/// crack puts the right rail count first for English messages
+fn test_crack_english() {
+    let text = "WE ARE DISCOVERED SO FLEE AT ONCE AND MEET ME BY THE OLD BRIDGE";
+    for rails in 2..8 {
+        let candidates = RailFence::crack(&RailFence::new(rails).encode(text), 12);
+        assert_eq!(candidates.len(), 12);
+        assert_eq!((candidates[0].rails, candidates[0].clear_text.as_str()), (rails, text));
+        assert!(candidates.windows(2).all(|pair| pair[0].score >= pair[1].score));
+    }
+}
+
+#[test]
+/// short messages give few distinct cipher-texts
+fn test_distinct_ciphertexts() {
+    // 1 and 4+ rails leave ABCD alone; 2 and 3 rails give ACBD and ABDC
+    assert_eq!(RailFence::distinct_ciphertexts("ABCD", 10), 3);
+    assert_eq!(RailFence::distinct_ciphertexts("", 5), 1);
+    assert_eq!(RailFence::distinct_ciphertexts("ABCD", 0), 0);
+}
+
+#[test]
+#[cfg(feature = "std")]
+/// rail frequencies count the characters on each rail
+fn test_rail_frequencies() {
+    let frequencies = RailFence::new(3).rail_frequencies("WEAREDISCOVEREDFLEEATONCE");
+    assert_eq!(frequencies.len(), 3);
+    // The top rail is "WECRLTE"
+    assert_eq!(frequencies[0].get(&'E'), Some(&2));
+    assert_eq!(frequencies[0].values().sum::<usize>(), 7);
+    assert_eq!(frequencies.iter().map(|rail| rail.values().sum::<usize>()).sum::<usize>(), 25);
+    assert!(RailFence::new(2).rail_frequencies("").iter().all(|rail| rail.is_empty()));
+}
+
+// rails
+
+#[test]
+/// joining the rails gives the cipher-text
+fn test_encode_rails_concatenate_to_encode() {
+    let rail_fence = RailFence::new(3);
+    let rails = rail_fence.encode_rails("WEAREDISCOVEREDFLEEATONCE");
+    assert_eq!(rails, vec!["WECRLTE", "ERDSOEEFEAOC", "AIVDEN"]);
+    assert_eq!(rails.concat(), rail_fence.encode("WEAREDISCOVEREDFLEEATONCE"));
+}
+
+#[test]
+/// rails can be decoded directly
+fn test_decode_from_rails() {
+    let rail_fence = RailFence::new(3);
+    let rails = rail_fence.encode_rails("THEDEVILISINTHEDETAILS");
+    assert_eq!(rail_fence.decode_from_rails(&rails).unwrap(), "THEDEVILISINTHEDETAILS");
+    assert_eq!(
+        rail_fence.decode_from_rails(&rails[..2]),
+        Err(RailFenceError::RailCountMismatch { expected: 3, found: 2 })
+    );
+}
+
+#[test]
+/// delimited encoding round-trips, including empty rails
+fn test_delimited_round_trip() {
+    let rail_fence = RailFence::new(5);
+    let encoded = rail_fence.encode_delimited("HI", '|');
+    assert_eq!(encoded, "H|I|||");
+    assert_eq!(rail_fence.decode_delimited(&encoded, '|').unwrap(), "HI");
+    let encoded = rail_fence.encode_delimited("EXERCISMISAWESOME", '/');
+    assert_eq!(encoded, "EIE/XMSM/ESAO/RIWS/CE");
+    assert_eq!(rail_fence.decode_delimited(&encoded, '/').unwrap(), "EXERCISMISAWESOME");
+}
+
+#[test]
+/// separators and escapes in the message are escaped
+fn test_delimited_escapes_separator() {
+    let rail_fence = RailFence::new(2);
+    let encoded = rail_fence.encode_delimited("A|B\\C", '|');
+    assert_eq!(encoded, "ABC|\\|\\\\");
+    assert_eq!(rail_fence.decode_delimited(&encoded, '|').unwrap(), "A|B\\C");
+    for text in &["|||", "\\\\||", "a/b\\c|d", ""] {
+        for sep in ['|', '\\', '/'] {
+            let encoded = rail_fence.encode_delimited(text, sep);
+            assert_eq!(rail_fence.decode_delimited(&encoded, sep).unwrap(), *text);
+        }
+    }
+}
+
+#[test]
+/// a dangling escape is rejected
+fn test_delimited_dangling_escape() {
+    assert_eq!(RailFence::new(2).decode_delimited("AB|C\\", '|'), Err(RailFenceError::InvalidEscape));
+}
+
+#[test]
+/// rail lengths sent with the cipher-text split it into rails
+fn test_decode_with_lengths() {
+    let rail_fence = RailFence::new(3);
+    let text = "古池蛙飛び込む水の音";
+    let lengths = rail_fence.rail_lengths(10);
+    assert_eq!(rail_fence.decode_with_lengths(&rail_fence.encode(text), &lengths), Ok(String::from(text)));
+    assert_eq!(rail_fence.decode_with_lengths("", &[0, 0, 0]), Ok(String::new()));
+}
+
+#[test]
+/// length arrays that do not fit are rejected
+fn test_decode_with_lengths_mismatch() {
+    let rail_fence = RailFence::new(3);
+    let cipher = "WECRLTEERDSOEEFEAOCAIVDEN";
+    assert_eq!(
+        rail_fence.decode_with_lengths(cipher, &[7, 18]),
+        Err(RailFenceError::RailCountMismatch { expected: 3, found: 2 })
+    );
+    assert_eq!(
+        rail_fence.decode_with_lengths(cipher, &[7, 12, 5]),
+        Err(RailFenceError::LengthMismatch { expected: 24, found: 25 })
+    );
+    assert_eq!(
+        rail_fence.decode_with_lengths(cipher, &[8, 11, 6]),
+        Err(RailFenceError::RailLengthMismatch { rail: 0, expected: 7, found: 8 })
+    );
+}
+
+// string arguments
+
+#[test]
+/// encode and decode accept owned and borrowed strings alike
+fn test_encode_decode_accept_as_ref_str() {
+    use std::borrow::Cow;
+    let rail_fence = RailFence::new(3);
+    let owned = String::from("WEAREDISCOVEREDFLEEATONCE");
+    let encoded = rail_fence.encode(&owned);
+    assert_eq!(rail_fence.encode(owned.clone()), encoded);
+    assert_eq!(rail_fence.encode(Cow::Borrowed(owned.as_str())), encoded);
+    assert_eq!(rail_fence.decode(encoded.clone()), owned);
+    assert_eq!(rail_fence.decode(Cow::<str>::Owned(encoded)), owned);
+}
+
+// reconfiguration
+
+#[test]
+/// a reconfigured fence behaves like a freshly constructed one
+fn test_set_rails_matches_new() {
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    let mut rail_fence = RailFence::new(2);
+    for rails in 1..10 {
+        rail_fence.set_rails(rails).unwrap();
+        assert_eq!(rail_fence.rails(), rails);
+        assert_eq!(rail_fence.encode(text), RailFence::new(rails).encode(text));
+    }
+}
+
+#[test]
+/// zero rails is rejected and leaves the fence unchanged
+fn test_set_rails_rejects_zero() {
+    let mut rail_fence = RailFence::new(3);
+    assert_eq!(rail_fence.set_rails(0), Err(RailFenceError::ZeroRails));
+    assert_eq!(rail_fence.rails(), 3);
+}
+
+// integer and byte sequences
+
+#[test]
+/// integer sequences transpose like characters
+fn test_encode_u32_matches_chars() {
+    let rail_fence = RailFence::new(3);
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    let data: Vec<u32> = text.chars().map(u32::from).collect();
+    let expected: Vec<u32> = rail_fence.encode(text).chars().map(u32::from).collect();
+    assert_eq!(rail_fence.encode_u32(&data), expected);
+}
+
+#[test]
+/// arbitrary integer sequences round-trip for rails 1 to 8
+fn test_u32_round_trip() {
+    let data: Vec<u32> = (0..97u32).map(|i| i.wrapping_mul(2_654_435_761)).collect();
+    for rails in 1..=8 {
+        let rail_fence = RailFence::new(rails);
+        for len in 0..data.len() {
+            let encoded = rail_fence.encode_u32(&data[..len]);
+            assert_eq!(rail_fence.decode_u32(&encoded), &data[..len]);
+        }
+    }
+}
+
+#[test]
+/// slices of any item type transpose like characters and round-trip
+fn test_slice_round_trip() {
+    #[derive(Debug, Clone, PartialEq)]
+    struct Token(String);
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    let tokens: Vec<Token> = text.chars().map(|c| Token(c.to_string())).collect();
+    for rails in 1..=8 {
+        let rail_fence = RailFence::new(rails).with_turn_policy(TurnPolicy::Dwell);
+        let encoded = rail_fence.encode_slice(&tokens);
+        let joined: String = encoded.iter().map(|token| token.0.as_str()).collect();
+        assert_eq!(joined, rail_fence.encode(text));
+        assert_eq!(rail_fence.decode_slice(&encoded), tokens);
+    }
+}
+
+#[test]
+/// bytes transpose like the characters of ascii text
+fn test_encode_bytes_matches_chars() {
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    for rails in 1..=8 {
+        let rail_fence = RailFence::new(rails).with_offset(1).with_period_alternating(true);
+        assert_eq!(rail_fence.encode_bytes(text.as_bytes()), rail_fence.encode(text).into_bytes());
+    }
+}
+
+#[test]
+/// arbitrary binary data round-trips for rails 1 to 8
+fn test_bytes_round_trip() {
+    let data: Vec<u8> = (0..=255u8).rev().chain(0..=255).collect();
+    for rails in 1..=8 {
+        let rail_fence = RailFence::new(rails).with_turn_policy(TurnPolicy::Dwell);
+        for len in 0..data.len() {
+            let encoded = rail_fence.encode_bytes(&data[..len]);
+            assert_eq!(rail_fence.decode_bytes(&encoded), &data[..len]);
+        }
+    }
+}
+
+// prelude
+
+#[test]
+#[cfg(not(feature = "bytes-default"))]
+/// the prelude encodes characters by default
+fn test_prelude_defaults_to_chars() {
+    use rail_fence_cipher::prelude;
+    let rail_fence = RailFence::new(3);
+    let encoded: String = prelude::encode(&rail_fence, "古池蛙飛び込む水の音");
+    assert_eq!(encoded, "古びの池飛込水音蛙む");
+    assert_eq!(prelude::decode(&rail_fence, "TEITELHDVLSNHDTISEIIEA"), "THEDEVILISINTHEDETAILS");
+}
+
+#[test]
+#[cfg(feature = "bytes-default")]
+/// the prelude encodes bytes when bytes-default is enabled
+fn test_prelude_bytes_default() {
+    use rail_fence_cipher::prelude;
+    let rail_fence = RailFence::new(3);
+    let data = [0u8, 255, 1, 254, 2, 253, 3];
+    let encoded: Vec<u8> = prelude::encode(&rail_fence, &data);
+    assert_eq!(encoded, vec![0, 2, 255, 254, 253, 1, 3]);
+    assert_eq!(prelude::decode(&rail_fence, &encoded), data);
+}
+
+#[test]
+/// the char methods are available regardless of the prelude mode
+fn test_char_methods_unaffected_by_prelude() {
+    let rail_fence = RailFence::new(3);
+    assert_eq!(rail_fence.encode("古池蛙飛び込む水の音"), "古びの池飛込水音蛙む");
+}
+
+// diagrams
+
+#[test]
+/// the diagram matches the layout in the README
+fn test_diagram_three_rails() {
+    let expected = "\
+W - - - E - - - C - - - R - - - L - - - T - - - E
+- E - R - D - S - O - E - E - F - E - A - O - C -
+- - A - - - I - - - V - - - D - - - E - - - N - -";
+    assert_eq!(RailFence::new(3).diagram("WEAREDISCOVEREDFLEEATONCE"), expected);
+}
+
+#[test]
+/// reading the grid rail by rail gives the cipher-text
+fn test_grid_reads_as_encode() {
+    let rail_fence = RailFence::new(4);
+    let read: String = rail_fence.grid("EXERCISES").into_iter().flatten().flatten().collect();
+    assert_eq!(read, rail_fence.encode("EXERCISES"));
+}
+
+#[test]
+/// the trace has one frame per character and ends with the diagram
+fn test_trace_frames() {
+    let rail_fence = RailFence::new(3);
+    let steps = rail_fence.trace("EXERCISES");
+    assert_eq!(steps.len(), 9);
+    assert_eq!(steps[0], "E - - - - - - - -\n- - - - - - - - -\n- - - - - - - - -");
+    assert_eq!(steps.last().unwrap(), &rail_fence.diagram("EXERCISES"));
+    assert!(rail_fence.trace("").is_empty());
+}
+
+#[test]
+/// hand-built rails of the right shape decode
+fn test_decode_from_rails_valid_reconstruction() {
+    let rail_fence = RailFence::new(4);
+    let rails = vec![String::from("ES"), String::from("XIE"), String::from("ECS"), String::from("R")];
+    assert_eq!(rail_fence.decode_from_rails(&rails).unwrap(), "EXERCISES");
+}
+
+#[test]
+/// rails that no encode could have produced are rejected
+fn test_decode_from_rails_inconsistent_lengths() {
+    let rail_fence = RailFence::new(4);
+    assert_eq!(
+        rail_fence.decode_from_rails(&["E", "SXIE", "ECS", "R"]),
+        Err(RailFenceError::RailLengthMismatch { rail: 0, expected: 2, found: 1 })
+    );
+    assert_eq!(
+        rail_fence.decode_from_rails(&["ES", "XIE", "EC", "SR"]),
+        Err(RailFenceError::RailLengthMismatch { rail: 2, expected: 3, found: 2 })
+    );
+}
+
+#[test]
+/// encode_with_grid agrees with encode and grid
+fn test_encode_with_grid() {
+    let fences = [
+        RailFence::new(4),
+        RailFence::new(3).with_read_order(ReadOrder::ColumnMajor),
+        RailFence::new(3).with_period_alternating(true),
+    ];
+    for rail_fence in &fences {
+        for text in &["", "WEAREDISCOVEREDFLEEATONCE", "古池蛙飛び込む水の音"] {
+            let (encoded, grid) = rail_fence.encode_with_grid(text);
+            assert_eq!(encoded, rail_fence.encode(text));
+            assert_eq!(grid, rail_fence.grid(text));
+        }
+    }
+}
+
+#[test]
+/// encode_with_grid lays out the text that encode places on the fence
+fn test_encode_with_grid_configured() {
+    let rail_fence = RailFence::with_rail_order(&[2, 0, 1])
+        .with_case(Case::Upper)
+        .with_char_filter(CharFilter::Strip)
+        .with_period_padding(Some('X'))
+        .with_grouping(Some(4))
+        .with_rounds(2);
+    let (encoded, grid) = rail_fence.encode_with_grid("Rust is great!");
+    assert_eq!(encoded, rail_fence.encode("Rust is great!"));
+    assert_eq!(grid, rail_fence.grid("RUSTISGREATX"));
+}
+
+// thread safety
+
+/// RailFence must stay shareable across threads, e.g. in a parallel map.
+/// This fails to compile rather than failing at run time.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<RailFence>();
+    assert_send_sync::<FenceInfo>();
+    assert_send_sync::<RailFenceError>();
+};
+
+#[test]
+/// a shared fence encodes from several threads
+fn test_encode_across_threads() {
+    let rail_fence = std::sync::Arc::new(RailFence::new(3));
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let rail_fence = rail_fence.clone();
+            std::thread::spawn(move || rail_fence.encode("WEAREDISCOVEREDFLEEATONCE"))
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), "WECRLTEERDSOEEFEAOCAIVDEN");
+    }
+}
+
+// batches
+
+#[test]
+/// batch encoding matches encoding each message serially
+fn test_encode_batch_matches_encode() {
+    let rail_fence = RailFence::new(5);
+    let texts: Vec<String> = (0..200).map(|i| "WEAREDISCOVERED".repeat(i % 7) + &i.to_string()).collect();
+    let serial: Vec<String> = texts.iter().map(|text| rail_fence.encode(text)).collect();
+    assert_eq!(rail_fence.encode_batch(&texts), serial);
+    assert!(rail_fence.encode_batch(&[]).is_empty());
+}
+
+#[test]
+/// records are transposed together and split back exactly
+fn test_records_round_trip() {
+    let rail_fence = RailFence::new(3);
+    let records: Vec<String> = ["id,name", "", "1,Zoë", "2,古池", "3,Bob\n"].iter().map(|r| r.to_string()).collect();
+    let encoded = rail_fence.encode_records(&records);
+    assert_eq!(encoded.len(), records.len());
+    assert_eq!(encoded.concat(), rail_fence.encode(records.concat()));
+    assert_ne!(encoded, rail_fence.encode_batch(&records));
+    assert_eq!(rail_fence.decode_records(&encoded), records);
+    assert!(rail_fence.encode_records(&[]).is_empty());
+}
+
+#[test]
+/// multiplexed messages come back out separately
+fn test_multiplex_round_trip() {
+    let messages = ["WEARE", "DISCO", "VERED", "FLEE"];
+    for rails in 1..8 {
+        let rail_fence = RailFence::new(rails);
+        let cipher = rail_fence.encode_multiplex(&messages);
+        assert_eq!(cipher.len(), 19);
+        assert_eq!(rail_fence.decode_multiplex(&cipher, messages.len()), messages);
+    }
+    let rail_fence = RailFence::new(3);
+    let cipher = rail_fence.encode_multiplex(&["RUS", "TIS", "GR"]);
+    assert_eq!(rail_fence.decode(&cipher), "RTGUIRSS");
+    assert_eq!(rail_fence.encode_multiplex(&[]), "");
+    assert_eq!(rail_fence.decode_multiplex("", 2), vec!["", ""]);
+}
+
+#[test]
+#[should_panic]
+/// messages which could not be dealt back out are rejected
+fn test_multiplex_uneven_lengths() {
+    RailFence::new(3).encode_multiplex(&["RUST", "IS", "GREAT"]);
+}
+
+#[test]
+/// test vectors pair each sample with its cipher-text
+fn test_test_vectors() {
+    let rail_fence = RailFence::new(3);
+    let vectors = rail_fence.test_vectors(&["WEAREDISCOVEREDFLEEATONCE", "", "古池蛙飛び込む水の音"]);
+    assert_eq!(vectors.len(), 3);
+    assert_eq!(vectors[0], (String::from("WEAREDISCOVEREDFLEEATONCE"), String::from("WECRLTEERDSOEEFEAOCAIVDEN")));
+    for (clear_text, cipher_text) in &vectors {
+        assert_eq!(rail_fence.decode(cipher_text), *clear_text);
+    }
+}
+
+// schedules
+
+#[test]
+/// a schedule matching the zig-zag gives the standard cipher
+fn test_with_schedule_zig_zag() {
+    let zig_zag = |position: usize| match position % 4 {
+        3 => 1,
+        phase => phase as u32,
+    };
+    let rail_fence = RailFence::with_schedule(3, zig_zag);
+    assert_eq!(rail_fence.encode("WEAREDISCOVEREDFLEEATONCE"), "WECRLTEERDSOEEFEAOCAIVDEN");
+    assert_eq!(rail_fence.decode("WECRLTEERDSOEEFEAOCAIVDEN"), "WEAREDISCOVEREDFLEEATONCE");
+}
+
+#[test]
+/// an irregular schedule round-trips
+fn test_with_schedule_round_trip() {
+    let rail_fence = RailFence::with_schedule(4, |position| (position * position % 7 % 4) as u32);
+    let text = "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG";
+    let encoded = rail_fence.encode(text);
+    assert_ne!(encoded, text);
+    assert_eq!(rail_fence.decode(&encoded), text);
+    assert_eq!(rail_fence.rail_lengths(text.len()).iter().sum::<usize>(), text.len());
+}
+
+#[test]
+#[should_panic(expected = "a rail fence needs at least one rail")]
+/// encoding on a fence with no rails fails up front with a clear message
+fn test_encode_zero_rails() {
+    RailFence::new(0).encode("RUST");
+}
+
+#[test]
+#[should_panic(expected = "a rail fence needs at least one rail")]
+/// decoding on a fence with no rails fails the same way
+fn test_decode_zero_rails() {
+    RailFence::new(0).decode("RUST");
+}
+
+#[test]
+#[should_panic(expected = "rail 3 of a 3-rail fence")]
+/// a schedule may not use rails the fence does not have
+fn test_with_schedule_out_of_range() {
+    RailFence::with_schedule(3, |position| position as u32).encode("RUST");
+}
+
+// ascii fast path
+
+#[test]
+/// ASCII and non-ASCII text are transposed the same way
+fn test_ascii_matches_char_path() {
+    let rail_fence = RailFence::new(4);
+    let ascii = "EXERCISMISAWESOME";
+    let wide: String = ascii.chars().map(|c| char::from_u32(c as u32 + 0xFEE0).unwrap()).collect();
+    let encoded: String = rail_fence.encode(&wide).chars().map(|c| char::from_u32(c as u32 - 0xFEE0).unwrap()).collect();
+    assert_eq!(rail_fence.encode(ascii), encoded);
+    assert_eq!(rail_fence.decode(rail_fence.encode(&wide)), wide);
+}
+
+// output lengths
+
+#[test]
+/// encoded_len is the length of the cipher-text
+fn test_encoded_len_matches_encode() {
+    let rail_fence = RailFence::new(4);
+    for text in &["", "RUSTISGREAT", "古池蛙飛び込む水の音"] {
+        assert_eq!(rail_fence.encoded_len(text), rail_fence.encode(text).chars().count());
+    }
+}
+
+#[test]
+/// encoded_len counts what normalizing, padding and grouping do to the text
+fn test_encoded_len_with_options() {
+    let fences = vec![
+        RailFence::new(3).with_whitespace_stripped(true),
+        RailFence::new(3).with_char_filter(CharFilter::Strip),
+        RailFence::new(3).with_char_filter(CharFilter::PreservePositions).with_period_padding(Some('X')),
+        RailFence::new(4).with_period_padding(Some('X')),
+        RailFence::new(4).with_grouping(Some(5)),
+        RailFence::new(5).with_whitespace_stripped(true).with_period_padding(Some('Q')).with_grouping(Some(3)),
+        RailFence::new(4).with_case(Case::Upper).with_char_filter(CharFilter::Strip).with_period_padding(Some('X')).with_grouping(Some(4)),
+    ];
+    for rail_fence in &fences {
+        for text in &["", "A", "RUST IS GREAT", "We are discovered, flee at once!", "古池 蛙飛び込む 水の音"] {
+            assert_eq!(rail_fence.encoded_len(text), rail_fence.encode(text).chars().count(), "{:?} on {:?}", text, rail_fence);
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "rand")]
+/// encoded_padded_len is the length of the padded cipher-text
+fn test_encoded_padded_len_matches_encode_padded_to() {
+    use rand::SeedableRng;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+    let rail_fence = RailFence::new(4);
+    for target_len in [0, 5, 12, 13, 30] {
+        let encoded = rail_fence.encode_padded_to("RUSTISGREAT", target_len, &mut rng);
+        assert_eq!(encoded.chars().count(), rail_fence.encoded_padded_len("RUSTISGREAT", target_len));
+    }
+}
+
+#[test]
+#[cfg(feature = "rand")]
+/// encoded_padded_len follows encode_padded_to on grouped and filtered fences
+fn test_encoded_padded_len_with_options() {
+    use rand::SeedableRng;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+    let fences = vec![
+        RailFence::new(4).with_grouping(Some(5)),
+        RailFence::new(3).with_char_filter(CharFilter::Strip).with_grouping(Some(4)),
+        RailFence::new(4).with_period_padding(Some('X')).with_grouping(Some(3)),
+    ];
+    for rail_fence in &fences {
+        for target_len in [0, 5, 16, 20, 30] {
+            let encoded = rail_fence.encode_padded_to("RUST, IS GREAT", target_len, &mut rng);
+            assert_eq!(encoded.chars().count(), rail_fence.encoded_padded_len("RUST, IS GREAT", target_len), "{:?}", rail_fence);
+        }
+    }
+}
+
+// turn policies
+
+#[test]
+/// dwelling at the turns places two characters on each end rail
+fn test_dwell_placement() {
+    let rail_fence = RailFence::new(3).with_turn_policy(TurnPolicy::Dwell);
+    assert_eq!(rail_fence.period(), 6);
+    assert_eq!(rail_fence.encode_rails("ABCDEFGHIJ"), vec!["ABGH", "CFI", "DEJ"]);
+}
+
+#[test]
+/// dwell round-trips and differs from bounce
+fn test_dwell_round_trip() {
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    for rails in 1..8 {
+        let dwell = RailFence::new(rails).with_turn_policy(TurnPolicy::Dwell);
+        let encoded = dwell.encode(text);
+        assert_eq!(dwell.decode(&encoded), text);
+        assert_eq!(dwell.rail_lengths(text.len()), dwell.encode_rails(text).iter().map(String::len).collect::<Vec<_>>());
+        if rails > 1 {
+            assert_ne!(encoded, RailFence::new(rails).encode(text));
+        }
+    }
+}
+
+// aligned rails
+
+#[test]
+/// every rail of an aligned encoding has the same length
+fn test_encode_aligned_equal_rails() {
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    for rails in 1..7 {
+        let rail_fence = RailFence::new(rails);
+        let encoded: Vec<char> = rail_fence.encode_aligned(text, '*').chars().collect();
+        assert_eq!(encoded.len(), rails as usize * text.len());
+        for rail in encoded.chunks(text.len()) {
+            assert_eq!(rail.len(), text.len());
+        }
+        let without_fill: String = encoded.iter().filter(|&&c| c != '*').collect();
+        assert_eq!(without_fill, rail_fence.encode(text));
+    }
+}
+
+#[test]
+/// decoding ignores the fill cells even if the message contains the fill
+fn test_decode_aligned_round_trip() {
+    let rail_fence = RailFence::new(4);
+    let text = "X MARKS THE SPOT, XXX";
+    let encoded = rail_fence.encode_aligned(text, 'X');
+    assert_eq!(rail_fence.decode_aligned(&encoded).unwrap(), text);
+    assert_eq!(
+        rail_fence.decode_aligned("XXXXX"),
+        Err(RailFenceError::UnalignedLength { length: 5, rails: 4 })
+    );
+}
+
+// frames
+
+#[test]
+/// a frame decodes without knowing the rail count in advance
+fn test_framed_round_trip() {
+    let receiver = RailFence::new(1);
+    for rails in 1..8 {
+        let frame = RailFence::new(rails).encode_framed("WE ARE DISCOVERED: FLEE AT ONCE");
+        assert_eq!(receiver.decode_framed(&frame).unwrap(), "WE ARE DISCOVERED: FLEE AT ONCE");
+    }
+    assert_eq!(receiver.decode_framed("3:0:").unwrap(), "");
+}
+
+#[test]
+/// truncated or non-numeric headers are bad headers
+fn test_decode_framed_bad_header() {
+    let receiver = RailFence::new(3);
+    for frame in &["", "4", "4:", "4:11", "x:11:RGUSRSIETTA", "4:-1:", "+4:11:RGUSRSIETTA", ":11:RGUSRSIETTA", "99999999999:1:A"] {
+        assert_eq!(receiver.decode_framed(frame), Err(RailFenceError::BadHeader), "{}", frame);
+    }
+}
+
+#[test]
+/// a header with a huge rail count costs no more than the message it frames
+fn test_decode_framed_huge_rail_count() {
+    let receiver = RailFence::new(3);
+    assert_eq!(receiver.decode_framed("4294967295:11:RUSTISGREAT").unwrap(), "RUSTISGREAT");
+    assert_eq!(receiver.decode_framed("30000000:0:").unwrap(), "");
+    let upward = RailFence::new(u32::MAX).with_upward_start(true);
+    assert_eq!(upward.encode("RUST"), "TSUR");
+    assert_eq!(upward.decode_lossy("4294967295:4:TSU"), format!("{}UST", MISSING_CHAR));
+}
+
+#[test]
+/// more rails than characters still reads the rails in order
+fn test_more_rails_than_characters() {
+    let text = "WEAREDISCOVERED";
+    let fences = vec![
+        RailFence::new(20),
+        RailFence::new(20).with_offset(7),
+        RailFence::new(20).with_upward_start(true).with_offset(3),
+        RailFence::new(20).with_turn_policy(TurnPolicy::Dwell).with_offset(30),
+        RailFence::new(20).with_wave(Wave::Sawtooth).with_offset(12),
+    ];
+    for rail_fence in &fences {
+        for len in 0..=text.len() {
+            let cipher = rail_fence.encode(&text[..len]);
+            assert_eq!(cipher, rail_fence.encode_rails(&text[..len]).concat(), "{:?}", rail_fence);
+            assert_eq!(rail_fence.decode(&cipher), &text[..len], "{:?}", rail_fence);
+        }
+    }
+}
+
+#[test]
+/// a header with zero rails is rejected
+fn test_decode_framed_zero_rails() {
+    assert_eq!(RailFence::new(3).decode_framed("0:4:RUST"), Err(RailFenceError::ZeroRails));
+}
+
+#[test]
+/// cipher-text that is too short or too long for its header is rejected
+fn test_decode_framed_length_mismatch() {
+    let receiver = RailFence::new(3);
+    assert_eq!(
+        receiver.decode_framed("4:11:RGUSR"),
+        Err(RailFenceError::LengthMismatch { expected: 11, found: 5 })
+    );
+    assert_eq!(
+        receiver.decode_framed("4:2:RGUSR"),
+        Err(RailFenceError::LengthMismatch { expected: 2, found: 5 })
+    );
+}
+
+#[test]
+/// a frame missing its last character loses only that character
+fn test_decode_lossy_missing_last_character() {
+    let receiver = RailFence::new(1);
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    for rails in 1..8 {
+        let mut frame = RailFence::new(rails).encode_framed(text);
+        frame.pop();
+        let decoded = receiver.decode_lossy(&frame);
+        let lost: Vec<usize> = decoded.chars().enumerate().filter(|&(_, c)| c == MISSING_CHAR).map(|(i, _)| i).collect();
+        assert_eq!(lost.len(), 1);
+        let expected: String = text.chars().enumerate().map(|(i, c)| if i == lost[0] { MISSING_CHAR } else { c }).collect();
+        assert_eq!(decoded, expected);
+    }
+}
+
+#[test]
+/// extra characters are dropped and unreadable headers fall back to decode
+fn test_decode_lossy_extra_or_unframed() {
+    let rail_fence = RailFence::new(4);
+    assert_eq!(rail_fence.decode_lossy("4:11:RGUSRSIETTA??"), "RUSTISGREAT");
+    assert_eq!(rail_fence.decode_lossy("4:11:RGUSRSIETTA"), "RUSTISGREAT");
+    assert_eq!(rail_fence.decode_lossy("RGUSRSIETTA"), "RUSTISGREAT");
+}
+
+#[test]
+/// a header claiming far more characters than are present is not trusted
+fn test_decode_lossy_huge_length() {
+    let rail_fence = RailFence::new(3);
+    assert_eq!(rail_fence.decode_lossy("4:3000000000:AB"), "AB");
+    assert_eq!(rail_fence.decode_lossy("4:18446744073709551615:RGUSRSIETTA"), RailFence::new(4).decode("RGUSRSIETTA"));
+    assert_eq!(rail_fence.decode_lossy("2:1:"), "");
+    // Losing half of the message is still repaired
+    assert_eq!(rail_fence.decode_lossy("1:4:AB"), format!("AB{}{}", MISSING_CHAR, MISSING_CHAR));
+}
+
+#[test]
+/// could_be_framed agrees with decode_framed
+fn test_could_be_framed() {
+    let rail_fence = RailFence::new(2);
+    for frame in &["4:11:RGUSRSIETTA", "3:0:", "0:1:A", "4:11:RGUSRSIETT", "4:x:AB", ":1:A", "99999999999:1:A", ""] {
+        assert_eq!(rail_fence.could_be_framed(frame), rail_fence.decode_framed(frame).is_ok(), "{}", frame);
+    }
+}
+
+#[test]
+/// any string is a plain cipher-text for a working fence
+fn test_could_be_ciphertext() {
+    assert!(RailFence::new(3).could_be_ciphertext(""));
+    assert!(RailFence::new(3).could_be_ciphertext("古池蛙飛び込む水の音"));
+    assert!(!RailFence::new(0).could_be_ciphertext("ABC"));
+    let schedule = RailFence::with_schedule(2, |position| position as u32);
+    assert!(schedule.could_be_ciphertext("AB"));
+    assert!(!schedule.could_be_ciphertext("ABC"));
+}
+
+// wrappers on configured fences
+
+/// Fences with each of the options that change the length or layout of the
+/// cipher-text
+fn configured_fences() -> Vec<RailFence> {
+    vec![
+        RailFence::new(4).with_period_padding(Some('X')),
+        RailFence::new(4).with_grouping(Some(5)),
+        RailFence::new(4).with_char_filter(CharFilter::Strip),
+        RailFence::new(4).with_char_filter(CharFilter::PreservePositions),
+        RailFence::new(3).with_case(Case::Upper).with_period_padding(Some('Q')).with_grouping(Some(3)),
+        RailFence::new(3).with_whitespace_stripped(true),
+    ]
+}
+
+#[test]
+/// records keep their lengths and decode on every configured fence
+fn test_records_with_options() {
+    let records = vec![String::from("RUST"), String::from("ISGREAT")];
+    for rail_fence in configured_fences() {
+        let encoded = rail_fence.encode_records(&records);
+        assert_eq!(vec![4, 7], encoded.iter().map(|record| record.chars().count()).collect::<Vec<_>>(), "{:?}", rail_fence);
+        assert_eq!(rail_fence.decode_records(&encoded), records, "{:?}", rail_fence);
+    }
+}
+
+#[test]
+/// numbers are all digits, as many as the number has, and decode on every
+/// configured fence
+fn test_numbers_with_options() {
+    for rail_fence in configured_fences() {
+        let encoded = rail_fence.encode_number(9876543210);
+        assert_eq!(10, encoded.len(), "{:?}", rail_fence);
+        assert!(encoded.bytes().all(|b| b.is_ascii_digit()), "{:?}", rail_fence);
+        assert_ne!("9876543210", encoded, "{:?}", rail_fence);
+        assert_eq!(rail_fence.decode_number(&encoded), Ok(9876543210), "{:?}", rail_fence);
+    }
+}
+
+#[test]
+/// ranges keep their lengths and decode on every configured fence
+fn test_ranges_with_options() {
+    for rail_fence in configured_fences() {
+        let encoded = rail_fence.encode_range("id=RUSTISGREAT;", 3..14);
+        assert_eq!(15, encoded.chars().count(), "{:?}", rail_fence);
+        assert!(encoded.starts_with("id=") && encoded.ends_with(';'), "{:?}", rail_fence);
+        assert_eq!(rail_fence.decode_range(&encoded, 3..14), "id=RUSTISGREAT;", "{:?}", rail_fence);
+    }
+}
+
+#[test]
+/// frames state their own length and decode on every configured fence
+fn test_framed_with_options() {
+    for rail_fence in configured_fences() {
+        let frame = rail_fence.encode_framed("RustIsGreat");
+        assert_eq!(rail_fence.decode_framed(&frame).unwrap(), rail_fence.normalize("RustIsGreat"), "{:?}", rail_fence);
+    }
+}
+
+#[test]
+/// the columnar transposition decodes on every configured fence
+fn test_2d_with_options() {
+    for rail_fence in configured_fences() {
+        for cols in 1..5 {
+            let encoded = rail_fence.encode_2d("Rust,IsGreat!", cols);
+            assert_eq!(rail_fence.decode_2d(&encoded, cols), rail_fence.normalize("Rust,IsGreat!"), "{:?}", rail_fence);
+        }
+    }
+    let stripped = RailFence::new(3).with_whitespace_stripped(true);
+    let encoded = stripped.encode_2d("RUST IS GREAT", 3);
+    assert_eq!(encoded, stripped.encode_2d("RUSTISGREAT", 3));
+    assert_eq!(stripped.decode_2d(&encoded, 3), "RUSTISGREAT");
+}
+
+#[test]
+/// power-of-two padding keeps its size and decodes on every configured fence
+fn test_pow2_with_options() {
+    for rail_fence in configured_fences() {
+        let encoded = rail_fence.encode_pow2("RUST IS GREAT", '.');
+        assert_eq!(16, encoded.chars().count(), "{:?}", rail_fence);
+        assert_eq!(rail_fence.decode_pow2(&encoded).unwrap(), rail_fence.normalize("RUST IS GREAT"), "{:?}", rail_fence);
+    }
+}
+
+#[test]
+#[cfg(feature = "rand")]
+/// random padding reaches exactly the target and decodes on every
+/// configured fence
+fn test_padded_to_with_options() {
+    use rand::SeedableRng;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+    for rail_fence in configured_fences() {
+        let encoded = rail_fence.encode_padded_to("RUST IS GREAT", 20, &mut rng);
+        assert_eq!(20, encoded.chars().count(), "{:?}", rail_fence);
+        assert_eq!(20, rail_fence.encoded_padded_len("RUST IS GREAT", 20), "{:?}", rail_fence);
+        assert_eq!(rail_fence.decode_padded(&encoded).unwrap(), rail_fence.normalize("RUST IS GREAT"), "{:?}", rail_fence);
+    }
+}
+
+// caesar
+
+#[test]
+/// shift 3 on a 4-rail fence round-trips
+fn test_caesar_round_trip() {
+    let rail_fence = RailFence::new(4);
+    let text = "Attack at dawn, Zebra-7!";
+    let encoded = rail_fence.encode_caesar(text, 3);
+    assert_eq!(encoded, rail_fence.encode("Dwwdfn dw gdzq, Cheud-7!"));
+    assert_eq!(rail_fence.decode_caesar(&encoded, 3), text);
+}
+
+#[test]
+/// shifts wrap around the alphabet and leave other characters alone
+fn test_caesar_shift_wraps() {
+    let rail_fence = RailFence::new(1);
+    assert_eq!(rail_fence.encode_caesar("xyz XYZ 123 é", 3), "abc ABC 123 é");
+    assert_eq!(rail_fence.encode_caesar("abc", 29), "def");
+    assert_eq!(rail_fence.decode_caesar("abc", 255), "fgh");
+    assert_eq!(rail_fence.encode_caesar("fgh", 255), "abc");
+}
+
+#[test]
+/// a letter that shifts onto the fill character is not stripped as padding
+fn test_caesar_period_padding() {
+    let rail_fence = RailFence::new(3).with_period_padding(Some('X'));
+    let encoded = rail_fence.encode_caesar("ATTACKU", 3);
+    assert_eq!(encoded, rail_fence.encode("DWWDFNXA"));
+    assert_eq!(rail_fence.decode_caesar(&encoded, 3), "ATTACKU");
+    for rail_fence in configured_fences() {
+        let encoded = rail_fence.encode_caesar("Rust,IsGreat!", 5);
+        assert_eq!(rail_fence.decode_caesar(&encoded, 5), rail_fence.normalize("Rust,IsGreat!"), "{:?}", rail_fence);
+    }
+}
+
+#[test]
+/// one line per rail decodes, with or without a final newline
+fn test_decode_multiline() {
+    let rail_fence = RailFence::new(3);
+    let lines = rail_fence.encode_rails("WEAREDISCOVEREDFLEEATONCE").join("\n");
+    assert_eq!(rail_fence.decode_multiline(&lines).unwrap(), "WEAREDISCOVEREDFLEEATONCE");
+    assert_eq!(rail_fence.decode_multiline(&(lines.clone() + "\n")).unwrap(), "WEAREDISCOVEREDFLEEATONCE");
+    assert_eq!(rail_fence.decode_multiline(&lines.replace('\n', "\r\n")).unwrap(), "WEAREDISCOVEREDFLEEATONCE");
+    assert_eq!(RailFence::new(5).decode_multiline("H\nI\n\n\n").unwrap(), "HI");
+}
+
+#[test]
+/// the wrong number of lines is an error
+fn test_decode_multiline_wrong_line_count() {
+    let rail_fence = RailFence::new(4);
+    assert_eq!(
+        rail_fence.decode_multiline("RG\nUSR\nSIETTA"),
+        Err(RailFenceError::RailCountMismatch { expected: 4, found: 3 })
+    );
+    assert_eq!(
+        rail_fence.decode_multiline("RG\nUSR\nSIET\nT\nA"),
+        Err(RailFenceError::RailCountMismatch { expected: 4, found: 5 })
+    );
+}
+
+// permutations
+
+#[test]
+/// the permutation says where encode moves each character
+fn test_permutation_matches_encode() {
+    let text: Vec<char> = "WEAREDISCOVEREDFLEEATONCE".chars().collect();
+    for rails in 1..8 {
+        let rail_fence = RailFence::new(rails);
+        let encoded: Vec<char> = rail_fence.encode(text.iter().collect::<String>()).chars().collect();
+        for (from, to) in rail_fence.permutation(text.len()).into_iter().enumerate() {
+            assert_eq!(encoded[to], text[from]);
+        }
+    }
+}
+
+#[test]
+/// fixed points are the characters left in place
+fn test_fixed_points() {
+    // "EXERCISES" moves to positions [0, 2, 5, 8, 6, 3, 1, 4, 7]
+    assert_eq!(RailFence::new(4).fixed_points(9), vec![0]);
+    assert_eq!(RailFence::new(3).fixed_points(25), vec![0, 13]);
+    assert_eq!(RailFence::new(1).fixed_points(5), vec![0, 1, 2, 3, 4]);
+    assert_eq!(RailFence::new(9).fixed_points(5), vec![0, 1, 2, 3, 4]);
+    assert!(RailFence::new(3).fixed_points(0).is_empty());
+}
+
+#[test]
+/// inverse_permutation undoes permutation for every fence option
+fn test_inverse_permutation() {
+    for rails in 1..7 {
+        let fences = [
+            RailFence::new(rails),
+            RailFence::new(rails).with_turn_policy(TurnPolicy::Dwell).with_offset(1),
+            RailFence::new(rails).with_period_alternating(true),
+            RailFence::new(rails).with_read_order(ReadOrder::ColumnMajor),
+        ];
+        for rail_fence in &fences {
+            for len in 0..30 {
+                let permutation = rail_fence.permutation(len);
+                let inverse = rail_fence.inverse_permutation(len);
+                for i in 0..len {
+                    assert_eq!(permutation[inverse[i]], i);
+                }
+            }
+        }
+    }
+}
+
+// directions
+
+#[test]
+/// the two directions are encode and decode
+fn test_transform_directions() {
+    let rail_fence = RailFence::new(3);
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    assert_eq!(rail_fence.transform(text, Direction::Forward), rail_fence.encode(text));
+    assert_eq!(rail_fence.transform(text, Direction::Inverse), rail_fence.decode(text));
+}
+
+#[test]
+/// either direction is undone by its reverse
+fn test_transform_round_trip_both_orders() {
+    let text = "THEDEVILISINTHEDETAILS";
+    for rails in 1..8 {
+        let rail_fence = RailFence::new(rails);
+        for &dir in &[Direction::Forward, Direction::Inverse] {
+            let transformed = rail_fence.transform(text, dir);
+            assert_eq!(rail_fence.transform(&transformed, dir.reverse()), text);
+        }
+    }
+}
+
+// digits
+
+#[test]
+/// digit-only and mixed alphanumeric messages round-trip
+fn test_digits_round_trip() {
+    for text in &["1234567890", "A1B2C3", "１２３４５６７８９０", "Ａ1Ｂ2Ｃ3", "٠١٢٣٤٥٦٧٨٩"] {
+        for rails in 2..=5 {
+            let rail_fence = RailFence::new(rails);
+            let encoded = rail_fence.encode(text);
+            assert_eq!(rail_fence.decode(&encoded), *text, "{} on {} rails", text, rails);
+        }
+    }
+}
+
+#[test]
+/// digits are transposed like any other character
+fn test_digits_encode() {
+    process_encode_case("1234567890", 3, "1592468037");
+    process_encode_case("A1B2C3", 2, "ABC123");
+    process_encode_case("１２３４５６７８９０", 3, "１５９２４６８０３７");
+}
+
+#[test]
+/// numbers round-trip through their transposed digits
+fn test_number_round_trip() {
+    let samples = [0, 1, 9, 10, 42, 1_000_000, 123_456_789, u64::MAX / 3, u64::MAX - 1, u64::MAX];
+    for rails in 1..8 {
+        let rail_fence = RailFence::new(rails);
+        for &n in &samples {
+            let n = n ^ (rails as u64);
+            assert_eq!(rail_fence.decode_number(&rail_fence.encode_number(n)), Ok(n));
+        }
+    }
+}
+
+#[test]
+/// non-digits and overflowing numbers are rejected
+fn test_decode_number_invalid() {
+    let rail_fence = RailFence::new(3);
+    assert_eq!(rail_fence.decode_number(""), Err(RailFenceError::InvalidNumber));
+    assert_eq!(rail_fence.decode_number("12a"), Err(RailFenceError::InvalidNumber));
+    assert_eq!(rail_fence.decode_number("１２３"), Err(RailFenceError::InvalidNumber));
+    assert_eq!(rail_fence.decode_number("99999999999999999999"), Err(RailFenceError::InvalidNumber));
+}
+
+// blocks
+
+#[test]
+/// blocks are whole periods near the target size
+fn test_block_boundaries() {
+    let rail_fence = RailFence::new(4);
+    assert_eq!(rail_fence.block_boundaries(20, 12), vec![0, 12, 20]);
+    assert_eq!(rail_fence.block_boundaries(20, 14), vec![0, 12, 20]);
+    assert_eq!(rail_fence.block_boundaries(20, 1), vec![0, 6, 12, 18, 20]);
+    assert_eq!(rail_fence.block_boundaries(18, 100), vec![0, 18]);
+    assert_eq!(rail_fence.block_boundaries(0, 6), vec![0]);
+}
+
+#[test]
+/// blocks encoded on their own merge rail by rail into the full encode
+fn test_block_rails_merge_to_full_encode() {
+    let text: Vec<char> = "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG".chars().collect();
+    for rails in 1..7 {
+        let rail_fence = RailFence::new(rails);
+        for target in 1..12 {
+            let mut merged = vec![String::new(); rails as usize];
+            for block in rail_fence.block_boundaries(text.len(), target).windows(2) {
+                let block: String = text[block[0]..block[1]].iter().collect();
+                for (rail, part) in merged.iter_mut().zip(rail_fence.encode_rails(&block)) {
+                    rail.push_str(&part);
+                }
+            }
+            assert_eq!(merged, rail_fence.encode_rails(&text.iter().collect::<String>()));
+        }
+    }
+}
+
+#[test]
+/// encoding a long message in parallel blocks matches encode on every kind of fence
+fn test_encode_parallel_matches_encode() {
+    let text: String = "We are discovered, flee at once! 古池".chars().cycle().take(300_000).collect();
+    let fences = [
+        RailFence::new(1),
+        RailFence::new(7),
+        RailFence::new(5).with_turn_policy(TurnPolicy::Dwell).with_offset(3),
+        RailFence::new(6).with_wave(Wave::Sawtooth).with_upward_start(true),
+        RailFence::with_rail_order(&[2, 0, 3, 1]),
+        RailFence::new(4).with_case(Case::Upper).with_char_filter(CharFilter::Strip).with_period_padding(Some(DEFAULT_FILL)),
+        RailFence::new(4).with_period_alternating(true),
+        RailFence::new(3).with_char_filter(CharFilter::PreservePositions),
+        RailFence::with_schedule(3, |position| (position % 3) as u32),
+    ];
+    for rail_fence in &fences {
+        assert_eq!(rail_fence.encode_parallel(&text), rail_fence.encode(&text), "{:?}", rail_fence);
+        assert_eq!(rail_fence.encode_parallel("RUSTISGREAT"), rail_fence.encode("RUSTISGREAT"), "{:?}", rail_fence);
+    }
+}
+
+// transcoding
+
+#[test]
+/// transcoding there and back recovers the original cipher-text
+fn test_transcode_round_trip() {
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    for from in 1..6 {
+        for to in 1..6 {
+            let (from, to) = (RailFence::new(from), RailFence::new(to));
+            let cipher = from.encode(text);
+            let transcoded = from.transcode(&cipher, &to);
+            assert_eq!(transcoded, to.encode(text));
+            assert_eq!(to.transcode(&transcoded, &from), cipher);
+        }
+    }
+}
+
+// read orders
+
+#[test]
+/// reading column by column gives back the clear-text
+fn test_column_major_is_identity() {
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    for rails in 1..6 {
+        let rail_fence = RailFence::new(rails).with_read_order(ReadOrder::ColumnMajor);
+        assert_eq!(rail_fence.encode(text), text);
+        assert_eq!(rail_fence.decode(text), text);
+        assert_eq!(rail_fence.decode(rail_fence.encode("古池蛙飛び込む水の音")), "古池蛙飛び込む水の音");
+    }
+}
+
+#[test]
+/// rail-major is the default read order
+fn test_rail_major_is_default() {
+    let rail_fence = RailFence::new(3).with_read_order(ReadOrder::RailMajor);
+    assert_eq!(rail_fence.encode("WEAREDISCOVEREDFLEEATONCE"), RailFence::new(3).encode("WEAREDISCOVEREDFLEEATONCE"));
+    assert_eq!(ReadOrder::default(), ReadOrder::RailMajor);
+}
+
+// letters only
+
+#[test]
+/// the Wikipedia example decodes from its published grouped form
+fn test_decode_letters_only_published_challenge() {
+    // https://en.wikipedia.org/wiki/Rail_fence_cipher
+    let rail_fence = RailFence::new(3);
+    assert_eq!(rail_fence.decode_letters_only("WECRL TEERD SOEEF EAOCA IVDEN"), "WEAREDISCOVEREDFLEEATONCE");
+    assert_eq!(rail_fence.decode_letters_only("wecrlte\nerdsoeefeaoc\naivden\n"), "WEAREDISCOVEREDFLEEATONCE");
+}
+
+#[test]
+/// punctuation and digits in the cipher-text are dropped
+fn test_decode_letters_only_drops_non_letters() {
+    let rail_fence = RailFence::new(4);
+    assert_eq!(rail_fence.decode_letters_only("R-G-U-S-R 5 S-I-E-T-T-A."), "RUSTISGREAT");
+}
+
+#[test]
+/// non-letters stay in place while the letters are transposed
+fn test_skeleton_keeps_punctuation() {
+    let rail_fence = RailFence::new(2);
+    assert_eq!(rail_fence.encode_skeleton("A-B-C-D-E"), "A-C-E-B-D");
+    let rail_fence = RailFence::new(3);
+    for text in &["", "---", "2024-06-01: WE ARE DISCOVERED!", "(a,b),[c;d]"] {
+        let encoded = rail_fence.encode_skeleton(text);
+        let skeleton = |s: &str| s.chars().map(|c| if c.is_alphabetic() { '_' } else { c }).collect::<String>();
+        assert_eq!(skeleton(&encoded), skeleton(text));
+        assert_eq!(rail_fence.decode_skeleton(&encoded), *text);
+    }
+}
+
+#[test]
+/// mixed-case messages come back in exactly their original case
+fn test_case_marked_round_trip() {
+    let rail_fence = RailFence::new(4);
+    for text in &["WeAreDiscovered", "rust IS great", "Straße ǅemal İstanbul", ""] {
+        let marked = rail_fence.encode_case_marked(text);
+        assert_eq!(marked.uppercase.len(), text.chars().count());
+        assert_eq!(rail_fence.decode_case_marked(&marked).unwrap(), *text);
+    }
+    let marked = rail_fence.encode_case_marked("rust IS great");
+    assert_eq!(marked.cipher, rail_fence.encode("RUST IS GREAT"));
+}
+
+#[test]
+/// fences that strip or pad the message mark the case of what they encode
+fn test_case_marked_with_options() {
+    let stripped = RailFence::new(3).with_whitespace_stripped(true);
+    let marked = stripped.encode_case_marked("Rust is Great");
+    assert_eq!(marked.cipher, stripped.encode("RUSTISGREAT"));
+    assert_eq!(marked.uppercase.len(), 11);
+    assert_eq!(stripped.decode_case_marked(&marked).unwrap(), "RustisGreat");
+    for rail_fence in configured_fences() {
+        let marked = rail_fence.encode_case_marked("Rust,IsGreat!");
+        assert_eq!(rail_fence.decode_case_marked(&marked).unwrap(), rail_fence.normalize("Rust,IsGreat!"), "{:?}", rail_fence);
+    }
+}
+
+#[test]
+/// a case mask of the wrong length is rejected
+fn test_case_marked_length_mismatch() {
+    let rail_fence = RailFence::new(3);
+    let mut marked = rail_fence.encode_case_marked("Hello");
+    marked.uppercase.pop();
+    assert_eq!(
+        rail_fence.decode_case_marked(&marked),
+        Err(RailFenceError::LengthMismatch { expected: 4, found: 5 })
+    );
+}
+
+// fixed buffers
+
+#[test]
+/// an exact-size buffer receives the whole cipher-text
+fn test_encode_into_slice_exact_size() {
+    let rail_fence = RailFence::new(3);
+    let input = b"WEAREDISCOVEREDFLEEATONCE";
+    let mut out = [0u8; 25];
+    assert_eq!(rail_fence.encode_into_slice(input, &mut out), Ok(25));
+    assert_eq!(&out[..], rail_fence.encode("WEAREDISCOVEREDFLEEATONCE").as_bytes());
+    let mut larger = [b'#'; 30];
+    assert_eq!(rail_fence.encode_into_slice(input, &mut larger), Ok(25));
+    assert_eq!(&larger[25..], b"#####");
+}
+
+#[test]
+/// a buffer shorter than the input is rejected untouched
+fn test_encode_into_slice_too_small() {
+    let rail_fence = RailFence::new(3);
+    let mut out = [0u8; 24];
+    assert_eq!(
+        rail_fence.encode_into_slice(b"WEAREDISCOVEREDFLEEATONCE", &mut out),
+        Err(RailFenceError::BufferTooSmall { needed: 25, available: 24 })
+    );
+    assert_eq!(out, [0u8; 24]);
+}
+
+#[test]
+/// any capacity hint gives the same cipher-text as encode
+fn test_encode_with_capacity() {
+    let rail_fence = RailFence::new(3);
+    for text in &["", "WEAREDISCOVEREDFLEEATONCE", "古池蛙飛び込む水の音"] {
+        for cap in [0, 1, text.len(), 1000] {
+            let encoded = rail_fence.encode_with_capacity(text, cap);
+            assert_eq!(encoded, rail_fence.encode(text));
+            assert!(encoded.capacity() >= cap);
+        }
+    }
+}
+
+#[test]
+/// a capacity hint keeps every option of the fence
+fn test_encode_with_capacity_configured() {
+    let rail_fence = RailFence::with_rail_order(&[2, 0, 3, 1])
+        .with_offset(3)
+        .with_case(Case::Upper)
+        .with_char_filter(CharFilter::Strip)
+        .with_period_padding(Some('X'))
+        .with_grouping(Some(4))
+        .with_rounds(3);
+    let text = "We are discovered, flee at once!";
+    for cap in [0, 8, 1000] {
+        let encoded = rail_fence.encode_with_capacity(text, cap);
+        assert_eq!(encoded, rail_fence.encode(text));
+        assert!(encoded.capacity() >= cap);
+    }
+}
+
+// alternating periods
+
+#[test]
+/// alternating periods round-trip on multi-period messages
+fn test_period_alternating_round_trip() {
+    let text = "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG";
+    for rails in 1..8 {
+        let rail_fence = RailFence::new(rails).with_period_alternating(true);
+        let encoded = rail_fence.encode(text);
+        assert_eq!(rail_fence.decode(&encoded), text);
+        if rails > 2 {
+            assert_ne!(encoded, RailFence::new(rails).encode(text));
+        }
+    }
+}
+
+#[test]
+/// each period is read on its own, odd periods from the bottom up
+fn test_period_alternating_order() {
+    let rail_fence = RailFence::new(3).with_period_alternating(true);
+    // ABCD EFGH IJ, read as A BD C, G FH E, I J
+    assert_eq!(rail_fence.encode("ABCDEFGHIJ"), "ABDCGFHEIJ");
+    let mut out = [0u8; 10];
+    rail_fence.encode_into_slice(b"ABCDEFGHIJ", &mut out).unwrap();
+    assert_eq!(&out, b"ABDCGFHEIJ");
+}
+
+#[test]
+/// alternating periods read a keyed rail order, reversed on odd periods
+fn test_period_alternating_rail_order() {
+    let rail_fence = RailFence::with_rail_order(&[2, 0, 1]).with_period_alternating(true);
+    // ABCD EFGH IJ, read as C A BD, FH E G, I J
+    assert_eq!(rail_fence.encode("ABCDEFGHIJ"), "CABDFHEGIJ");
+    assert_ne!(rail_fence.encode("ABCDEFGHIJ"), RailFence::new(3).with_period_alternating(true).encode("ABCDEFGHIJ"));
+    assert_eq!(rail_fence.decode("CABDFHEGIJ"), "ABCDEFGHIJ");
+    let mut out = [0u8; 10];
+    rail_fence.encode_into_slice(b"ABCDEFGHIJ", &mut out).unwrap();
+    assert_eq!(&out, b"CABDFHEGIJ");
+    let mut in_place = String::from("ABCDEFGHIJ");
+    rail_fence.encode_in_place(&mut in_place);
+    assert_eq!(in_place, "CABDFHEGIJ");
+    rail_fence.decode_in_place(&mut in_place);
+    assert_eq!(in_place, "ABCDEFGHIJ");
+    let text = "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG";
+    let keyed = RailFence::with_rail_order(&[3, 1, 0, 4, 2]).with_period_alternating(true);
+    assert_eq!(keyed.decode(keyed.encode(text)), text);
+    let typed: String = text.chars().take(20).collect();
+    let (_, at) = keyed.append_char_position(20, 'S');
+    let mut shown: Vec<char> = keyed.encode(&typed).chars().collect();
+    shown.insert(at, 'S');
+    assert_eq!(shown.into_iter().collect::<String>(), keyed.encode(&text[..21]));
+}
+
+// builder
+
+#[test]
+/// the builder makes the same fence as the chained setters
+fn test_builder_builds() {
+    let built = RailFenceBuilder::new(3).read_order(ReadOrder::RailMajor).period_alternating(true).build().unwrap();
+    let chained = RailFence::new(3).with_period_alternating(true);
+    assert_eq!(built.encode("THEQUICKBROWNFOX"), chained.encode("THEQUICKBROWNFOX"));
+}
+
+#[test]
+/// validation reports every problem at once
+fn test_builder_reports_all_problems() {
+    let builder = RailFenceBuilder::new(0)
+        .schedule(|position| (position % 2) as u32)
+        .turn_policy(TurnPolicy::Dwell)
+        .period_alternating(true);
+    let expected = vec![
+        RailFenceError::ZeroRails,
+        RailFenceError::ConflictingOptions { first: "a custom schedule", second: "a turn policy" },
+        RailFenceError::ConflictingOptions { first: "a custom schedule", second: "alternating periods" },
+    ];
+    assert_eq!(builder.validate(), Err(expected.clone()));
+    assert_eq!(builder.build().unwrap_err(), expected);
+}
+
+#[test]
+/// the builder takes a rail order and an alphabet like the constructors
+fn test_builder_rail_order_and_alphabet() {
+    let built = RailFenceBuilder::new(4)
+        .rail_order(Some(vec![2, 0, 3, 1]))
+        .alphabet(Some("ABCDEFGHIJKLMNOPQRSTUVWXYZ"))
+        .build()
+        .unwrap();
+    let keyed = RailFence::with_rail_order(&[2, 0, 3, 1]);
+    assert_eq!(built.encode("RUSTISGREAT"), keyed.encode("RUSTISGREAT"));
+    assert_eq!(built.try_encode("RUST IS"), Err(RailFenceError::InvalidCharacter { ch: ' ', position: 4 }));
+    let alternating = RailFenceBuilder::new(3).rail_order(Some(vec![2, 0, 1])).period_alternating(true).build().unwrap();
+    assert_eq!(alternating.encode("ABCDEFGHIJ"), RailFence::with_rail_order(&[2, 0, 1]).with_period_alternating(true).encode("ABCDEFGHIJ"));
+}
+
+#[test]
+/// validation rejects rail orders and alphabets that cannot work
+fn test_builder_rejects_bad_rail_order_and_alphabet() {
+    for order in [vec![], vec![0, 0, 1], vec![1, 2, 3], vec![0, 2]] {
+        let problems = RailFenceBuilder::new(3).rail_order(Some(order.clone())).validate().unwrap_err();
+        assert_eq!(problems, vec![RailFenceError::InvalidRailOrder], "{:?}", order);
+    }
+    assert_eq!(
+        RailFenceBuilder::new(3).rail_order(Some(vec![1, 0])).validate(),
+        Err(vec![RailFenceError::ConflictingOptions { first: "a rail order", second: "a different number of rails" }])
+    );
+    assert_eq!(
+        RailFenceBuilder::new(2).rail_order(Some(vec![1, 0])).read_order(ReadOrder::ColumnMajor).validate(),
+        Err(vec![RailFenceError::ConflictingOptions { first: "column-major reading", second: "a rail order" }])
+    );
+    assert_eq!(
+        RailFenceBuilder::new(3).alphabet(Some("ABCAB")).build().unwrap_err(),
+        vec![RailFenceError::RepeatedInAlphabet { ch: 'A' }]
+    );
+}
+
+#[test]
+/// the builder applies the starting direction, casing and whitespace options
+fn test_builder_text_options() {
+    let built = RailFenceBuilder::new(4)
+        .upward(true)
+        .offset(1)
+        .case(Case::Lower)
+        .strip_whitespace(true)
+        .build()
+        .unwrap();
+    let chained = RailFence::new(4)
+        .with_upward_start(true)
+        .with_offset(1)
+        .with_case(Case::Lower)
+        .with_whitespace_stripped(true);
+    assert_eq!(built.encode("Rust is GREAT"), chained.encode("Rust is GREAT"));
+    assert_eq!(built.decode(built.encode("Rust is GREAT")), "rustisgreat");
+    let problems = RailFenceBuilder::new(3).schedule(|position| (position % 3) as u32).upward(true).validate();
+    assert_eq!(problems, Err(vec![RailFenceError::ConflictingOptions { first: "a custom schedule", second: "an upward start" }]));
+}
+
+#[test]
+/// an upward start mirrors the fence top to bottom
+fn test_upward_start_mirrors_fence() {
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    for rails in 1..7 {
+        for offset in 0..RailFence::new(rails).period() {
+            let down = RailFence::new(rails).with_offset(offset);
+            let up = down.clone().with_upward_start(true);
+            let mut mirrored = down.grid(text);
+            mirrored.reverse();
+            assert_eq!(up.grid(text), mirrored);
+            let mut rails_up = down.encode_rails(text);
+            rails_up.reverse();
+            assert_eq!(up.encode(text), rails_up.concat());
+            assert_eq!(up.decode(up.encode(text)), text);
+            assert!(!up.is_compatible_with(&down) || rails == 1);
+        }
+    }
+}
+
+#[test]
+/// casing and whitespace stripping prepare the text before it is placed
+fn test_normalize_options() {
+    let text = "Straße am\tMeer\n";
+    assert!(matches!(RailFence::new(3).normalize(text), std::borrow::Cow::Borrowed(_)));
+    assert_eq!(RailFence::new(3).with_case(Case::Upper).normalize(text), "STRASSE AM\tMEER\n");
+    assert_eq!(RailFence::new(3).with_case(Case::Lower).normalize(text), "straße am\tmeer\n");
+    assert_eq!(RailFence::new(3).with_whitespace_stripped(true).normalize(text), "StraßeamMeer");
+    let rail_fence = RailFence::with_alphabet(3, "ABCDEFGHIJKLMNOPQRSTUVWXYZ").with_case(Case::Upper).with_whitespace_stripped(true);
+    assert_eq!(rail_fence.try_encode("we are discovered"), Ok(RailFence::new(3).encode("WEAREDISCOVERED")));
+}
+
+#[test]
+/// stripping removes everything but letters before encoding
+fn test_char_filter_strip() {
+    let rail_fence = RailFence::new(3).with_char_filter(CharFilter::Strip);
+    assert_eq!(rail_fence.normalize("We are discovered; flee at once!"), "Wearediscoveredfleeatonce");
+    assert_eq!(rail_fence.encode("WE ARE DISCOVERED. FLEE AT ONCE!"), "WECRLTEERDSOEEFEAOCAIVDEN");
+    assert_eq!(RailFence::new(3).with_char_filter(CharFilter::Passthrough).encode("A-B"), RailFence::new(3).encode("A-B"));
+}
+
+#[test]
+/// preserving positions transposes only the letters and round trips the rest
+fn test_char_filter_preserve_positions() {
+    let text = "We are discovered, flee at once! 123";
+    for rails in 1..8 {
+        let rail_fence = RailFence::new(rails).with_char_filter(CharFilter::PreservePositions);
+        let cipher_text = rail_fence.encode(text);
+        assert_eq!(cipher_text, RailFence::new(rails).encode_skeleton(text));
+        assert_eq!(rail_fence.decode(&cipher_text), text);
+        assert_eq!(rail_fence.try_decode(&cipher_text), Ok(String::from(text)));
+        let marks: Vec<(usize, char)> = text.char_indices().filter(|(_, c)| !c.is_alphabetic()).collect();
+        assert_eq!(marks, cipher_text.char_indices().filter(|(_, c)| !c.is_alphabetic()).collect::<Vec<_>>());
+    }
+    let built = RailFenceBuilder::new(3).char_filter(CharFilter::PreservePositions).build().unwrap();
+    assert_eq!(built.encode(text), RailFence::new(3).encode_skeleton(text));
+}
+
+#[test]
+/// period padding fills each message out to whole periods and decode drops it
+fn test_period_padding() {
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    let rail_fence = RailFence::new(3).with_period_padding(Some(DEFAULT_FILL));
+    assert_eq!(rail_fence.encode(text), "WECRLTEERDSOEEFEAOCXXAIVDENX");
+    for rails in 1..8 {
+        for turn in [TurnPolicy::Bounce, TurnPolicy::Dwell] {
+            let rail_fence = RailFence::new(rails).with_turn_policy(turn).with_period_padding(Some('*'));
+            for len in 0..text.len() {
+                let cipher_text = rail_fence.encode(&text[..len]);
+                assert!(cipher_text.len().is_multiple_of(rail_fence.period()));
+                assert!(cipher_text.len() < len + rail_fence.period());
+                assert_eq!(rail_fence.decode(&cipher_text), &text[..len]);
+            }
+        }
+    }
+    let problems = RailFenceBuilder::new(3).schedule(|position| (position % 3) as u32).period_padding(Some('X')).validate();
+    assert_eq!(problems, Err(vec![RailFenceError::ConflictingOptions { first: "a custom schedule", second: "period padding" }]));
+}
+
+#[test]
+/// grouped cipher-text comes out in classic groups and decodes with any spacing
+fn test_grouping_and_ignored_whitespace() {
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    let grouped = RailFence::new(3).with_grouping(Some(5));
+    assert_eq!(grouped.encode(text), "WECRL TEERD SOEEF EAOCA IVDEN");
+    assert_eq!(grouped.try_encode(text), Ok(String::from("WECRL TEERD SOEEF EAOCA IVDEN")));
+    assert_eq!(grouped.encode_chars(text).collect::<String>(), grouped.encode(text));
+    assert_eq!(grouped.encode_parallel(text), grouped.encode(text));
+    assert_eq!(grouped.decode("WECRL TEERD SOEEF EAOCA IVDEN"), text);
+    assert_eq!(grouped.decode_iter("WECR LTEE RDSO\nEEFE AOCA IVDE N").collect::<String>(), text);
+    assert_eq!(RailFence::new(3).with_grouping(Some(0)).encode(text), RailFence::new(3).encode(text));
+    for len in 0..text.len() {
+        let cipher_text = grouped.encode(&text[..len]);
+        assert!(cipher_text.split(' ').all(|group| group.len() <= 5));
+        assert_eq!(grouped.decode(&cipher_text), &text[..len]);
+    }
+    let tolerant = RailFence::new(3).with_whitespace_ignored(true);
+    assert_eq!(tolerant.encode(text), RailFence::new(3).encode(text));
+    assert_eq!(tolerant.decode("WECRLTE\r\nERDSOEE\tFEAOCAIVDEN\n"), text);
+    let problems = RailFenceBuilder::new(3).char_filter(CharFilter::PreservePositions).grouping(Some(5)).ignore_whitespace(true).validate();
+    assert_eq!(problems, Err(vec![
+        RailFenceError::ConflictingOptions { first: "preserved positions", second: "grouping" },
+        RailFenceError::ConflictingOptions { first: "preserved positions", second: "ignoring whitespace" },
+    ]));
+}
+
+// last character placement
+
+#[test]
+/// decode consumes every character when the message ends mid-fence
+fn test_decode_ending_mid_fence() {
+    let alphabet = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+    for rails in 3..7 {
+        let rail_fence = RailFence::new(rails);
+        let period = rail_fence.period();
+        for len in 1..alphabet.len() {
+            // The last character's phase, on the way down or the way back up
+            let phase = (len - 1) % period;
+            if phase == 0 || phase == rails as usize - 1 {
+                continue;
+            }
+            let text = &alphabet[..len];
+            let decoded = rail_fence.decode(rail_fence.encode(text));
+            assert_eq!(decoded, text, "{} rails, {} characters", rails, len);
+        }
+    }
+}
+
+// start offsets
+
+#[test]
+/// every start offset round-trips, and offsets wrap at the period
+fn test_offset_round_trip() {
+    let text = "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG";
+    for rails in 1..6 {
+        for turn in [TurnPolicy::Bounce, TurnPolicy::Dwell] {
+            let plain = RailFence::new(rails).with_turn_policy(turn);
+            let period = plain.period();
+            for offset in 0..period {
+                let rail_fence = plain.clone().with_offset(offset);
+                assert_eq!(rail_fence.decode(rail_fence.encode(text)), text);
+                let wrapped = plain.clone().with_offset(offset + period);
+                assert_eq!(wrapped.encode(text), rail_fence.encode(text));
+            }
+        }
+    }
+}
+
+#[test]
+/// a starting rail and direction convert to an offset
+fn test_offset_from_starting_rail() {
+    for rails in 2..7 {
+        let period = RailFence::new(rails).period();
+        for rail in 0..rails as usize {
+            let down = RailFence::new(rails).with_offset(rail);
+            let up = RailFence::new(rails).with_offset(period - rail);
+            // The end rails can only be left one way
+            let below = if rail + 1 < rails as usize { rail + 1 } else { rail - 1 };
+            let above = if rail > 0 { rail - 1 } else { 1 };
+            for (rail_fence, next) in [(down, below), (up, above)] {
+                let grid = rail_fence.grid("AB");
+                assert_eq!(grid[rail][0], Some('A'));
+                assert_eq!(grid[next][1], Some('B'), "{} rails from rail {}", rails, rail);
+            }
+        }
+    }
+}
+
+#[test]
+/// a start offset makes short messages scramble
+fn test_offset_is_not_degenerate() {
+    let rail_fence = RailFence::new(3).with_offset(1);
+    assert!(!rail_fence.is_degenerate(3));
+    assert_eq!(rail_fence.encode("ABC"), "ACB");
+}
+
+#[test]
+/// the builder rejects offsets beyond the period
+fn test_builder_offset_out_of_range() {
+    let builder = RailFenceBuilder::new(3).offset(4);
+    assert_eq!(builder.validate(), Err(vec![RailFenceError::OffsetOutOfRange { offset: 4, period: 4 }]));
+    assert!(RailFenceBuilder::new(3).offset(3).build().is_ok());
+}
+
+#[test]
+#[cfg(feature = "rand")]
+/// randomized messages always decode, and repeats look different
+fn test_randomized_round_trip() {
+    use rand::SeedableRng;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+    let rail_fence = RailFence::new(5);
+    let mut seen = std::collections::HashSet::new();
+    for _ in 0..200 {
+        let encoded = rail_fence.encode_randomized("MEETMEATNOON", &mut rng);
+        assert_eq!(rail_fence.decode_randomized(&encoded).unwrap(), "MEETMEATNOON");
+        seen.insert(encoded);
+    }
+    assert!(seen.len() > 1);
+}
+
+#[test]
+/// garbage is not mistaken for a randomized message
+fn test_decode_randomized_without_header() {
+    assert_eq!(RailFence::new(3).decode_randomized(""), Err(RailFenceError::BadHeader));
+    assert_eq!(RailFence::new(1).decode_randomized("9:ABC"), Err(RailFenceError::BadHeader));
+}
+
+// effective rails
+
+#[test]
+/// rail counts collapse once the zig-zag no longer turns back up
+fn test_effective_rails() {
+    for (rails, len, effective) in [(50, 5, 1), (5, 5, 1), (4, 5, 4), (3, 5, 3), (2, 1, 1), (7, 0, 1), (1, 9, 1)] {
+        assert_eq!(RailFence::new(rails).effective_rails(len), effective, "{} rails, length {}", rails, len);
+    }
+    let dwell = RailFence::new(2).with_turn_policy(TurnPolicy::Dwell);
+    assert_eq!(dwell.effective_rails(2), 1);
+    let column_major = RailFence::new(6).with_read_order(ReadOrder::ColumnMajor);
+    assert_eq!(column_major.effective_rails(20), 1);
+    let schedule = RailFence::with_schedule(3, |_| 0);
+    assert_eq!(schedule.effective_rails(10), 3);
+}
+
+#[test]
+/// a keyed rail order is only asked about its own rail count
+fn test_effective_rails_rail_order() {
+    let keyed = RailFence::with_rail_order(&[2, 0, 3, 1]);
+    assert_eq!(keyed.effective_rails(11), 4);
+    assert_eq!(keyed.effective_rails(1), 1);
+    assert_eq!(keyed.with_period_alternating(true).effective_rails(11), 4);
+    assert_eq!(RailFence::with_rail_order(&[0, 1, 2, 3]).effective_rails(3), 1);
+    assert_eq!(RailFence::with_rail_order(&[3, 2, 1, 0]).effective_rails(3), 4);
+    assert!(RailFence::from_passphrase(5, "SECRET").effective_rails(20) <= 5);
+}
+
+// fallible construction
+
+#[test]
+/// try_new refuses only a fence with no rails
+fn test_try_new() {
+    assert_eq!(RailFence::try_new(0).map(|rail_fence| rail_fence.rails()), Err(RailFenceError::ZeroRails));
+    for rails in 1..30 {
+        let rail_fence = RailFence::try_new(rails).unwrap();
+        assert_eq!(rail_fence.rails(), rails);
+        for text in &["", "AB", "WEAREDISCOVEREDFLEEATONCE"] {
+            let cipher = rail_fence.try_encode(text).unwrap();
+            assert_eq!(rail_fence.try_decode(&cipher), Ok(text.to_string()));
+        }
+    }
+}
+
+// fallible decoding
+
+#[test]
+/// try_decode agrees with decode on every fence it accepts
+fn test_try_decode_matches_decode() {
+    let texts = ["", "A", "RGUSRSIETTA", "古池蛙飛び込む水の音", "a\u{301}e\u{301}\u{0}", "🦀 rails 🦀"];
+    for rails in 1..12 {
+        for offset in 0..3 {
+            let rail_fence = RailFence::new(rails).with_offset(offset);
+            for text in &texts {
+                let encoded = rail_fence.encode(text);
+                assert_eq!(rail_fence.try_decode(&encoded), Ok(String::from(*text)));
+                assert_eq!(rail_fence.try_decode(text), Ok(rail_fence.decode(text)));
+            }
+        }
+    }
+}
+
+#[test]
+/// trailing junk after the cipher-text is ignored
+fn test_decode_len_ignores_trailing_junk() {
+    let rail_fence = RailFence::new(3);
+    let encoded = rail_fence.encode("古池蛙飛び込む水の音");
+    assert_eq!(rail_fence.decode_len(&(encoded.clone() + "xyz"), 10), Ok(String::from("古池蛙飛び込む水の音")));
+    assert_eq!(rail_fence.decode_len(&encoded, 10), Ok(String::from("古池蛙飛び込む水の音")));
+    assert_eq!(rail_fence.decode_len("ABC", 0), Ok(String::new()));
+}
+
+#[test]
+/// a cipher-text shorter than the stated length is rejected
+fn test_decode_len_too_short() {
+    assert_eq!(
+        RailFence::new(3).decode_len("WECRL", 6),
+        Err(RailFenceError::LengthMismatch { expected: 6, found: 5 })
+    );
+}
+
+#[test]
+/// a fence without rails is an error, not a panic
+fn test_try_decode_zero_rails() {
+    let rail_fence = RailFence::new(0);
+    assert_eq!(rail_fence.try_decode(""), Err(RailFenceError::ZeroRails));
+    assert_eq!(rail_fence.try_decode("ABC"), Err(RailFenceError::ZeroRails));
+}
+
+#[test]
+/// a schedule that leaves the fence is an error, not a panic
+fn test_try_decode_schedule_out_of_range() {
+    let rail_fence = RailFence::with_schedule(2, |position| position as u32);
+    assert_eq!(rail_fence.try_decode("AB"), Ok(String::from("AB")));
+    assert_eq!(
+        rail_fence.try_decode("ABC"),
+        Err(RailFenceError::ScheduleOutOfRange { position: 2, rail: 2, rails: 2 })
+    );
+}
+
+#[test]
+#[cfg(feature = "testutil")]
+/// random fences are valid and round-trip
+fn test_random_fence() {
+    use rail_fence_cipher::testutil::random_fence;
+    use rand::SeedableRng;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(17);
+    for _ in 0..100 {
+        let rail_fence = random_fence(&mut rng, 6);
+        assert!((1..=6).contains(&rail_fence.rails()));
+        let text = "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG";
+        assert_eq!(rail_fence.decode(rail_fence.encode(text)), text);
+    }
+    assert_eq!(random_fence(&mut rng, 0).rails(), 1);
+}
+
+// diffusion
+
+#[test]
+/// the chosen rail count moves more characters than the trivial choices
+fn test_best_diffusion_rails() {
+    let len = 100;
+    let rails = RailFence::best_diffusion_rails(len);
+    assert!((2..=MAX_DIFFUSION_RAILS).contains(&rails));
+    let moved = |rails| len - RailFence::new(rails).fixed_points(len).len();
+    assert!(moved(rails) > moved(1));
+    assert!(moved(rails) > moved(len as u32));
+    assert!(moved(rails) >= moved(2));
+    assert_eq!(RailFence::best_diffusion_rails(0), 2);
+    assert_eq!(RailFence::best_diffusion_rails(2), 2);
+}
+
+#[test]
+/// the scrambling rail count leaves fewer characters in place than its neighbours
+fn test_scrambling_rails() {
+    let fixed = |rails, len| RailFence::new(rails).fixed_points(len).len();
+    let rails = RailFence::scrambling_rails(12);
+    assert_eq!(rails, 6);
+    assert!(fixed(rails, 12) < fixed(rails - 1, 12));
+    assert!(fixed(rails, 12) < fixed(rails + 1, 12));
+    for len in 3..=MAX_DIFFUSION_RAILS as usize {
+        let least = (2..len as u32).map(|rails| fixed(rails, len)).min();
+        assert_eq!(Some(fixed(RailFence::scrambling_rails(len), len)), least);
+    }
+    assert_eq!(RailFence::scrambling_rails(0), 2);
+}
+
+#[test]
+/// encoding cycle_length times gives back the message and no fewer times do
+fn test_cycle_length() {
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    for rails in 1..8 {
+        for rail_fence in &[RailFence::new(rails), RailFence::new(rails).with_turn_policy(TurnPolicy::Dwell)] {
+            let cycle = rail_fence.cycle_length(text.len()).unwrap();
+            let mut encoded = text.to_string();
+            for round in 1..=cycle {
+                encoded = rail_fence.encode(&encoded);
+                assert_eq!(encoded == text, round == cycle);
+            }
+        }
+    }
+    assert_eq!(RailFence::new(3).cycle_length(0), Some(1));
+    assert_eq!(RailFence::new(1).cycle_length(10), Some(1));
+}
+
+#[test]
+/// a cycle length too large for a usize is None rather than an overflow
+fn test_cycle_length_overflow() {
+    assert_eq!(RailFence::new(3).cycle_length(10970), None);
+}
+
+// iterators
+
+#[test]
+/// decode_iter yields exactly what decode returns
+fn test_decode_iter_matches_decode() {
+    for rails in 1..8 {
+        let rail_fence = RailFence::new(rails).with_turn_policy(TurnPolicy::Dwell);
+        for cipher in &["", "RGUSRSIETTA", "古池蛙飛び込む水の音", "WECRLTEERDSOEEFEAOCAIVDEN"] {
+            let decoded: String = rail_fence.decode_iter(cipher).collect();
+            assert_eq!(decoded, rail_fence.decode(cipher));
+        }
+    }
+}
+
+#[test]
+/// encode_chars and decode_iter agree with encode and decode whatever the text options
+fn test_char_iterators_match_text_options() {
+    let fences = [
+        RailFence::new(4),
+        RailFence::new(3).with_offset(2).with_period_alternating(true),
+        RailFence::new(4).with_case(Case::Lower).with_whitespace_stripped(true),
+        RailFence::new(3).with_char_filter(CharFilter::Strip),
+        RailFence::new(3).with_char_filter(CharFilter::PreservePositions),
+        RailFence::new(5).with_period_padding(Some(DEFAULT_FILL)),
+        RailFence::new(4).with_char_filter(CharFilter::PreservePositions).with_period_padding(Some('Q')),
+    ];
+    for rail_fence in &fences {
+        for text in &["", "We are discovered, flee at once!", "古池や 蛙飛び込む", "RUSTISGREAT"] {
+            let cipher_text = rail_fence.encode(text);
+            assert_eq!(rail_fence.encode_chars(text).collect::<String>(), cipher_text, "{:?}", rail_fence);
+            assert_eq!(rail_fence.decode_iter(&cipher_text).collect::<String>(), rail_fence.decode(&cipher_text), "{:?}", rail_fence);
+        }
+    }
+}
+
+#[test]
+/// the sink variants append the same text encode and decode return
+fn test_encode_into_and_to_sinks() {
+    let rail_fence = RailFence::new(3).with_char_filter(CharFilter::PreservePositions);
+    let mut out = String::with_capacity(64);
+    for text in &["We are discovered, flee at once!", "古池や 蛙飛び込む", ""] {
+        out.clear();
+        rail_fence.encode_into(text, &mut out);
+        assert_eq!(out, rail_fence.encode(text));
+        let mut written = String::from(">");
+        rail_fence.encode_to(text, &mut written).unwrap();
+        assert_eq!(written, String::from(">") + &rail_fence.encode(text));
+        let cipher_text = out.clone();
+        out.clear();
+        rail_fence.decode_into(&cipher_text, &mut out);
+        assert_eq!(out, *text);
+        let mut written = String::new();
+        rail_fence.decode_to(&cipher_text, &mut written).unwrap();
+        assert_eq!(written, *text);
+    }
+}
+
+#[test]
+/// decode_iter can stop part way through
+fn test_decode_iter_prefix() {
+    let rail_fence = RailFence::new(3);
+    let mut decoded = rail_fence.decode_iter("WECRLTEERDSOEEFEAOCAIVDEN");
+    assert_eq!(decoded.next(), Some('W'));
+    assert_eq!(decoded.take(5).collect::<String>(), "EARED");
+}
+
+#[test]
+/// encode_chunks splits the cipher-text into pieces of the requested size
+fn test_encode_chunks() {
+    let rail_fence = RailFence::new(3);
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    for chunk_len in 1..30 {
+        let chunks: Vec<String> = rail_fence.encode_chunks(text, chunk_len).collect();
+        assert_eq!(chunks.len(), text.len().div_ceil(chunk_len));
+        assert!(chunks.iter().all(|chunk| chunk.chars().count() <= chunk_len));
+        assert!(chunks[..chunks.len() - 1].iter().all(|chunk| chunk.chars().count() == chunk_len));
+        assert_eq!(chunks.concat(), rail_fence.encode(text));
+    }
+    let chunks: Vec<String> = RailFence::new(2).encode_chunks("古池蛙飛び", 2).collect();
+    assert_eq!(chunks, vec!["古蛙", "び池", "飛"]);
+    assert_eq!(rail_fence.encode_chunks("", 4).count(), 0);
+}
+
+#[test]
+#[should_panic]
+/// encode_chunks cannot make empty pieces
+fn test_encode_chunks_zero_length() {
+    let _ = RailFence::new(3).encode_chunks("RUSTISGREAT", 0);
+}
+
+// grapheme clusters
+
+#[test]
+#[cfg(feature = "graphemes")]
+/// zero-width-joiner emoji survive the transposition intact
+fn test_graphemes_keep_zwj_sequences() {
+    use unicode_segmentation::UnicodeSegmentation;
+    let text = "Hi 👨\u{200d}👩\u{200d}👧\u{200d}👦 there";
+    for rails in 1..6 {
+        let rail_fence = RailFence::new(rails);
+        let encoded = rail_fence.encode_graphemes(text);
+        assert!(encoded.graphemes(true).any(|cluster| cluster == "👨\u{200d}👩\u{200d}👧\u{200d}👦"));
+        assert_eq!(encoded.graphemes(true).count(), text.graphemes(true).count());
+        assert_eq!(rail_fence.decode_graphemes(&encoded), text);
+    }
+}
+
+#[test]
+#[cfg(feature = "graphemes")]
+/// combining marks stay on the letter they belong to, while plain decoding splits them
+fn test_graphemes_keep_combining_sequences() {
+    let text = "cafe\u{301} nai\u{308}ve";
+    let rail_fence = RailFence::new(3);
+    let encoded = rail_fence.encode_graphemes(text);
+    assert_eq!(encoded, "c vae\u{301}ni\u{308}efa");
+    assert_eq!(rail_fence.decode_graphemes(&encoded), text);
+    let by_chars = rail_fence.encode(text);
+    assert!(!by_chars.contains("e\u{301}"));
+    assert_eq!(rail_fence.decode(by_chars), text);
+}
+
+// comb
+
+#[test]
+/// comb order round-trips and differs from reading the rails in order
+fn test_comb_round_trip() {
+    let rail_fence = RailFence::new(5);
+    let text = "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG";
+    let encoded = rail_fence.encode_comb(text);
+    assert_ne!(encoded, rail_fence.encode(text));
+    assert_eq!(rail_fence.decode_comb(&encoded), text);
+    let rails = rail_fence.encode_rails(text);
+    assert_eq!(encoded, [0, 2, 4, 1, 3].iter().map(|&rail| rails[rail].as_str()).collect::<String>());
+}
+
+#[test]
+/// the comb replaces a fence's own rail order
+fn test_comb_replaces_rail_order() {
+    let text = "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG";
+    let keyed = RailFence::with_rail_order(&[4, 3, 2, 1, 0]);
+    assert_eq!(keyed.encode_comb(text), RailFence::new(5).encode_comb(text));
+    assert_eq!(keyed.decode_comb(&keyed.encode_comb(text)), text);
+}
+
+#[test]
+/// with alternating periods the comb order is read backwards on odd periods
+fn test_comb_period_alternating() {
+    let rail_fence = RailFence::new(3).with_period_alternating(true);
+    // ABCD EFGH IJ, read in comb order as A C BD, FH G E, I J
+    assert_eq!(rail_fence.encode_comb("ABCDEFGHIJ"), "ACBDFHGEIJ");
+    assert_ne!(rail_fence.encode_comb("ABCDEFGHIJ"), rail_fence.encode("ABCDEFGHIJ"));
+    let text = "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG";
+    let rail_fence = RailFence::new(5).with_period_alternating(true);
+    assert_eq!(rail_fence.decode_comb(&rail_fence.encode_comb(text)), text);
+}
+
+// scripts
+
+#[test]
+/// right-to-left, Indic and CJK scripts round-trip in logical order
+fn test_non_latin_scripts_round_trip() {
+    let texts = [
+        "مرحبا بالعالم",
+        "שלום עולם",
+        "नमस्ते दुनिया",
+        "你好，世界",
+        "こんにちは世界",
+        "안녕하세요 세계",
+        "Hello مرحبا and שלום 123",
+        "\u{202b}RTL embed\u{202c} with marks \u{200f}",
+    ];
+    for rails in 1..8 {
+        let rail_fence = RailFence::new(rails);
+        for text in &texts {
+            let encoded = rail_fence.encode(text);
+            assert_eq!(encoded.chars().count(), text.chars().count());
+            assert_eq!(rail_fence.decode(&encoded), *text);
+        }
+    }
+}
+
+#[test]
+/// right-to-left text is transposed in the order it is stored
+fn test_rtl_uses_logical_order() {
+    // Logical order is ש ל ו ם, whatever the display direction
+    assert_eq!(RailFence::new(2).encode("שלום"), "שולם");
+}
+
+// precomputed schedules
+
+#[test]
+/// stored schedules reload and transpose exactly like the fence
+fn test_rail_schedule_bytes_round_trip() {
+    let text = "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG";
+    for rails in 1..8 {
+        let rail_fence = RailFence::new(rails);
+        let schedule = rail_fence.rail_schedule(text.len());
+        let reloaded = RailSchedule::from_bytes(text.len(), &schedule.to_bytes()).unwrap();
+        assert_eq!(reloaded, schedule);
+        assert_eq!(reloaded.encode(text).unwrap(), rail_fence.encode(text));
+        assert_eq!(reloaded.decode(&rail_fence.encode(text)).unwrap(), text);
+    }
+    let large = RailFence::new(50).rail_schedule(10_000);
+    assert_eq!(RailSchedule::from_bytes(10_000, &large.to_bytes()), Ok(large));
+}
+
+#[test]
+/// corrupt stored schedules are rejected
+fn test_rail_schedule_invalid_bytes() {
+    let bytes = RailFence::new(3).rail_schedule(6).to_bytes();
+    assert_eq!(RailSchedule::from_bytes(6, &bytes[..5]), Err(RailFenceError::InvalidSchedule));
+    assert_eq!(RailSchedule::from_bytes(6, &[bytes.as_slice(), &[0]].concat()), Err(RailFenceError::InvalidSchedule));
+    assert_eq!(RailSchedule::from_bytes(3, &[0, 0, 2]), Err(RailFenceError::InvalidSchedule));
+    assert_eq!(RailSchedule::from_bytes(1, &[0xff; 12]), Err(RailFenceError::InvalidSchedule));
+    assert_eq!(RailSchedule::from_bytes(0, &[]).map(|schedule| schedule.len()), Ok(0));
+}
+
+#[test]
+/// a schedule only accepts messages of its own length
+fn test_rail_schedule_length_mismatch() {
+    let schedule = RailFence::new(3).rail_schedule(4);
+    assert_eq!(schedule.encode("ABC"), Err(RailFenceError::LengthMismatch { expected: 4, found: 3 }));
+}
+
+// fixed rail counts
+
+fn check_const_fence<const RAILS: usize>(data: &[u8]) {
+    let fixed = ConstRailFence::<RAILS>::new();
+    let rail_fence = fixed.to_rail_fence();
+    assert_eq!(fixed.rails(), RAILS);
+    for len in 0..data.len() {
+        let mut out = vec![0u8; len + 3];
+        assert_eq!(fixed.encode_into_slice(&data[..len], &mut out), Ok(len));
+        assert_eq!(&out[..len], &rail_fence.encode_bytes(&data[..len])[..]);
+        let mut back = vec![0u8; len];
+        assert_eq!(fixed.decode_into_slice(&out[..len], &mut back), Ok(len));
+        assert_eq!(back, &data[..len]);
+    }
+    let mut small = [0u8; 2];
+    assert_eq!(fixed.encode_into_slice(b"RUST", &mut small), Err(RailFenceError::BufferTooSmall { needed: 4, available: 2 }));
+    assert_eq!(fixed.decode_into_slice(b"RUST", &mut small), Err(RailFenceError::BufferTooSmall { needed: 4, available: 2 }));
+}
+
+#[test]
+/// a fence with its rails fixed at compile time matches the plain fence
+fn test_const_fence_matches_rail_fence() {
+    let data: Vec<u8> = (0..=255u8).rev().take(40).collect();
+    check_const_fence::<1>(&data);
+    check_const_fence::<2>(&data);
+    check_const_fence::<3>(&data);
+    check_const_fence::<5>(&data);
+    check_const_fence::<8>(&data);
+    check_const_fence::<64>(&data);
+}
+
+// in-place transposition
+
+#[test]
+/// in-place encoding matches encode and decodes back exactly
+fn test_in_place_round_trip() {
+    let texts = ["", "A", "WEAREDISCOVEREDFLEEATONCE", "古池蛙飛び込む水の音", "mixed ÄSCII, 🦀 and ǅ"];
+    for rails in 1..7 {
+        let fences = [
+            RailFence::new(rails),
+            RailFence::new(rails).with_turn_policy(TurnPolicy::Dwell).with_offset(2),
+            RailFence::new(rails).with_period_alternating(true),
+            RailFence::new(rails).with_read_order(ReadOrder::ColumnMajor),
+        ];
+        for rail_fence in &fences {
+            for text in &texts {
+                let mut buf = String::from(*text);
+                rail_fence.encode_in_place(&mut buf);
+                assert_eq!(buf, rail_fence.encode(text));
+                rail_fence.decode_in_place(&mut buf);
+                assert_eq!(buf, *text);
+            }
+        }
+    }
+}
+
+#[test]
+/// many rounds are combined rather than done one pass at a time
+fn test_in_place_many_rounds() {
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    let rail_fence = RailFence::new(4).with_rounds(1_000_000_007);
+    let mut buf = String::from(text);
+    rail_fence.encode_in_place(&mut buf);
+    assert_eq!(buf, rail_fence.encode(text));
+    rail_fence.decode_in_place(&mut buf);
+    assert_eq!(buf, text);
+}
+
+#[test]
+/// only the transposition is done in place, without the text options
+fn test_in_place_transposition_only() {
+    let text = "We are discovered";
+    let plain = RailFence::with_rail_order(&[1, 3, 0, 2]);
+    let configured = RailFence::with_rail_order(&[1, 3, 0, 2])
+        .with_case(Case::Upper)
+        .with_char_filter(CharFilter::Strip)
+        .with_period_padding(Some('X'))
+        .with_grouping(Some(5));
+    let mut buf = String::from(text);
+    configured.encode_in_place(&mut buf);
+    assert_eq!(buf, plain.encode(text));
+    configured.decode_in_place(&mut buf);
+    assert_eq!(buf, text);
+}
+
+// columnar transposition
+
+#[test]
+/// the combined transposition round-trips for any width
+fn test_2d_round_trip() {
+    let text = "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG";
+    for rails in 1..6 {
+        let rail_fence = RailFence::new(rails);
+        for cols in [0, 1, 3, 5, 7, 35, 50] {
+            let encoded = rail_fence.encode_2d(text, cols);
+            assert_eq!(encoded.chars().count(), text.len());
+            assert_eq!(rail_fence.decode_2d(&encoded, cols), text);
+        }
+    }
+}
+
+#[test]
+/// one column leaves only the rail fence
+fn test_2d_single_column() {
+    let rail_fence = RailFence::new(3);
+    assert_eq!(rail_fence.encode_2d("WEAREDISCOVERED", 1), rail_fence.encode("WEAREDISCOVERED"));
+}
+
+// transposition ciphers
+
+#[test]
+/// every cipher behind the trait round-trips and only reorders characters
+fn test_transposition_ciphers_round_trip() {
+    let ciphers: Vec<Box<dyn TranspositionCipher>> = vec![
+        Box::new(RailFence::new(3)),
+        Box::new(Scytale::new(0)),
+        Box::new(Scytale::new(4)),
+        Box::new(SpiralRoute::new(1)),
+        Box::new(SpiralRoute::new(3)),
+        Box::new(SpiralRoute::new(6)),
+    ];
+    let text = "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG";
+    for cipher in &ciphers {
+        for len in 0..text.len() {
+            let encoded = cipher.encode(&text[..len]);
+            let mut sorted: Vec<char> = encoded.chars().collect();
+            sorted.sort_unstable();
+            let mut expected: Vec<char> = text[..len].chars().collect();
+            expected.sort_unstable();
+            assert_eq!(sorted, expected);
+            assert_eq!(cipher.decode(&encoded), &text[..len]);
+            assert_eq!(cipher.try_decode(&encoded), Ok(String::from(&text[..len])));
+        }
+    }
+    assert_eq!(TranspositionCipher::try_encode(&RailFence::new(0), "RUST"), Err(RailFenceError::ZeroRails));
+}
+
+#[test]
+/// the spiral winds inwards through a full grid
+fn test_spiral_route_full_grid() {
+    // Rows "ABCD", "EFGH", "IJKL"
+    assert_eq!(SpiralRoute::new(4).encode("ABCDEFGHIJKL"), "ABCDHLKJIEFG");
+    assert_eq!(Scytale::new(3).encode("ABCDEFGHIJKL"), "AEIBFJCGKDHL");
+}
+
+#[test]
+/// a pipeline matches encoding stage by stage, fused or not
+fn test_pipeline_matches_stages() {
+    let text = "THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG";
+    let fused = RailFence::new(3).then(Scytale::new(4)).then(SpiralRoute::new(5)).then(RailFence::new(5).with_offset(2));
+    let by_hand = RailFence::new(5).with_offset(2).encode(SpiralRoute::new(5).encode(&Scytale::new(4).encode(&RailFence::new(3).encode(text))));
+    assert_eq!(fused.len(), 4);
+    assert_eq!(fused.encode(text), by_hand);
+    assert_eq!(fused.try_encode(text), Ok(by_hand.clone()));
+    assert_eq!(fused.decode(&by_hand), text);
+    assert_eq!(fused.try_decode(&by_hand), Ok(String::from(text)));
+    // A stage that strips characters cannot be fused, and runs on its own
+    let unfused = RailFence::new(3).with_whitespace_stripped(true).then(RailFence::new(4));
+    assert_eq!(unfused.encode_order(text.len()), None);
+    assert_eq!(unfused.encode(text), RailFence::new(4).encode(RailFence::new(3).encode(text.replace(' ', ""))));
+    assert_eq!(Pipeline::new().encode(text), text);
+    let broken = RailFence::new(3).then(RailFence::new(0));
+    assert_eq!(broken.try_encode(text), Err(RailFenceError::ZeroRails));
+}
+
+// errors
+
+#[test]
+/// every error explains itself and names its stage
+fn test_error_display_and_context() {
+    let cases = [
+        (RailFenceError::ZeroRails, "at least one rail", ErrorContext::Construction),
+        (RailFenceError::InvalidPadding, "length header", ErrorContext::Framing),
+        (RailFenceError::RailCountMismatch { expected: 3, found: 2 }, "expected 3 rails but found 2", ErrorContext::Decode),
+        (
+            RailFenceError::RailLengthMismatch { rail: 1, expected: 4, found: 5 },
+            "rail 1 should hold 4 characters but holds 5",
+            ErrorContext::Decode,
+        ),
+        (RailFenceError::UnalignedLength { length: 7, rails: 3 }, "7 characters cannot be split into 3 rails", ErrorContext::Decode),
+        (RailFenceError::InvalidEscape, "escape character", ErrorContext::Framing),
+        (RailFenceError::InvalidNumber, "decimal number", ErrorContext::Decode),
+        (RailFenceError::BadHeader, "frame header", ErrorContext::Framing),
+        (RailFenceError::LengthMismatch { expected: 6, found: 5 }, "6 characters but found 5", ErrorContext::Decode),
+        (RailFenceError::BufferTooSmall { needed: 9, available: 4 }, "needs 9 bytes but the buffer holds 4", ErrorContext::Encode),
+        (
+            RailFenceError::ScheduleOutOfRange { position: 2, rail: 5, rails: 3 },
+            "position 2 on rail 5 of a 3-rail fence",
+            ErrorContext::Construction,
+        ),
+        (RailFenceError::InvalidSchedule, "stored schedule", ErrorContext::Framing),
+        (RailFenceError::OffsetOutOfRange { offset: 4, period: 4 }, "offset of 4", ErrorContext::Construction),
+        (RailFenceError::InvalidCharacter { ch: 'é', position: 3 }, "'é' at position 3", ErrorContext::Encode),
+        (RailFenceError::InvalidKey, "rails=, offset= and order=", ErrorContext::Construction),
+        (RailFenceError::InvalidPattern, "traversal pattern", ErrorContext::Construction),
+        (
+            RailFenceError::ConflictingOptions { first: "a custom schedule", second: "a turn policy" },
+            "a custom schedule cannot be combined with a turn policy",
+            ErrorContext::Construction,
+        ),
+        (RailFenceError::InvalidRailOrder, "each of its rails exactly once", ErrorContext::Construction),
+        (RailFenceError::RepeatedInAlphabet { ch: 'Q' }, "'Q' more than once", ErrorContext::Construction),
+    ];
+    for (error, message, context) in &cases {
+        assert!(error.to_string().contains(message), "{:?} displays as {:?}", error, error.to_string());
+        assert_eq!(error.context(), *context);
+    }
+}
+
+#[test]
+/// an error about the fence has the same context whichever call reports it
+fn test_error_context_from_either_direction() {
+    let rail_fence = RailFence::with_schedule(3, |position| position as u32);
+    let encode_error = rail_fence.try_encode("RUST").unwrap_err();
+    let decode_error = rail_fence.try_decode("RUST").unwrap_err();
+    assert_eq!(encode_error, decode_error);
+    assert_eq!(encode_error.context(), ErrorContext::Construction);
+    assert_eq!(RailFence::new(0).try_encode("RUST").unwrap_err().context(), ErrorContext::Construction);
+}
+
+#[test]
+#[cfg(feature = "std")]
+/// errors convert into I/O errors that keep the message
+fn test_error_into_io_error() {
+    let error: std::io::Error = RailFenceError::BadHeader.into();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(error.to_string(), RailFenceError::BadHeader.to_string());
+    let error: std::io::Error = RailFenceError::ZeroRails.into();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+#[cfg(feature = "std")]
+/// streaming encodes each block on its own and reads back through io::copy
+fn test_stream_round_trip() {
+    use std::io::Write;
+    let text: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+    for rails in 1..6 {
+        let rail_fence = RailFence::new(rails).with_turn_policy(TurnPolicy::Dwell);
+        for block_periods in 1..4 {
+            let mut writer = rail_fence.writer(Vec::new(), block_periods);
+            let block_len = writer.block_len();
+            assert_eq!(block_len, block_periods * rail_fence.period());
+            // Write in awkward pieces that do not line up with the blocks
+            for piece in text.chunks(7) {
+                writer.write_all(piece).unwrap();
+            }
+            writer.flush().unwrap();
+            assert_eq!(writer.get_ref().len(), text.len() / block_len * block_len);
+            let encoded = writer.finish().unwrap();
+            let expected: Vec<u8> = text.chunks(block_len).flat_map(|block| rail_fence.encode_bytes(block)).collect();
+            assert_eq!(encoded, expected);
+            let mut decoded = Vec::new();
+            std::io::copy(&mut rail_fence.reader(&encoded[..], block_periods), &mut decoded).unwrap();
+            assert_eq!(decoded, text);
+        }
+    }
+    let mut dropped = Vec::new();
+    let mut writer = RailFence::new(3).writer(&mut dropped, 1);
+    writer.write_all(b"RUST").unwrap();
+    drop(writer);
+    assert_eq!(dropped, RailFence::new(3).encode("RUST").into_bytes());
+}
+
+#[test]
+#[cfg(feature = "async")]
+/// async adapters cut the same blocks as the sync ones, through a pipe that makes them wait
+fn test_async_stream_round_trip() {
+    use std::io::Write;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    let text: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    for rails in 1..6 {
+        let rail_fence = RailFence::new(rails).with_turn_policy(TurnPolicy::Dwell);
+        for block_periods in 1..4 {
+            let mut sync_writer = rail_fence.writer(Vec::new(), block_periods);
+            sync_writer.write_all(&text).unwrap();
+            let expected = sync_writer.finish().unwrap();
+            runtime.block_on(async {
+                let mut writer = rail_fence.async_writer(Vec::new(), block_periods);
+                assert_eq!(writer.block_len(), block_periods * rail_fence.period());
+                for piece in text.chunks(7) {
+                    writer.write_all(piece).await.unwrap();
+                }
+                writer.flush().await.unwrap();
+                assert_eq!(writer.get_ref().len(), text.len() / writer.block_len() * writer.block_len());
+                writer.shutdown().await.unwrap();
+                assert_eq!(writer.into_inner(), expected);
+
+                // A small pipe fills up, so both ends have to wait on each other
+                let (near, far) = tokio::io::duplex(5);
+                let mut writer = rail_fence.async_writer(near, block_periods);
+                let mut reader = rail_fence.async_reader(far, block_periods);
+                let send = async {
+                    writer.write_all(&text).await.unwrap();
+                    writer.shutdown().await.unwrap();
+                };
+                let mut decoded = Vec::new();
+                tokio::join!(send, async { reader.read_to_end(&mut decoded).await.unwrap() });
+                assert_eq!(decoded, text);
+            });
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+/// files encode a line at a time or whole, and decode back to the same bytes
+fn test_file_round_trip() {
+    let dir = std::env::temp_dir().join(format!("rail-fence-files-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (clear, secret, back) = (dir.join("clear.txt"), dir.join("secret.txt"), dir.join("back.txt"));
+    let text = "WEAREDISCOVERED\r\nFLEE AT ONCE\n\nRUSTISGREAT";
+    std::fs::write(&clear, text).unwrap();
+    let rail_fence = RailFence::new(3);
+    let expected = [
+        (NewlinePolicy::PerLine, format!("{}\r\n{}\n\n{}", rail_fence.encode("WEAREDISCOVERED"), rail_fence.encode("FLEE AT ONCE"), rail_fence.encode("RUSTISGREAT"))),
+        (NewlinePolicy::WholeTrimmed, rail_fence.encode(text)),
+        (NewlinePolicy::Whole, rail_fence.encode(text)),
+    ];
+    for (newlines, cipher_text) in expected {
+        rail_fence.encode_file_with(&clear, &secret, newlines).unwrap();
+        assert_eq!(std::fs::read_to_string(&secret).unwrap(), cipher_text);
+        rail_fence.decode_file_with(&secret, &back, newlines).unwrap();
+        assert_eq!(std::fs::read_to_string(&back).unwrap(), text);
+    }
+    std::fs::write(&clear, "RUSTISGREAT\n").unwrap();
+    rail_fence.encode_file_with(&clear, &secret, NewlinePolicy::Whole).unwrap();
+    assert_eq!(std::fs::read_to_string(&secret).unwrap(), rail_fence.encode("RUSTISGREAT\n"));
+    rail_fence.encode_file(&clear, &secret).unwrap();
+    rail_fence.decode_file(&secret, &back).unwrap();
+    assert_eq!(std::fs::read_to_string(&back).unwrap(), "RUSTISGREAT\n");
+    let error = RailFence::new(0).encode_file(&clear, &secret).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(RailFence::new(3).encode_file(&dir.join("missing.txt"), &secret).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+/// each line is encoded on its own and keeps its line break
+fn test_encode_lines() {
+    let rail_fence = RailFence::new(3).with_grouping(Some(5));
+    let text = "WEAREDISCOVERED\r\nFLEEATONCE\n\nRUSTISGREAT";
+    let expected = format!(
+        "{}\r\n{}\n\n{}",
+        rail_fence.encode("WEAREDISCOVERED"),
+        rail_fence.encode("FLEEATONCE"),
+        rail_fence.encode("RUSTISGREAT")
+    );
+    assert_eq!(rail_fence.encode_lines(text), expected);
+    assert_eq!(rail_fence.decode_lines(&expected), text);
+    let rail_fence = RailFence::new(4);
+    for text in ["", "\n", "ONE LINE", "TRAILING\n\r\n", "逃げろ\nWE ARE\r\n"] {
+        assert_eq!(rail_fence.decode_lines(rail_fence.encode_lines(text)), text);
+    }
+}
+
+#[test]
+/// feeding a message in pieces gives the same result as all at once
+fn test_incremental_encoder_and_decoder() {
+    let text = "WE ARE DISCOVERED, FLEE AT ONCE — 逃げろ";
+    for rails in 1..8 {
+        let cipher_text = RailFence::new(rails).encode(text);
+        for piece in 1..6 {
+            let mut encoder = RailFenceEncoder::new(rails);
+            let mut decoder = RailFenceDecoder::new(rails);
+            let chars: Vec<char> = text.chars().collect();
+            for chunk in chars.chunks(piece) {
+                encoder.update(&chunk.iter().collect::<String>());
+            }
+            let cipher_chars: Vec<char> = cipher_text.chars().collect();
+            for chunk in cipher_chars.chunks(piece) {
+                decoder.update(&chunk.iter().collect::<String>());
+            }
+            assert_eq!(encoder.finalize(), cipher_text);
+            assert_eq!(decoder.finalize(), text);
+        }
+    }
+    let rail_fence = RailFence::new(3).with_case(Case::Upper).with_whitespace_stripped(true);
+    let mut encoder = rail_fence.encoder();
+    encoder.update("we are ");
+    encoder.update("discovered");
+    assert_eq!(encoder.finalize(), rail_fence.encode("we are discovered"));
+}
+
+#[test]
+#[cfg(feature = "zeroize")]
+/// types holding message text wipe it on request and on drop
+fn test_zeroize_state() {
+    use std::io::{Read, Write};
+    use zeroize::{Zeroize, ZeroizeOnDrop};
+    fn wiped_on_drop<T: ZeroizeOnDrop>(_: &T) {}
+    let rail_fence = RailFence::new(4);
+    let mut encoder = rail_fence.encoder();
+    encoder.update("SECRET");
+    encoder.zeroize();
+    encoder.update("RUSTISGREAT");
+    wiped_on_drop(&encoder);
+    assert_eq!(encoder.finalize(), "RGUSRSIETTA");
+    let mut decoder = rail_fence.decoder();
+    decoder.update("SECRET");
+    decoder.zeroize();
+    decoder.update("RGUSRSIETTA");
+    wiped_on_drop(&decoder);
+    assert_eq!(decoder.finalize(), "RUSTISGREAT");
+
+    let mut writer = rail_fence.writer(Vec::new(), 1);
+    writer.write_all(b"RUSTIS").unwrap();
+    writer.write_all(b"SECRET").unwrap();
+    writer.zeroize();
+    wiped_on_drop(&writer);
+    assert_eq!(writer.finish().unwrap(), rail_fence.encode_bytes(b"RUSTIS"));
+    let encoded: Vec<u8> = [rail_fence.encode_bytes(b"SECRET"), rail_fence.encode_bytes(b"RUSTIS")].concat();
+    let mut reader = rail_fence.reader(&encoded[..], 1);
+    let mut first = [0; 2];
+    reader.read_exact(&mut first).unwrap();
+    reader.zeroize();
+    wiped_on_drop(&reader);
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).unwrap();
+    assert_eq!((&first, &rest[..]), (b"SE", &b"RUSTIS"[..]));
+
+    // Wiping the working copies leaves every path's results unchanged
+    let text = "We are discovered, flee at once — 逃げろ";
+    let options = RailFence::new(3).with_case(Case::Upper).with_period_padding(Some(DEFAULT_FILL)).with_grouping(Some(5));
+    let cipher_text = options.encode(text);
+    assert_eq!(options.encode_chars(text).collect::<String>(), cipher_text);
+    assert_eq!(options.encode_parallel(text), cipher_text);
+    assert_eq!(options.decode(&cipher_text), options.decode_iter(&cipher_text).collect::<String>());
+}
+
+// tracing
+
+#[cfg(feature = "tracing")]
+mod tracing_events {
+    use rail_fence_cipher::*;
+    use std::fmt::Debug;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Records the fields of every event as text
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    struct Fields(String);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0 += &format!("{}={:?} ", field.name(), value);
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, _: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields(String::new());
+            event.record(&mut fields);
+            self.0.lock().unwrap().push(fields.0);
+        }
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    /// encode and decode report the layout they use
+    fn test_tracing_reports_layout() {
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let rail_fence = RailFence::new(4);
+            rail_fence.decode(rail_fence.encode("RUSTISGREAT"));
+        });
+        let events = recorder.0.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        for (event, operation) in events.iter().zip(["encode", "decode"]) {
+            assert!(event.contains(&format!("operation=\"{}\"", operation)), "{}", event);
+            assert!(event.contains("rails=4 len=11 period=6 rail_lengths=[2, 3, 4, 2]"), "{}", event);
+        }
+    }
+}
+
+// height patterns
+
+#[test]
+/// a repeating height pattern round-trips across many periods
+fn test_height_pattern_round_trip() {
+    let text = "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG".repeat(3);
+    for pattern in [vec![3, 4, 5, 4, 3], vec![1], vec![1, 2], vec![6, 2, 1]] {
+        let rail_fence = RailFence::with_height_fn(pattern.clone());
+        assert_eq!(rail_fence.rails(), *pattern.iter().max().unwrap());
+        assert_eq!(rail_fence.decode(rail_fence.encode(&text)), text);
+    }
+}
+
+#[test]
+/// a constant height is the ordinary fence
+fn test_height_pattern_constant() {
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    assert_eq!(RailFence::with_height_fn(vec![3]).encode(text), RailFence::new(3).encode(text));
+}
+
+#[test]
+#[should_panic]
+/// a height of zero is rejected
+fn test_height_pattern_zero() {
+    RailFence::with_height_fn(vec![3, 0]);
+}
+
+// traversal patterns
+
+#[test]
+/// every usable pattern decodes what it encodes, and the zig-zag is the ordinary fence
+fn test_traversal_patterns() {
+    let text = "WEAREDISCOVEREDFLEEATONCE".repeat(2);
+    for rails in 1..8 {
+        let patterns = [Pattern::ZigZag(rails), Pattern::W(rails), Pattern::DoubleZigZag(rails)];
+        for pattern in patterns {
+            let rail_fence = RailFence::with_pattern(pattern.clone());
+            assert_eq!(rail_fence.rails(), rails, "{:?}", pattern);
+            assert_eq!(rail_fence.decode(rail_fence.encode(&text)), text, "{:?}", pattern);
+        }
+        assert_eq!(RailFence::with_pattern(Pattern::ZigZag(rails)).encode(&text), RailFence::new(rails).encode(&text));
+    }
+    let rail_fence = RailFence::with_pattern(Pattern::Sequence(vec![0, 1, 2, 3, 1, 0, 2]));
+    assert_eq!(rail_fence.decode(rail_fence.encode(&text)), text);
+    assert_eq!(RailFence::try_with_pattern(Pattern::Sequence(vec![1, 0, 1])).map(|rail_fence| rail_fence.rails()), Ok(2));
+}
+
+#[test]
+/// a pattern must visit every rail down to its lowest
+fn test_traversal_pattern_errors() {
+    for sequence in [vec![], vec![1], vec![0, 2], vec![0, 1, u32::MAX]] {
+        assert_eq!(RailFence::try_with_pattern(Pattern::Sequence(sequence)).map(|_| ()), Err(RailFenceError::InvalidPattern));
+    }
+    for pattern in [Pattern::ZigZag(0), Pattern::W(0), Pattern::DoubleZigZag(0)] {
+        assert_eq!(RailFence::try_with_pattern(pattern).map(|_| ()), Err(RailFenceError::ZeroRails));
+    }
+}
+
+// rounds
+
+#[test]
+/// several rounds are the fence applied that many times, on every path
+fn test_rounds() {
+    let text = "We are discovered, flee at once";
+    let fences = [
+        RailFence::new(4),
+        RailFence::new(3).with_offset(2).with_turn_policy(TurnPolicy::Dwell),
+        RailFence::with_rail_order(&[2, 0, 3, 1]),
+        RailFence::new(3).with_period_alternating(true).with_period_padding(Some('X')),
+        RailFence::new(3).with_char_filter(CharFilter::PreservePositions),
+        RailFence::with_pattern(Pattern::W(4)),
+    ];
+    for rail_fence in fences {
+        let mut by_hand = rail_fence.encode(text);
+        for rounds in 1..5 {
+            let cipher = rail_fence.clone().with_rounds(rounds);
+            let cipher_text = cipher.encode(text);
+            assert_eq!(cipher_text, by_hand, "{} rounds of {:?}", rounds, rail_fence);
+            assert_eq!(cipher.decode(&cipher_text), rail_fence.decode(rail_fence.encode(text)));
+            assert_eq!(cipher.encode_parallel(text), cipher_text);
+            assert_eq!(cipher.encode_chars(text).collect::<String>(), cipher_text);
+            assert_eq!(cipher.decode_bytes(&cipher.encode_bytes(b"RUSTISGREAT")), b"RUSTISGREAT".to_vec());
+            by_hand = rail_fence.encode(&by_hand);
+        }
+    }
+}
+
+#[test]
+/// the wrappers around encode and decode follow the rounds
+fn test_rounds_wrappers() {
+    let rail_fence = RailFence::new(3).with_rounds(2);
+    let once = RailFence::new(3);
+    let twice = |text: &str| once.encode(once.encode(text));
+    assert_eq!(rail_fence.encode_number(123456789), twice("123456789"));
+    assert_eq!(rail_fence.decode_number(&twice("123456789")), Ok(123456789));
+    assert_eq!(rail_fence.encode_with_capacity("RUSTISGREAT", 64), twice("RUSTISGREAT"));
+    assert_eq!(rail_fence.encode_with_grid("RUSTISGREAT").0, twice("RUSTISGREAT"));
+    assert_eq!(rail_fence.encode_pow2("RUSTISGREAT", 'X'), twice("11:RUSTISGREATXX"));
+    assert_eq!(rail_fence.decode_pow2(&twice("11:RUSTISGREATXX")), Ok(String::from("RUSTISGREAT")));
+    assert_eq!(rail_fence.encode_records(&[String::from("RUST"), String::from("ISGREAT")]).concat(), twice("RUSTISGREAT"));
+    assert_eq!(rail_fence.encode_range("id=RUSTISGREAT;", 3..14), format!("id={};", twice("RUSTISGREAT")));
+}
+
+#[test]
+/// the buffer and layout helpers follow the rounds too
+fn test_rounds_helpers() {
+    let rail_fence = RailFence::new(4).with_rounds(2);
+    let twice = RailFence::new(4).encode(RailFence::new(4).encode("RUSTISGREAT"));
+    let mut out = [0u8; 11];
+    assert_eq!(rail_fence.encode_into_slice(b"RUSTISGREAT", &mut out), Ok(11));
+    assert_eq!(&out[..], twice.as_bytes());
+    let mut buf = String::from("RUSTISGREAT");
+    rail_fence.encode_in_place(&mut buf);
+    assert_eq!(buf, twice);
+    rail_fence.decode_in_place(&mut buf);
+    assert_eq!(buf, "RUSTISGREAT");
+    let (_, offset) = rail_fence.append_char_position(11, 'S');
+    assert_eq!(rail_fence.encode("RUSTISGREATS").chars().nth(offset), Some('S'));
+    let order = RailFence::new(4).cycle_length(11).unwrap() as u32;
+    assert!(RailFence::new(4).with_rounds(order).is_degenerate(11));
+    assert!(RailFence::new(4).with_rounds(0).is_degenerate(11));
+    assert_eq!(RailFence::new(4).with_rounds(0).encode("RUSTISGREAT"), "RUSTISGREAT");
+    assert!(!rail_fence.is_degenerate(11));
+    assert!(!rail_fence.is_compatible_with(&RailFence::new(4)));
+    assert_eq!(RailFence::new(4).with_rounds(u32::MAX).encode("RUSTISGREAT").len(), 11);
+}
+
+// passphrases
+
+#[test]
+/// a passphrase always gives the same rail order and decodes what it encodes
+fn test_passphrase_round_trip() {
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    for rails in 1..8 {
+        for passphrase in &["", "correct horse", "battery staple", "古池"] {
+            let rail_fence = RailFence::from_passphrase(rails, passphrase);
+            let cipher = rail_fence.encode(text);
+            assert_eq!(cipher, RailFence::from_passphrase(rails, passphrase).encode(text));
+            assert_eq!(rail_fence.decode(&cipher), text);
+        }
+    }
+    assert_eq!(RailFence::from_passphrase(4, "correct horse").encode("RUSTISGREAT"), "TARGSIETUSR");
+    assert_eq!(RailFence::from_passphrase(4, "battery staple").encode("RUSTISGREAT"), "SIETRGTAUSR");
+}
+
+// keys
+
+#[test]
+/// keys written as text read back to the same key and fence
+fn test_key_spec_round_trip() {
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    let key: KeySpec = " rails = 4 , order=2013,offset=2".parse().unwrap();
+    assert_eq!(key, KeySpec { rails: 4, offset: 2, order: Some(vec![2, 0, 1, 3]) });
+    assert_eq!(key.to_string(), "rails=4,offset=2,order=2013");
+    assert_eq!(key.to_rail_fence().encode(text), RailFence::with_rail_order(&[2, 0, 1, 3]).with_offset(2).encode(text));
+    assert_eq!("rails=5".parse(), Ok(KeySpec { rails: 5, offset: 0, order: None }));
+    assert_eq!("order=10".parse::<KeySpec>().map(|key| key.rails), Ok(2));
+    let large = KeySpec { rails: 12, offset: 7, order: Some((0..12).rev().collect()) };
+    assert_eq!(large.to_string(), "rails=12,offset=7,order=11-10-9-8-7-6-5-4-3-2-1-0");
+    assert_eq!(large.to_string().parse(), Ok(large));
+    assert_eq!("rails=0".parse::<KeySpec>(), Err(RailFenceError::ZeroRails));
+    assert_eq!("rails=3,offset=4".parse::<KeySpec>(), Err(RailFenceError::OffsetOutOfRange { offset: 4, period: 4 }));
+    for bad in ["", "rails", "rails=x", "rails=3,rails=3", "rails=3,turn=dwell", "rails=3,order=01", "order=011", "order=", "offset=1"] {
+        assert_eq!(bad.parse::<KeySpec>(), Err(RailFenceError::InvalidKey), "{:?}", bad);
+    }
+}
+
+#[test]
+#[cfg(feature = "rand")]
+/// generated keys are usable and survive being written out
+fn test_key_spec_generate() {
+    use rand::SeedableRng;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+    for max_rails in 0..16 {
+        let key = KeySpec::generate(&mut rng, max_rails);
+        assert!((2..=max_rails.max(2)).contains(&key.rails));
+        assert_eq!(key.to_string().parse(), Ok(key.clone()));
+        let rail_fence = key.to_rail_fence();
+        assert_eq!(rail_fence.decode(rail_fence.encode("WEAREDISCOVERED")), "WEAREDISCOVERED");
+    }
+}
+
+// fuzzing
+
+#[test]
+/// the round-trip guarantee holds for any rail count but zero
+fn test_roundtrips_guarantee() {
+    for rails in 1..12 {
+        for text in ["", "A", "WEAREDISCOVEREDFLEEATONCE", "We are discovered — 逃げろ!\n"] {
+            assert!(fuzzing::roundtrips(rails, text), "{} rails, {:?}", rails, text);
+        }
+    }
+    assert!(!fuzzing::roundtrips(0, "WEAREDISCOVERED"));
+}
+
+#[test]
+#[cfg(feature = "arbitrary")]
+/// arbitrary fences and keys are valid and decode what they encode
+fn test_arbitrary_fences_and_keys() {
+    use arbitrary::{Arbitrary, Unstructured};
+    let mut state = 0x2545_f491_u32;
+    for _ in 0..500 {
+        let bytes: Vec<u8> = (0..64)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (state >> 24) as u8
+            })
+            .collect();
+        let mut input = Unstructured::new(&bytes);
+        let rail_fence = RailFence::arbitrary(&mut input).unwrap();
+        assert!((1..=32).contains(&rail_fence.rails()));
+        let text = String::arbitrary(&mut input).unwrap();
+        assert_eq!(rail_fence.try_decode(&rail_fence.try_encode(&text).unwrap()), Ok(text));
+        let key = KeySpec::arbitrary(&mut input).unwrap();
+        assert_eq!(key.to_string().parse(), Ok(key.clone()));
+        let keyed = key.to_rail_fence();
+        assert_eq!(keyed.decode(keyed.encode("WEAREDISCOVERED")), "WEAREDISCOVERED");
+        Direction::arbitrary(&mut input).unwrap();
+        CharFilter::arbitrary(&mut input).unwrap();
+    }
+}
+
+// traces
+
+#[test]
+/// a trace places each character where encoding puts it
+fn test_encode_trace() {
+    let fences = vec![
+        RailFence::new(4),
+        RailFence::new(4).with_offset(3).with_upward_start(true),
+        RailFence::new(3).with_turn_policy(TurnPolicy::Dwell),
+        RailFence::new(3).with_wave(Wave::Sawtooth).with_read_order(ReadOrder::ColumnMajor),
+        RailFence::new(3).with_period_alternating(true).with_grouping(Some(4)),
+        RailFence::with_rail_order(&[2, 0, 1]).with_period_padding(Some('X')),
+        RailFence::new(3).with_char_filter(CharFilter::PreservePositions),
+        RailFence::with_height_fn(vec![2, 3]),
+    ];
+    for rail_fence in fences {
+        let text = "We are discovered, flee at once";
+        let encoded: Vec<char> = rail_fence.encode(text).chars().collect();
+        let trace = rail_fence.encode_trace(text);
+        for placement in &trace {
+            assert_eq!(encoded[placement.output_index], placement.ch, "{:?}", rail_fence);
+            assert!(placement.rail < rail_fence.rails() as usize);
+        }
+        for pair in trace.windows(2) {
+            let heading = if pair[1].rail > pair[0].rail {
+                Heading::Down
+            } else if pair[1].rail < pair[0].rail {
+                Heading::Up
+            } else {
+                Heading::Level
+            };
+            assert_eq!(heading, pair[0].heading);
+        }
+    }
+    let headings: Vec<Heading> = RailFence::new(3).encode_trace("ABCDE").iter().map(|placement| placement.heading).collect();
+    assert_eq!(vec![Heading::Down, Heading::Down, Heading::Up, Heading::Up, Heading::Down], headings);
+    assert!(RailFence::new(3).encode_trace("").is_empty());
+}
+
+// compatibility
+
+#[test]
+/// fences with the same layout settings are compatible and decode each other
+fn test_compatible_configurations() {
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    let pairs = [
+        (RailFence::new(3), RailFence::new(3)),
+        (RailFence::new(4).with_offset(2), RailFence::new(4).with_offset(8)),
+        (RailFence::new(3).with_turn_policy(TurnPolicy::Dwell), RailFence::new(3).with_turn_policy(TurnPolicy::Dwell)),
+        (RailFence::new(5).with_period_alternating(true), RailFence::new(5).with_period_alternating(true)),
+        (RailFence::from_passphrase(4, "key"), RailFence::from_passphrase(4, "key")),
+        (RailFence::new(3).with_case(Case::Upper), RailFence::new(3)),
+        (RailFence::new(3).with_whitespace_stripped(true), RailFence::new(3)),
+    ];
+    for (encoder, decoder) in &pairs {
+        assert!(encoder.is_compatible_with(decoder));
+        assert!(decoder.is_compatible_with(encoder));
+        assert_eq!(decoder.decode(encoder.encode(text)), text);
+    }
+    let scheduled = RailFence::with_schedule(3, |position| (position % 3) as u32);
+    assert!(scheduled.is_compatible_with(&scheduled.clone()));
+}
+
+#[test]
+/// any difference in the layout or decoding settings makes fences incompatible
+fn test_incompatible_configurations() {
+    let base = RailFence::new(3);
+    let others = [
+        RailFence::new(4),
+        RailFence::new(3).with_offset(1),
+        RailFence::new(3).with_turn_policy(TurnPolicy::Dwell),
+        RailFence::new(3).with_read_order(ReadOrder::ColumnMajor),
+        RailFence::new(3).with_period_alternating(true),
+        RailFence::from_passphrase(3, "key"),
+        RailFence::with_schedule(3, |position| (position % 3) as u32),
+        RailFence::new(3).with_period_padding(Some('X')),
+        RailFence::new(3).with_grouping(Some(5)),
+        RailFence::new(3).with_char_filter(CharFilter::PreservePositions),
+        RailFence::new(3).with_whitespace_ignored(true),
+    ];
+    for other in &others {
+        assert!(!base.is_compatible_with(other));
+        assert!(!other.is_compatible_with(&base));
+    }
+    let schedule = |position: usize| (position % 3) as u32;
+    assert!(!RailFence::with_schedule(3, schedule).is_compatible_with(&RailFence::with_schedule(3, schedule)));
+    let padded = RailFence::new(3).with_period_padding(Some('X'));
+    assert_ne!(base.decode(padded.encode("RUSTISGREAT")), "RUSTISGREAT");
+    assert!(!padded.is_compatible_with(&RailFence::new(3).with_period_padding(Some('Q'))));
+}
+
+// alphabets
+
+#[test]
+/// messages within the alphabet encode as usual
+fn test_alphabet_accepts_letters() {
+    let uppercase = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    for rails in 1..6 {
+        let rail_fence = RailFence::with_alphabet(rails, uppercase);
+        let text = "WEAREDISCOVEREDFLEEATONCE";
+        assert_eq!(rail_fence.try_encode(text), Ok(RailFence::new(rails).encode(text)));
+        assert_eq!(rail_fence.try_encode(""), Ok(String::new()));
+    }
+    assert_eq!(RailFence::new(3).try_encode("any thing, 古池"), Ok(RailFence::new(3).encode("any thing, 古池")));
+}
+
+#[test]
+/// the first character outside the alphabet is reported
+fn test_alphabet_rejects_other_characters() {
+    let rail_fence = RailFence::with_alphabet(3, "ABCDEFGHIJKLMNOPQRSTUVWXYZ");
+    assert_eq!(rail_fence.try_encode("WEARE discovered"), Err(RailFenceError::InvalidCharacter { ch: ' ', position: 5 }));
+    assert_eq!(rail_fence.try_encode("古池"), Err(RailFenceError::InvalidCharacter { ch: '古', position: 0 }));
+    assert_eq!(rail_fence.try_encode("FLEEA-Z"), Err(RailFenceError::InvalidCharacter { ch: '-', position: 5 }));
+    assert_eq!(rail_fence.encode("WEARE discovered"), RailFence::new(3).encode("WEARE discovered"));
+    assert_eq!(RailFence::with_alphabet(0, "AB").try_encode("AB"), Err(RailFenceError::ZeroRails));
+}
+
+// waves
+
+#[test]
+/// the sawtooth wave wraps from the bottom rail to the top
+fn test_sawtooth_wave_layout() {
+    let rail_fence = RailFence::new(4).with_wave(Wave::Sawtooth);
+    assert_eq!(rail_fence.period(), 4);
+    assert_eq!(rail_fence.rail_lengths(11), vec![3, 3, 3, 2]);
+    assert_eq!(rail_fence.encode("RUSTISGREAT"), "RIEUSASGTTR");
+    assert_eq!(rail_fence.encode("RUSTISGREAT"), RailFence::with_schedule(4, |position| (position % 4) as u32).encode("RUSTISGREAT"));
+    let dwelling = RailFence::new(4).with_wave(Wave::Sawtooth).with_turn_policy(TurnPolicy::Dwell);
+    assert_eq!(dwelling.encode("RUSTISGREAT"), "RIEUSASGTTR");
+    assert!(!rail_fence.is_compatible_with(&RailFence::new(4)));
+}
+
+#[test]
+/// the sawtooth wave decodes what it encodes
+fn test_sawtooth_wave_round_trip() {
+    for rails in 1..8 {
+        for offset in 0..rails as usize {
+            let rail_fence = RailFence::new(rails).with_wave(Wave::Sawtooth).with_offset(offset);
+            for text in &["", "WEAREDISCOVEREDFLEEATONCE", "古池蛙飛び込む水の音"] {
+                assert_eq!(rail_fence.decode(rail_fence.encode(text)), *text);
+            }
+        }
+    }
+}
+
+#[test]
+/// the builder rejects a sawtooth wave with options it would ignore
+fn test_builder_sawtooth_conflicts() {
+    let builder = RailFenceBuilder::new(3).wave(Wave::Sawtooth).turn_policy(TurnPolicy::Dwell).offset(3);
+    assert_eq!(
+        builder.validate(),
+        Err(vec![
+            RailFenceError::ConflictingOptions { first: "a sawtooth wave", second: "a turn policy" },
+            RailFenceError::OffsetOutOfRange { offset: 3, period: 3 },
+        ])
+    );
+    let builder = RailFenceBuilder::new(3).schedule(|position| (position % 3) as u32).wave(Wave::Sawtooth);
+    assert_eq!(
+        builder.validate(),
+        Err(vec![RailFenceError::ConflictingOptions { first: "a custom schedule", second: "a sawtooth wave" }])
+    );
+    assert!(RailFenceBuilder::new(3).wave(Wave::Sawtooth).offset(2).build().is_ok());
+}
+
+// appending
+
+#[test]
+/// inserting each typed character where append_char_position says builds up the cipher-text
+fn test_append_char_position() {
+    let text = "WEAREDISCOVEREDFLEEATONCE古池";
+    let fences = [
+        RailFence::new(1),
+        RailFence::new(3),
+        RailFence::new(4).with_offset(2),
+        RailFence::new(4).with_turn_policy(TurnPolicy::Dwell),
+        RailFence::new(4).with_wave(Wave::Sawtooth),
+        RailFence::new(4).with_read_order(ReadOrder::ColumnMajor),
+        RailFence::new(4).with_period_alternating(true),
+        RailFence::from_passphrase(5, "key"),
+        RailFence::with_schedule(3, |position| (position * position % 3) as u32),
+    ];
+    for rail_fence in &fences {
+        let mut shown: Vec<char> = Vec::new();
+        for (typed, c) in text.chars().enumerate() {
+            let (rail, offset) = rail_fence.append_char_position(typed, c);
+            assert!(rail < rail_fence.rails());
+            shown.insert(offset, c);
+            let prefix: String = text.chars().take(typed + 1).collect();
+            assert_eq!(shown.iter().collect::<String>(), rail_fence.encode(&prefix));
+            assert_eq!(rail_fence.encode_rails(&prefix)[rail as usize].chars().last(), Some(c));
+        }
+    }
+}
+
+// svg
+
+#[test]
+#[cfg(feature = "svg")]
+/// every placed character is drawn once, in the cell of its rail and position
+fn test_to_svg_cells() {
+    let svg = RailFence::new(4).to_svg("RUSTISGREAT");
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"264\" height=\"96\""));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert_eq!(svg.matches("<rect").count(), 11);
+    assert_eq!(svg.matches("<text").count(), 11);
+    assert!(svg.contains("<text x=\"12\" y=\"12\">R</text>"));
+    assert!(svg.contains("<text x=\"36\" y=\"36\">U</text>"));
+    assert!(svg.contains("<text x=\"84\" y=\"84\">T</text>"));
+    assert!(svg.contains("<text x=\"252\" y=\"60\">T</text>"));
+}
+
+#[test]
+#[cfg(feature = "svg")]
+/// the style is applied and markup in the text or font is escaped
+fn test_to_svg_style_and_escaping() {
+    let style = SvgStyle { cell_size: 10, font_size: 8, font_family: String::from("\"Fira\" & co") };
+    let svg = RailFence::new(2).to_svg_with("<a&b>", &style);
+    assert!(svg.contains("width=\"50\" height=\"20\""));
+    assert!(svg.contains("font-family=\"&quot;Fira&quot; &amp; co\" font-size=\"8\""));
+    assert!(svg.contains(">&lt;</text>") && svg.contains(">&amp;</text>") && svg.contains(">&gt;</text>"));
+    assert!(!svg.contains("<a"));
+    assert_eq!(RailFence::new(3).to_svg("").matches("<text").count(), 0);
+}
+
+// keyed rail orders
+
+#[test]
+/// the rails are read in the keyed order and decode back
+fn test_rail_order_reads_rails_in_key_order() {
+    let text = "WEAREDISCOVEREDFLEEATONCE";
+    for order in [vec![0], vec![1, 0], vec![2, 0, 3, 1], vec![4, 3, 2, 1, 0], vec![1, 3, 0, 4, 2]] {
+        let rail_fence = RailFence::with_rail_order(&order);
+        assert_eq!(rail_fence.rails() as usize, order.len());
+        let rails = RailFence::new(order.len() as u32).encode_rails(text);
+        let expected: String = order.iter().map(|&rail| rails[rail as usize].as_str()).collect();
+        assert_eq!(rail_fence.encode(text), expected);
+        assert_eq!(rail_fence.decode(&expected), text);
+    }
+}
+
+#[test]
+#[should_panic]
+/// an order that repeats a rail is rejected
+fn test_rail_order_repeated_rail() {
+    RailFence::with_rail_order(&[0, 1, 1]);
+}
+
+#[test]
+#[should_panic]
+/// an order that names a missing rail is rejected
+fn test_rail_order_missing_rail() {
+    RailFence::with_rail_order(&[0, 3, 1]);
+}
+
+#[test]
+/// a keyed order only covers its own rails, so the rail count is fixed
+fn test_rail_order_keeps_rail_count() {
+    let mut rail_fence = RailFence::with_rail_order(&[2, 0, 1]);
+    assert_eq!(
+        rail_fence.set_rails(4),
+        Err(RailFenceError::ConflictingOptions { first: "a rail order", second: "a different number of rails" })
+    );
+    assert_eq!(rail_fence.rails(), 3);
+    assert_eq!(rail_fence.set_rails(3), Ok(()));
+    assert!(RailFence::from_passphrase(3, "key").set_rails(5).is_err());
+}
+
+// serialization
+
+#[test]
+#[cfg(feature = "serde")]
+/// a stored fence loads back with the same settings
+fn test_serde_round_trip() {
+    let fences = [
+        RailFence::new(3),
+        RailFence::new(4).with_offset(2).with_turn_policy(TurnPolicy::Dwell).with_upward_start(true),
+        RailFence::new(5).with_wave(Wave::Sawtooth).with_read_order(ReadOrder::ColumnMajor),
+        RailFence::from_passphrase(4, "key").with_case(Case::Upper).with_whitespace_stripped(true),
+        RailFence::with_alphabet(3, "ABC").with_period_alternating(true),
+        RailFence::new(4).with_rounds(3),
+    ];
+    for rail_fence in &fences {
+        let json = serde_json::to_string(rail_fence).unwrap();
+        let loaded: RailFence = serde_json::from_str(&json).unwrap();
+        assert!(loaded.is_compatible_with(rail_fence), "{}", json);
+        assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+        assert_eq!(loaded.encode("We are discovered"), rail_fence.encode("We are discovered"));
+    }
+    let loaded: RailFence = serde_json::from_str(r#"{"rails": 4, "turn": "Dwell"}"#).unwrap();
+    assert!(loaded.is_compatible_with(&RailFence::new(4).with_turn_policy(TurnPolicy::Dwell)));
+}
+
+#[test]
+#[cfg(feature = "serde")]
+/// configurations that could not be built fail to load
+fn test_serde_rejects_invalid_configs() {
+    for json in &[
+        r#"{"rails": 0}"#,
+        r#"{"rails": 3, "offset": 4}"#,
+        r#"{"rails": 3, "wave": "Sawtooth", "turn": "Dwell"}"#,
+        r#"{"rails": 3, "rail_order": [0, 0, 1]}"#,
+        r#"{"rails": 3, "rail_order": [0, 1]}"#,
+        r#"{"rails": 3, "colour": "red"}"#,
+        r#"{"offset": 1}"#,
+    ] {
+        assert!(serde_json::from_str::<RailFence>(json).is_err(), "{}", json);
+    }
+    let error = serde_json::from_str::<RailFence>(r#"{"rails": 0}"#).unwrap_err();
+    assert!(error.to_string().contains("at least one rail"));
+    let scheduled = RailFence::with_schedule(3, |position| (position % 3) as u32);
+    assert!(serde_json::to_string(&scheduled).is_err());
+}
+
+#[test]
+#[cfg(feature = "wasm")]
+/// the JavaScript bindings agree with the fence methods
+fn test_wasm_bindings() {
+    let text = "THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG AND THEN RUNS INTO THE FOREST";
+    let cipher_text = wasm::encode(text, 5).ok().unwrap();
+    assert_eq!(cipher_text, RailFence::new(5).encode(text));
+    assert_eq!(wasm::decode(&cipher_text, 5).ok().unwrap(), text);
+    assert_eq!(wasm::crack(&cipher_text, 10).ok().unwrap(), text);
+}
+
+#[test]
+#[cfg(feature = "ffi")]
+/// the C bindings round trip a message and reject bad arguments
+fn test_ffi_round_trip() {
+    use std::ffi::{CStr, CString};
+    unsafe {
+        assert!(ffi::rail_fence_new(0).is_null());
+        let fence = ffi::rail_fence_new(4);
+        let text = CString::new("RUSTISGREAT").unwrap();
+        let encoded = ffi::rail_fence_encode(fence, text.as_ptr());
+        assert_eq!(CStr::from_ptr(encoded).to_str(), Ok("RGUSRSIETTA"));
+        let decoded = ffi::rail_fence_decode(fence, encoded);
+        assert_eq!(CStr::from_ptr(decoded).to_str(), Ok("RUSTISGREAT"));
+        let not_utf8 = CString::new(vec![0xff, 0xfe]).unwrap();
+        assert!(ffi::rail_fence_encode(fence, not_utf8.as_ptr()).is_null());
+        assert!(ffi::rail_fence_decode(std::ptr::null(), text.as_ptr()).is_null());
+        ffi::rail_fence_string_free(encoded);
+        ffi::rail_fence_string_free(decoded);
+        ffi::rail_fence_free(fence);
+    }
+}
+
+#[test]
+#[cfg(feature = "python")]
+/// the Python class encodes like the fence and rejects zero rails
+fn test_python_bindings() {
+    use pyo3::prelude::*;
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let class = py.get_type_bound::<python::PyRailFence>();
+        let fence = class.call1((4,)).unwrap();
+        let cipher_text: String = fence.call_method1("encode", ("RUSTISGREAT",)).unwrap().extract().unwrap();
+        assert_eq!(cipher_text, "RGUSRSIETTA");
+        let clear_text: String = fence.call_method1("decode", (cipher_text,)).unwrap().extract().unwrap();
+        assert_eq!(clear_text, "RUSTISGREAT");
+        assert_eq!(fence.getattr("rails").unwrap().extract::<u32>().unwrap(), 4);
+        assert!(class.call1((0,)).unwrap_err().is_instance_of::<pyo3::exceptions::PyValueError>(py));
+    });
+}